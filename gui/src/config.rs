@@ -1,9 +1,20 @@
 //! Centralized configuration and constants for the application.
 
+/// The application ID with its parts joined by `$sep`.
+///
+/// The ID and the resource prefix are both spelled from this one place.
+macro_rules! app_id {
+    ($sep:literal) => {
+        concat!("xyz", $sep, "xerolinux", $sep, "xero-toolkit")
+    };
+}
+
 /// Application information constants.
 pub mod app_info {
     pub const NAME: &str = "xero-toolkit";
-    pub const ID: &str = "xyz.xerolinux.xero-toolkit";
+    /// Name shown to the user in titles and dialogs.
+    pub const DISPLAY_NAME: &str = "Xero Toolkit";
+    pub const ID: &str = app_id!(".");
     pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 }
 
@@ -11,6 +22,8 @@ pub mod app_info {
 pub mod sidebar {
     pub const MIN_WIDTH: i32 = 200;
     pub const MAX_WIDTH: i32 = 400;
+    /// Width of the sidebar collapsed to tab icons
+    pub const COMPACT_WIDTH: i32 = 64;
 }
 
 /// External links.
@@ -19,6 +32,7 @@ pub mod links {
     pub const YOUTUBE: &str = "https://www.youtube.com/@XeroLinux";
     pub const WEBSITE: &str = "https://xerolinux.xyz/";
     pub const DONATE: &str = "https://ko-fi.com/xerolinux";
+    pub const REPOSITORY: &str = env!("CARGO_PKG_REPOSITORY");
 }
 
 /// Binary paths for system executables.
@@ -64,6 +78,30 @@ pub mod paths {
     pub fn system_autostart() -> PathBuf {
         PathBuf::from(SYSTEM_AUTOSTART)
     }
+
+    /// Get the user configuration file.
+    pub fn config_file() -> PathBuf {
+        dirs::config_dir()
+            .unwrap_or_else(|| PathBuf::from("~/.config"))
+            .join(super::app_info::NAME)
+            .join("config.toml")
+    }
+
+    /// Get the file where UI state is kept between restarts.
+    pub fn state_file() -> PathBuf {
+        dirs::config_dir()
+            .unwrap_or_else(|| PathBuf::from("~/.config"))
+            .join(super::app_info::NAME)
+            .join("state.toml")
+    }
+
+    /// Get the directory where task runner logs are written.
+    pub fn logs() -> PathBuf {
+        dirs::cache_dir()
+            .unwrap_or_else(|| PathBuf::from("~/.cache"))
+            .join(super::app_info::NAME)
+            .join("logs")
+    }
 }
 
 /// Cached environment variables read at startup.
@@ -105,6 +143,145 @@ pub mod env {
     }
 }
 
+/// User preferences read from `~/.config/xero-toolkit/config.toml`.
+pub mod user {
+    use log::warn;
+    use serde::{Deserialize, Serialize};
+    use std::sync::OnceLock;
+
+    /// When seasonal overlay effects are shown.
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    pub enum SeasonalMode {
+        /// Never show effects
+        Off,
+        /// Show effects during their season
+        #[default]
+        Auto,
+        /// Show effects all year round
+        Always,
+    }
+
+    /// Color scheme of the application.
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    pub enum ColorScheme {
+        /// Follow the system preference
+        #[default]
+        System,
+        /// Always light
+        Light,
+        /// Always dark
+        Dark,
+    }
+
+    /// Options of the user configuration file.
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(default)]
+    pub struct UserConfig {
+        /// AUR helper to use instead of the first one found
+        pub aur_helper: Option<String>,
+        /// Privilege backend (`pkexec`, `sudo` or `run0`), overridden by the environment
+        pub privilege_backend: Option<String>,
+        pub seasonal_effects: SeasonalMode,
+        /// Redraw rate of seasonal effects, 30 FPS if unset
+        pub seasonal_fps: Option<u32>,
+        /// Run AUR helper steps without asking; if off, each one is confirmed first
+        pub noconfirm: bool,
+        /// Let the AUR helper prompt before changing packages instead of passing `--noconfirm`
+        pub interactive: bool,
+        /// Lines kept in the task output view, 10000 if unset; the log keeps everything
+        pub output_line_limit: Option<usize>,
+        /// Send a desktop notification when a run ends while its window is not focused
+        pub notify_on_completion: bool,
+        /// Light or dark appearance, following the system if unset
+        pub color_scheme: ColorScheme,
+        /// Page ids in sidebar order; pages left out follow in their default order
+        pub tab_order: Vec<String>,
+        /// Page ids whose sidebar tabs are hidden
+        pub hidden_tabs: Vec<String>,
+        /// Collapse the sidebar to tab icons, expanding it while hovered
+        pub compact_sidebar: bool,
+    }
+
+    impl Default for UserConfig {
+        fn default() -> Self {
+            Self {
+                aur_helper: None,
+                privilege_backend: None,
+                seasonal_effects: SeasonalMode::default(),
+                seasonal_fps: None,
+                noconfirm: true,
+                interactive: false,
+                output_line_limit: None,
+                notify_on_completion: true,
+                color_scheme: ColorScheme::default(),
+                tab_order: Vec::new(),
+                hidden_tabs: Vec::new(),
+                compact_sidebar: false,
+            }
+        }
+    }
+
+    impl UserConfig {
+        /// Read the configuration file.
+        ///
+        /// A missing file gives the defaults; an invalid one is logged and ignored.
+        pub fn load() -> Self {
+            let path = super::paths::config_file();
+            let Ok(contents) = std::fs::read_to_string(&path) else {
+                return Self::default();
+            };
+
+            toml::from_str(&contents).unwrap_or_else(|e| {
+                warn!("Ignoring invalid config file {}: {}", path.display(), e);
+                Self::default()
+            })
+        }
+
+        /// Write the configuration file, creating the config directory if needed.
+        pub fn save(&self) -> anyhow::Result<()> {
+            let path = super::paths::config_file();
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+
+            std::fs::write(&path, toml::to_string(self)?)?;
+            Ok(())
+        }
+    }
+
+    static CONFIG: OnceLock<UserConfig> = OnceLock::new();
+
+    /// Get the user configuration, reading it on first use.
+    pub fn get() -> &'static UserConfig {
+        CONFIG.get_or_init(UserConfig::load)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_user_config_defaults_and_round_trip() {
+            let config: UserConfig = toml::from_str("aur_helper = \"yay\"").unwrap();
+            assert_eq!(config.aur_helper.as_deref(), Some("yay"));
+            assert!(config.noconfirm);
+            assert_eq!(config.seasonal_effects, SeasonalMode::Auto);
+            assert_eq!(config.color_scheme, ColorScheme::System);
+            assert_eq!(
+                toml::from_str::<UserConfig>("color_scheme = \"dark\"")
+                    .unwrap()
+                    .color_scheme,
+                ColorScheme::Dark
+            );
+
+            let contents = toml::to_string(&config).unwrap();
+            assert_eq!(toml::from_str::<UserConfig>(&contents).unwrap(), config);
+        }
+    }
+}
+
 /// Debug environment variables for seasonal effects.
 pub mod seasonal_debug {
     pub const ENABLE_SNOW: &str = "XERO_TOOLKIT_ENABLE_SNOW";
@@ -130,44 +307,81 @@ pub mod seasonal_debug {
     }
 }
 
+/// Debug environment variables for the task runner.
+pub mod debug {
+    pub const DRY_RUN: &str = "XERO_TOOLKIT_DRY_RUN";
+
+    /// Check if dry-run mode was requested via the environment.
+    pub fn is_dry_run() -> bool {
+        super::seasonal_debug::check_effect_env(DRY_RUN).unwrap_or(false)
+    }
+}
+
 /// UI resource paths for GResource files.
 pub mod resources {
+    /// Path of a bundled resource.
+    ///
+    /// The prefix is [`super::app_info::ID`] written as a path, matching the
+    /// `prefix` in `resources.gresource.xml`.
+    macro_rules! resource {
+        ($path:literal) => {
+            concat!("/", app_id!("/"), "/", $path)
+        };
+    }
+
     /// Main application window UI.
-    pub const MAIN_UI: &str = "/xyz/xerolinux/xero-toolkit/ui/main.ui";
+    pub const MAIN_UI: &str = resource!("ui/main.ui");
 
     /// Icons resource path.
-    pub const ICONS: &str = "/xyz/xerolinux/xero-toolkit/icons";
+    pub const ICONS: &str = resource!("icons");
 
     /// CSS stylesheet resource path.
-    pub const CSS: &str = "/xyz/xerolinux/xero-toolkit/css/style.css";
+    pub const CSS: &str = resource!("css/style.css");
 
     /// Dialog UI resources.
     pub mod dialogs {
-        pub const ABOUT: &str = "/xyz/xerolinux/xero-toolkit/ui/dialogs/about_dialog.ui";
-        pub const DEPENDENCY_ERROR: &str =
-            "/xyz/xerolinux/xero-toolkit/ui/dialogs/dependency_error_dialog.ui";
-        pub const DOWNLOAD: &str = "/xyz/xerolinux/xero-toolkit/ui/dialogs/download_dialog.ui";
-        pub const DOWNLOAD_SETUP: &str =
-            "/xyz/xerolinux/xero-toolkit/ui/dialogs/download_setup_dialog.ui";
-        pub const SELECTION: &str = "/xyz/xerolinux/xero-toolkit/ui/dialogs/selection_dialog.ui";
-        pub const TASK_LIST: &str = "/xyz/xerolinux/xero-toolkit/ui/dialogs/task_list_dialog.ui";
-        pub const TERMINAL: &str = "/xyz/xerolinux/xero-toolkit/ui/dialogs/terminal_dialog.ui";
-        pub const WARNING: &str = "/xyz/xerolinux/xero-toolkit/ui/dialogs/warning_dialog.ui";
-        pub const XEROLINUX_CHECK: &str =
-            "/xyz/xerolinux/xero-toolkit/ui/dialogs/xerolinux_check_dialog.ui";
+        pub const ABOUT: &str = resource!("ui/dialogs/about_dialog.ui");
+        pub const DEPENDENCY_ERROR: &str = resource!("ui/dialogs/dependency_error_dialog.ui");
+        pub const DOWNLOAD: &str = resource!("ui/dialogs/download_dialog.ui");
+        pub const DOWNLOAD_SETUP: &str = resource!("ui/dialogs/download_setup_dialog.ui");
+        pub const SELECTION: &str = resource!("ui/dialogs/selection_dialog.ui");
+        pub const TASK_LIST: &str = resource!("ui/dialogs/task_list_dialog.ui");
+        pub const TERMINAL: &str = resource!("ui/dialogs/terminal_dialog.ui");
+        pub const WARNING: &str = resource!("ui/dialogs/warning_dialog.ui");
+        pub const XEROLINUX_CHECK: &str = resource!("ui/dialogs/xerolinux_check_dialog.ui");
     }
 
     /// Page/tab UI resources.
     pub mod tabs {
-        pub const CONTAINERS_VMS: &str = "/xyz/xerolinux/xero-toolkit/ui/tabs/containers_vms.ui";
-        pub const CUSTOMIZATION: &str = "/xyz/xerolinux/xero-toolkit/ui/tabs/customization.ui";
-        pub const DRIVERS: &str = "/xyz/xerolinux/xero-toolkit/ui/tabs/drivers.ui";
-        pub const GAMESCOPE: &str = "/xyz/xerolinux/xero-toolkit/ui/tabs/gamescope.ui";
-        pub const GAMING_TOOLS: &str = "/xyz/xerolinux/xero-toolkit/ui/tabs/gaming_tools.ui";
-        pub const MAIN_PAGE: &str = "/xyz/xerolinux/xero-toolkit/ui/tabs/main_page.ui";
-        pub const MULTIMEDIA_TOOLS: &str =
-            "/xyz/xerolinux/xero-toolkit/ui/tabs/multimedia_tools.ui";
-        pub const SERVICING_SYSTEM_TWEAKS: &str =
-            "/xyz/xerolinux/xero-toolkit/ui/tabs/servicing_system_tweaks.ui";
+        pub const CONTAINERS_VMS: &str = resource!("ui/tabs/containers_vms.ui");
+        pub const CUSTOMIZATION: &str = resource!("ui/tabs/customization.ui");
+        pub const DRIVERS: &str = resource!("ui/tabs/drivers.ui");
+        pub const GAMESCOPE: &str = resource!("ui/tabs/gamescope.ui");
+        pub const GAMING_TOOLS: &str = resource!("ui/tabs/gaming_tools.ui");
+        pub const KERNEL_MANAGER_SCX: &str = resource!("ui/tabs/kernel_manager_scx.ui");
+        pub const MAIN_PAGE: &str = resource!("ui/tabs/main_page.ui");
+        pub const MULTIMEDIA_TOOLS: &str = resource!("ui/tabs/multimedia_tools.ui");
+        pub const SERVICING_SYSTEM_TWEAKS: &str = resource!("ui/tabs/servicing_system_tweaks.ui");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resource_paths_match_app_id() {
+        let prefix = format!("/{}", app_info::ID.replace('.', "/"));
+        for path in [
+            resources::MAIN_UI,
+            resources::CSS,
+            resources::dialogs::ABOUT,
+            resources::tabs::MAIN_PAGE,
+        ] {
+            assert!(path.starts_with(&format!("{}/", prefix)), "{}", path);
+        }
+
+        let gresource = include_str!("../resources/resources.gresource.xml");
+        assert!(gresource.contains(&format!("prefix=\"{}\"", prefix)));
     }
 }
@@ -0,0 +1,103 @@
+//! Audit trail of executed commands in the systemd journal.
+//!
+//! Every command the toolkit runs is logged together with its exit code,
+//! tagged with the application ID, so admins can see what was changed on the
+//! system after the GUI is gone (`journalctl -t xyz.xerolinux.xero-toolkit`).
+//! Entries go straight to the journal's native socket; if it is unavailable
+//! they are only in the application log.
+
+use crate::config::app_info;
+use log::debug;
+use std::os::unix::net::UnixDatagram;
+
+/// Socket accepting entries in the journal's native protocol.
+const JOURNAL_SOCKET: &str = "/run/systemd/journal/socket";
+
+/// Syslog priority of successful commands.
+const PRIORITY_INFO: &str = "6";
+
+/// Syslog priority of failed commands.
+const PRIORITY_WARNING: &str = "4";
+
+/// Log that a command is about to run.
+///
+/// `command_line` is the fully resolved command, including any privilege
+/// escalation prefix.
+pub fn log_command_start(description: &str, command_line: &str) {
+    send(&[
+        ("MESSAGE", &format!("Running: {}", command_line)),
+        ("PRIORITY", PRIORITY_INFO),
+        ("XERO_TOOLKIT_STEP", description),
+        ("XERO_TOOLKIT_COMMAND", command_line),
+    ]);
+}
+
+/// Log the outcome of a command, with `None` if it had no exit code.
+pub fn log_command_result(description: &str, exit_code: Option<i32>) {
+    let (priority, outcome) = match exit_code {
+        Some(0) => (PRIORITY_INFO, "succeeded".to_string()),
+        Some(code) => (PRIORITY_WARNING, format!("failed with exit code {}", code)),
+        None => (PRIORITY_WARNING, "failed without exit code".to_string()),
+    };
+    let exit_code = exit_code.map(|code| code.to_string()).unwrap_or_default();
+
+    send(&[
+        ("MESSAGE", &format!("{} {}", description, outcome)),
+        ("PRIORITY", priority),
+        ("XERO_TOOLKIT_STEP", description),
+        ("XERO_TOOLKIT_EXIT_CODE", &exit_code),
+    ]);
+}
+
+/// Send an entry with the app ID as identifier to the journal.
+fn send(fields: &[(&str, &str)]) {
+    let mut entry = encode_entry(&[("SYSLOG_IDENTIFIER", app_info::ID)]);
+    entry.extend(encode_entry(fields));
+
+    let result = UnixDatagram::unbound().and_then(|socket| socket.send_to(&entry, JOURNAL_SOCKET));
+    if let Err(e) = result {
+        debug!("Failed to write audit entry to the journal: {}", e);
+    }
+}
+
+/// Encode fields in the journal's native protocol.
+///
+/// Values containing a newline are sent length-prefixed, the others as
+/// `KEY=value` lines.
+fn encode_entry(fields: &[(&str, &str)]) -> Vec<u8> {
+    let mut entry = Vec::new();
+    for (key, value) in fields {
+        entry.extend_from_slice(key.as_bytes());
+        if value.contains('\n') {
+            entry.push(b'\n');
+            entry.extend_from_slice(&(value.len() as u64).to_le_bytes());
+        } else {
+            entry.push(b'=');
+        }
+        entry.extend_from_slice(value.as_bytes());
+        entry.push(b'\n');
+    }
+    entry
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_entry() {
+        assert_eq!(
+            encode_entry(&[("MESSAGE", "hello"), ("PRIORITY", "6")]),
+            b"MESSAGE=hello\nPRIORITY=6\n"
+        );
+
+        // Multi-line values are length-prefixed
+        let mut expected = b"XERO_TOOLKIT_COMMAND\n".to_vec();
+        expected.extend_from_slice(&8u64.to_le_bytes());
+        expected.extend_from_slice(b"a\nb && c\n");
+        assert_eq!(
+            encode_entry(&[("XERO_TOOLKIT_COMMAND", "a\nb && c")]),
+            expected
+        );
+    }
+}
@@ -1,81 +1,164 @@
 //! AUR helper detection and management.
 //!
-//! This module handles detection and access to AUR helpers (paru/yay)
-//! used for installing packages from the Arch User Repository.
+//! This module handles detection and access to AUR helpers (paru, yay,
+//! pikaur and aura) used for installing packages from the Arch User Repository,
+//! and translates pacman-style arguments for helpers with a different syntax.
 
-use log::debug;
+use log::{debug, warn};
 use std::env;
+use std::ffi::OsStr;
 use std::os::unix::fs::PermissionsExt;
 use std::path::PathBuf;
-use std::sync::OnceLock;
+use std::sync::RwLock;
 
-/// Global storage for the detected AUR helper.
-static AUR_HELPER: OnceLock<String> = OnceLock::new();
+/// Global storage for the selected AUR helper.
+static AUR_HELPER: RwLock<Option<&'static str>> = RwLock::new(None);
 
 /// Priority order for AUR helper detection.
-const AUR_HELPERS: [&str; 2] = ["paru", "yay"];
+pub const AUR_HELPERS: [&str; 4] = ["paru", "yay", "pikaur", "aura"];
 
 /// Detect and return the available AUR helper.
 ///
-/// Searches for AUR helpers in priority order (paru, then yay).
+/// Searches for AUR helpers in priority order (paru, yay, pikaur, then aura).
 /// Returns the first found helper or None if none are available.
 pub fn detect() -> Option<&'static str> {
-    for &helper in AUR_HELPERS.iter() {
-        if is_executable_in_path(helper) {
-            debug!("Found AUR helper: {}", helper);
-            return Some(helper);
+    detect_with(is_executable_in_path)
+}
+
+/// Detect the first helper in priority order for which `is_installed` holds.
+fn detect_with(is_installed: impl Fn(&str) -> bool) -> Option<&'static str> {
+    let helper = AUR_HELPERS.into_iter().find(|helper| is_installed(helper));
+    match helper {
+        Some(helper) => debug!("Found AUR helper: {}", helper),
+        None => debug!("No AUR helper found"),
+    }
+    helper
+}
+
+/// List all installed AUR helpers, in priority order.
+pub fn installed() -> Vec<&'static str> {
+    installed_with(is_executable_in_path)
+}
+
+/// List the helpers for which `is_installed` holds, in priority order.
+fn installed_with(is_installed: impl Fn(&str) -> bool) -> Vec<&'static str> {
+    AUR_HELPERS
+        .into_iter()
+        .filter(|helper| is_installed(helper))
+        .collect()
+}
+
+/// Pick the helper to use: the configured one if it is supported and
+/// installed, otherwise the first installed one in priority order.
+fn choose(configured: Option<&str>, is_installed: impl Fn(&str) -> bool) -> Option<&'static str> {
+    if let Some(name) = configured {
+        match AUR_HELPERS.into_iter().find(|helper| *helper == name) {
+            Some(helper) if is_installed(helper) => return Some(helper),
+            _ => warn!(
+                "Configured AUR helper '{}' is not supported or not installed, detecting one instead",
+                name
+            ),
         }
     }
 
-    debug!("No AUR helper found");
-    None
+    detect_with(is_installed)
 }
 
 /// Initialize the global AUR helper.
 ///
-/// Should be called once at startup after dependency checks pass.
-/// Returns true if an AUR helper was found and initialized.
+/// The helper set in the user config takes priority over detection. Should be
+/// called once at startup after dependency checks pass. Returns true if an
+/// AUR helper was found and initialized.
 pub fn init() -> bool {
-    if let Some(helper) = detect() {
-        let _ = AUR_HELPER.set(helper.to_string());
-        true
-    } else {
-        false
+    let configured = crate::config::user::get().aur_helper.as_deref();
+    let helper = choose(configured, is_executable_in_path);
+    if let Some(helper) = helper {
+        debug!("Using AUR helper: {}", helper);
+        set(helper);
     }
+    helper.is_some()
+}
+
+/// Select the AUR helper used for subsequent AUR commands.
+pub fn set(helper: &'static str) {
+    *AUR_HELPER.write().unwrap() = Some(helper);
 }
 
 /// Get the initialized AUR helper.
 ///
 /// Returns None if no helper has been initialized.
 pub fn get() -> Option<&'static str> {
-    AUR_HELPER.get().map(String::as_str)
+    *AUR_HELPER.read().unwrap()
 }
 
-/// Check if a command is executable in PATH.
-fn is_executable_in_path(cmd: &str) -> bool {
-    if cmd.contains(std::path::MAIN_SEPARATOR) {
-        return PathBuf::from(cmd).is_file();
+/// Translate pacman-style arguments into the syntax of the given helper.
+///
+/// Pages build AUR commands with pacman flags (`-S`, `-Syu`, `-R`...), which
+/// paru, yay and pikaur accept as-is. aura keeps pacman operations for repo
+/// packages but uses `-A` for AUR installs and upgrades.
+pub fn translate_args(helper: &str, args: &[String]) -> Vec<String> {
+    match helper {
+        "aura" => args
+            .iter()
+            .map(|arg| match arg.as_str() {
+                "-S" => "-A".to_string(),
+                "-Syu" | "-Sua" => "-Au".to_string(),
+                _ => arg.clone(),
+            })
+            .collect(),
+        _ => args.to_vec(),
     }
+}
 
-    let paths = match env::var_os("PATH") {
-        Some(p) => p,
-        None => return false,
-    };
+/// Remove `--noconfirm` so the helper asks before changing packages.
+///
+/// Used in interactive mode, where the user answers the helper's prompts.
+pub fn strip_noconfirm(args: &[String]) -> Vec<String> {
+    args.iter()
+        .filter(|arg| arg.as_str() != "--noconfirm")
+        .cloned()
+        .collect()
+}
 
-    for dir in env::split_paths(&paths) {
-        let mut candidate = dir.clone();
-        candidate.push(cmd);
-        if candidate.exists() {
-            if let Ok(metadata) = std::fs::metadata(&candidate) {
-                let perms = metadata.permissions();
-                if perms.mode() & 0o111 != 0 {
-                    return true;
-                }
+/// Arguments telling the helper which program to use for privilege escalation.
+///
+/// paru and yay take `--sudo` and `--sudoflags`. pikaur and aura have no such
+/// option and call `sudo` from PATH, where the sudo shim is picked up instead.
+pub fn sudo_args(helper: &str, sudo: &str, flags: &[&str]) -> Vec<String> {
+    match helper {
+        "paru" | "yay" => {
+            let mut args = vec!["--sudo".to_string(), sudo.to_string()];
+            if !flags.is_empty() {
+                args.push("--sudoflags".to_string());
+                args.push(flags.join(" "));
             }
+            args
         }
+        _ => Vec::new(),
+    }
+}
+
+/// Check if a command is executable in PATH.
+pub fn is_executable_in_path(cmd: &str) -> bool {
+    is_executable_in(cmd, env::var_os("PATH").as_deref())
+}
+
+/// Check if a command is executable in the given PATH value.
+///
+/// Commands containing a path separator are checked as a file directly.
+fn is_executable_in(cmd: &str, path: Option<&OsStr>) -> bool {
+    if cmd.contains(std::path::MAIN_SEPARATOR) {
+        return PathBuf::from(cmd).is_file();
     }
 
-    false
+    let Some(paths) = path else {
+        return false;
+    };
+
+    env::split_paths(paths).any(|dir| {
+        std::fs::metadata(dir.join(cmd))
+            .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+    })
 }
 
 #[cfg(test)]
@@ -87,4 +170,87 @@ mod tests {
         // This test just verifies the function doesn't panic
         let _ = detect();
     }
+
+    #[test]
+    fn test_detect_with_follows_priority_order() {
+        let installed = |helper: &str| ["yay", "paru", "aura"].contains(&helper);
+        assert_eq!(detect_with(installed), Some("paru"));
+        assert_eq!(installed_with(installed), vec!["paru", "yay", "aura"]);
+
+        assert_eq!(detect_with(|helper| helper == "pikaur"), Some("pikaur"));
+        assert_eq!(detect_with(|_| false), None);
+        assert!(installed_with(|_| false).is_empty());
+    }
+
+    #[test]
+    fn test_is_executable_in() {
+        let dir = std::env::temp_dir().join(format!("xero-toolkit-path-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("paru"), "").unwrap();
+        std::fs::set_permissions(dir.join("paru"), std::fs::Permissions::from_mode(0o755)).unwrap();
+        std::fs::write(dir.join("yay"), "").unwrap();
+        std::fs::set_permissions(dir.join("yay"), std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        let path = env::join_paths(["/nonexistent".into(), dir.clone()]).unwrap();
+        assert!(is_executable_in("paru", Some(&path)));
+        assert!(!is_executable_in("yay", Some(&path)));
+        assert!(!is_executable_in("pikaur", Some(&path)));
+        assert!(!is_executable_in("paru", None));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_choose_prefers_configured_helper() {
+        let installed = |helper: &str| ["paru", "yay"].contains(&helper);
+
+        assert_eq!(choose(Some("yay"), installed), Some("yay"));
+        assert_eq!(choose(None, installed), Some("paru"));
+        // Not installed or unknown: fall back to detection order
+        assert_eq!(choose(Some("pikaur"), installed), Some("paru"));
+        assert_eq!(choose(Some("trizen"), installed), Some("paru"));
+        assert_eq!(choose(Some("yay"), |_| false), None);
+    }
+
+    #[test]
+    fn test_strip_noconfirm() {
+        let args: Vec<String> = ["-S", "--noconfirm", "--needed", "pkg"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(strip_noconfirm(&args), vec!["-S", "--needed", "pkg"]);
+    }
+
+    #[test]
+    fn test_translate_args_per_helper() {
+        let args: Vec<String> = ["-S", "--needed", "--noconfirm", "pkg"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+
+        for helper in ["paru", "yay", "pikaur"] {
+            assert_eq!(translate_args(helper, &args), args);
+        }
+        assert_eq!(
+            translate_args("aura", &args),
+            vec!["-A", "--needed", "--noconfirm", "pkg"]
+        );
+
+        let remove = vec!["-Rdd".to_string(), "iptables".to_string()];
+        assert_eq!(translate_args("aura", &remove), remove);
+    }
+
+    #[test]
+    fn test_sudo_args_per_helper() {
+        assert_eq!(
+            sudo_args("paru", "sudo", &["-A"]),
+            vec!["--sudo", "sudo", "--sudoflags", "-A"]
+        );
+        assert_eq!(
+            sudo_args("yay", "/opt/xero-auth", &[]),
+            vec!["--sudo", "/opt/xero-auth"]
+        );
+        assert!(sudo_args("pikaur", "sudo", &[]).is_empty());
+        assert!(sudo_args("aura", "sudo", &[]).is_empty());
+    }
 }
@@ -51,7 +51,16 @@ pub async fn fetch_arch_iso_info() -> Result<(String, String)> {
     Ok((iso_name, download_url))
 }
 
+/// Path the data is downloaded to before it is moved to `dest_path`.
+fn partial_path(dest_path: &str) -> String {
+    format!("{}.part", dest_path)
+}
+
 /// Download a file with progress tracking
+///
+/// Data is written to `<dest_path>.part`, which is resumed if it is left over
+/// from an earlier attempt. Once the size matches the one reported by the
+/// server, the file is moved to `dest_path`.
 pub async fn download_file<F>(
     url: String,
     dest_path: String,
@@ -64,6 +73,7 @@ where
 {
     use futures_util::StreamExt;
     use reqwest::header::RANGE;
+    use reqwest::StatusCode;
     use tokio::io::AsyncWriteExt;
 
     info!("Starting download from {} to {}", url, dest_path);
@@ -73,17 +83,24 @@ where
         .build()
         .context("Failed to build HTTP client")?;
 
-    // Create file (truncate if exists)
-    let mut file = tokio::fs::File::create(&dest_path)
+    // Append to a partial download if there is one
+    let part_path = partial_path(&dest_path);
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&part_path)
         .await
         .context("Failed to create destination file")?;
 
-    let mut downloaded: u64 = 0;
+    let mut downloaded: u64 = file.metadata().await?.len();
+    if downloaded > 0 {
+        info!("Found partial download of {} bytes", downloaded);
+    }
     let mut total_size: u64 = 0;
 
     // Speed calculation variables
     let mut last_update = Instant::now();
-    let mut last_downloaded = 0u64;
+    let mut last_downloaded = downloaded;
     let mut speed_samples: Vec<f64> = Vec::with_capacity(20);
     let max_samples = 20;
 
@@ -100,7 +117,7 @@ where
         if cancel_flag.load(Ordering::Relaxed) {
             info!("Download cancelled");
             drop(file);
-            let _ = tokio::fs::remove_file(&dest_path).await;
+            let _ = tokio::fs::remove_file(&part_path).await;
             anyhow::bail!("Download cancelled");
         }
 
@@ -110,6 +127,14 @@ where
             continue;
         }
 
+        // A partial file larger than the download can't be resumed
+        if total_size > 0 && downloaded > total_size {
+            info!("Partial download is larger than the file, starting over");
+            file.set_len(0).await?;
+            downloaded = 0;
+            last_downloaded = 0;
+        }
+
         // Check if finished
         if total_size > 0 && downloaded >= total_size {
             break;
@@ -135,9 +160,20 @@ where
                 }
 
                 let status = response.status();
+                if downloaded > 0 && status == StatusCode::OK {
+                    // The server ignored the range and sends the whole file
+                    info!("Server does not support resuming, starting over");
+                    file.set_len(0).await?;
+                    downloaded = 0;
+                    last_downloaded = 0;
+                    if let Some(len) = response.content_length() {
+                        total_size = len;
+                    }
+                }
+
                 if !status.is_success() {
                     info!("Request failed with status: {}", status);
-                    if status == reqwest::StatusCode::RANGE_NOT_SATISFIABLE
+                    if status == StatusCode::RANGE_NOT_SATISFIABLE
                         && total_size > 0
                         && downloaded >= total_size
                     {
@@ -154,7 +190,7 @@ where
                     if cancel_flag.load(Ordering::Relaxed) {
                         info!("Download cancelled");
                         drop(file);
-                        let _ = tokio::fs::remove_file(&dest_path).await;
+                        let _ = tokio::fs::remove_file(&part_path).await;
                         anyhow::bail!("Download cancelled");
                     }
 
@@ -228,6 +264,23 @@ where
     file.flush().await?;
     drop(file);
 
+    // Verify the final size before handing out the file
+    let size = tokio::fs::metadata(&part_path)
+        .await
+        .context("Failed to read downloaded file")?
+        .len();
+    if total_size > 0 && size != total_size {
+        anyhow::bail!(
+            "Downloaded file is {} but should be {}",
+            format_bytes(size),
+            format_bytes(total_size)
+        );
+    }
+
+    tokio::fs::rename(&part_path, &dest_path)
+        .await
+        .context("Failed to move download into place")?;
+
     // Final update
     let state = DownloadState {
         downloaded,
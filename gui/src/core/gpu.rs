@@ -0,0 +1,199 @@
+//! GPU detection.
+//!
+//! Parses `lspci` output for display controllers so driver pages can adapt
+//! to the installed hardware, including multi-GPU laptops (e.g. Intel + NVIDIA).
+
+use log::{info, warn};
+use std::process::Command;
+
+/// GPU vendor of a display controller.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GpuVendor {
+    Nvidia,
+    Amd,
+    Intel,
+}
+
+impl GpuVendor {
+    /// Packages providing VA-API and VDPAU hardware video decoding.
+    pub fn video_acceleration_packages(self) -> &'static [&'static str] {
+        match self {
+            // VDPAU ships with the proprietary driver, VA-API is translated to NVDEC
+            Self::Nvidia => &["libva-nvidia-driver"],
+            Self::Amd => &["libva-mesa-driver", "mesa-vdpau"],
+            Self::Intel => &["intel-media-driver"],
+        }
+    }
+}
+
+/// NVIDIA chip generation, derived from the chip codename (e.g. `TU106`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum NvidiaGeneration {
+    Kepler,
+    Maxwell,
+    Pascal,
+    Volta,
+    Turing,
+    Ampere,
+    Ada,
+    Hopper,
+    Blackwell,
+}
+
+impl NvidiaGeneration {
+    /// Map a chip codename prefix to its generation.
+    fn from_codename(codename: &str) -> Option<Self> {
+        let prefix = codename.get(..2)?;
+        match prefix {
+            "GK" => Some(Self::Kepler),
+            "GM" => Some(Self::Maxwell),
+            "GP" => Some(Self::Pascal),
+            "GV" => Some(Self::Volta),
+            "TU" => Some(Self::Turing),
+            "GA" => Some(Self::Ampere),
+            "AD" => Some(Self::Ada),
+            "GH" => Some(Self::Hopper),
+            "GB" => Some(Self::Blackwell),
+            _ => None,
+        }
+    }
+
+    /// Match a word that looks like a chip codename, e.g. `TU106` or `AD104M`.
+    fn from_codename_word(word: &str) -> Option<Self> {
+        let has_digits = word.chars().skip(2).take(3).all(|c| c.is_ascii_digit());
+        if word.len() >= 5 && has_digits {
+            Self::from_codename(word)
+        } else {
+            None
+        }
+    }
+
+    /// Whether the open-source NVIDIA kernel modules support this generation.
+    pub fn supports_open_modules(self) -> bool {
+        self >= Self::Turing
+    }
+}
+
+/// A detected display controller.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gpu {
+    pub vendor: GpuVendor,
+    /// Device description as reported by lspci
+    pub name: String,
+    /// Chip generation, only known for NVIDIA cards
+    pub nvidia_generation: Option<NvidiaGeneration>,
+}
+
+/// Detect all GPUs via `lspci`.
+///
+/// Returns an empty list if lspci is unavailable or fails.
+pub fn detect_gpus() -> Vec<Gpu> {
+    match Command::new("lspci").output() {
+        Ok(output) if output.status.success() => {
+            let gpus = parse_lspci(&String::from_utf8_lossy(&output.stdout));
+            info!("Detected GPUs: {:?}", gpus);
+            gpus
+        }
+        Ok(_) => {
+            warn!("lspci returned an error, GPU detection skipped");
+            Vec::new()
+        }
+        Err(e) => {
+            warn!("Failed to run lspci: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Detect the vendors of all installed GPUs, without duplicates.
+pub fn detect_gpu_vendors() -> Vec<GpuVendor> {
+    vendors(&detect_gpus())
+}
+
+/// Unique vendors of the given GPUs, in detection order.
+fn vendors(gpus: &[Gpu]) -> Vec<GpuVendor> {
+    let mut vendors = Vec::new();
+    for gpu in gpus {
+        if !vendors.contains(&gpu.vendor) {
+            vendors.push(gpu.vendor);
+        }
+    }
+    vendors
+}
+
+/// Parse display controllers out of `lspci` output.
+fn parse_lspci(output: &str) -> Vec<Gpu> {
+    const CONTROLLER_CLASSES: [&str; 3] = [
+        "VGA compatible controller",
+        "3D controller",
+        "Display controller",
+    ];
+
+    output
+        .lines()
+        .filter_map(|line| {
+            // Format: "01:00.0 VGA compatible controller: NVIDIA Corporation TU106 [...]"
+            let (class, device) = line.split_once(": ")?;
+            if !CONTROLLER_CLASSES.iter().any(|c| class.ends_with(c)) {
+                return None;
+            }
+
+            let lower = device.to_lowercase();
+            let vendor = if lower.starts_with("nvidia") {
+                GpuVendor::Nvidia
+            } else if lower.starts_with("advanced micro devices") || lower.starts_with("amd") {
+                GpuVendor::Amd
+            } else if lower.starts_with("intel") {
+                GpuVendor::Intel
+            } else {
+                return None;
+            };
+
+            let nvidia_generation = match vendor {
+                GpuVendor::Nvidia => device
+                    .split_whitespace()
+                    .find_map(NvidiaGeneration::from_codename_word),
+                _ => None,
+            };
+
+            Some(Gpu {
+                vendor,
+                name: device.trim().to_string(),
+                nvidia_generation,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_lspci_hybrid_laptop() {
+        let output = "\
+00:00.0 Host bridge: Intel Corporation Device 9b54 (rev 05)
+00:02.0 VGA compatible controller: Intel Corporation CometLake-H GT2 [UHD Graphics] (rev 05)
+01:00.0 3D controller: NVIDIA Corporation TU106M [GeForce RTX 2060 Mobile] (rev a1)
+01:00.1 Audio device: NVIDIA Corporation TU106 High Definition Audio Controller (rev a1)";
+
+        let gpus = parse_lspci(output);
+        assert_eq!(gpus.len(), 2);
+        assert_eq!(vendors(&gpus), vec![GpuVendor::Intel, GpuVendor::Nvidia]);
+        assert_eq!(gpus[1].nvidia_generation, Some(NvidiaGeneration::Turing));
+        assert!(gpus[1].nvidia_generation.unwrap().supports_open_modules());
+    }
+
+    #[test]
+    fn test_parse_lspci_legacy_nvidia_and_amd() {
+        let output = "\
+01:00.0 VGA compatible controller: NVIDIA Corporation GP104 [GeForce GTX 1070] (rev a1)
+0c:00.0 VGA compatible controller: Advanced Micro Devices, Inc. [AMD/ATI] Navi 21 (rev c0)";
+
+        let gpus = parse_lspci(output);
+        assert_eq!(vendors(&gpus), vec![GpuVendor::Nvidia, GpuVendor::Amd]);
+        assert_eq!(gpus[0].nvidia_generation, Some(NvidiaGeneration::Pascal));
+        assert!(!gpus[0].nvidia_generation.unwrap().supports_open_modules());
+        assert_eq!(gpus[1].nvidia_generation, None);
+    }
+}
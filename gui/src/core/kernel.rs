@@ -0,0 +1,140 @@
+//! Installed kernel detection.
+//!
+//! Lists the Arch kernel packages that are installed and finds the one the
+//! system is currently running, so the kernel manager never removes it by
+//! accident.
+
+use log::{debug, warn};
+use std::fs;
+
+/// Kernel packages offered by the kernel manager, with a short description.
+pub const KNOWN_KERNELS: &[(&str, &str)] = &[
+    ("linux", "Stable mainline kernel"),
+    ("linux-lts", "Long-term support kernel"),
+    ("linux-zen", "Kernel tuned for desktop responsiveness"),
+    ("linux-hardened", "Security-focused kernel"),
+    ("linux-rt", "Realtime kernel"),
+    ("linux-rt-lts", "Long-term support realtime kernel"),
+];
+
+/// An installed kernel package.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KernelInfo {
+    /// Package name, such as `linux-zen`
+    pub name: String,
+    /// Installed package version
+    pub version: String,
+    /// Whether the system is currently booted into this kernel
+    pub running: bool,
+}
+
+impl KernelInfo {
+    /// Name of the matching headers package.
+    pub fn headers(&self) -> String {
+        format!("{}-headers", self.name)
+    }
+}
+
+/// List the installed kernel packages, marking the running one.
+pub fn list_installed_kernels() -> Vec<KernelInfo> {
+    let names: Vec<&str> = KNOWN_KERNELS.iter().map(|(name, _)| *name).collect();
+
+    // pacman exits with an error for every kernel that is not installed, but
+    // still prints the ones that are
+    let output = match std::process::Command::new("pacman")
+        .arg("-Q")
+        .args(&names)
+        .output()
+    {
+        Ok(output) => output,
+        Err(e) => {
+            warn!("Failed to query installed kernels: {}", e);
+            return Vec::new();
+        }
+    };
+
+    let running = running_kernel_package();
+    let kernels = parse_kernel_list(&String::from_utf8_lossy(&output.stdout), running.as_deref());
+    debug!("Installed kernels: {:?}", kernels);
+    kernels
+}
+
+/// Parse `pacman -Q` output into known kernel packages.
+fn parse_kernel_list(output: &str, running: Option<&str>) -> Vec<KernelInfo> {
+    output
+        .lines()
+        .filter_map(|line| line.trim().split_once(' '))
+        .filter(|(name, _)| KNOWN_KERNELS.iter().any(|(known, _)| known == name))
+        .map(|(name, version)| KernelInfo {
+            name: name.to_string(),
+            version: version.trim().to_string(),
+            running: running == Some(name),
+        })
+        .collect()
+}
+
+/// Package name of the running kernel.
+///
+/// Arch kernel packages record their name in `pkgbase` next to the modules of
+/// the release reported by `uname -r`.
+pub fn running_kernel_package() -> Option<String> {
+    let release = running_kernel_release()?;
+    let path = format!("/usr/lib/modules/{}/pkgbase", release);
+    match fs::read_to_string(&path) {
+        Ok(pkgbase) => Some(pkgbase.trim().to_string()),
+        Err(e) => {
+            warn!("Failed to read {}: {}", path, e);
+            None
+        }
+    }
+}
+
+/// Whether a module for the running kernel is installed, such as one built by DKMS.
+pub fn is_module_available(module: &str) -> bool {
+    std::process::Command::new("modinfo")
+        .args(["-F", "filename", module])
+        .output()
+        .inspect_err(|e| warn!("Failed to run modinfo: {}", e))
+        .is_ok_and(|output| output.status.success())
+}
+
+/// Release of the running kernel, as reported by `uname -r`.
+pub fn running_kernel_release() -> Option<String> {
+    let output = std::process::Command::new("uname")
+        .arg("-r")
+        .output()
+        .inspect_err(|e| warn!("Failed to run uname: {}", e))
+        .ok()?;
+
+    let release = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !release.is_empty()).then_some(release)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_kernel_list() {
+        let output = "linux 6.10.5.arch1-1\nlinux-zen 6.10.5.zen1-1\nlinux-firmware 20240809-1\n";
+        let kernels = parse_kernel_list(output, Some("linux-zen"));
+
+        assert_eq!(
+            kernels,
+            vec![
+                KernelInfo {
+                    name: "linux".to_string(),
+                    version: "6.10.5.arch1-1".to_string(),
+                    running: false,
+                },
+                KernelInfo {
+                    name: "linux-zen".to_string(),
+                    version: "6.10.5.zen1-1".to_string(),
+                    running: true,
+                },
+            ]
+        );
+        assert_eq!(kernels[1].headers(), "linux-zen-headers");
+        assert!(parse_kernel_list("", None).is_empty());
+    }
+}
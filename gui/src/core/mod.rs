@@ -1,20 +1,40 @@
 //! Core functionality and business logic.
 //!
 //! This module contains:
+//! - `audit`: Journal audit trail of executed commands
 //! - `aur`: AUR helper detection and management
 //! - `daemon`: Daemon management for xero-auth
 //! - `download`: File download functionality
+//! - `gpu`: GPU vendor and generation detection
+//! - `kernel`: Installed and running kernel detection
 //! - `package`: Package and flatpak checking utilities
+//! - `pacman_lock`: Stale pacman database lock detection
+//! - `privilege`: Privilege escalation backend selection
+//! - `scx`: sched-ext scheduler detection
 //! - `system_check`: System dependency and distribution validation
+//! - `sysinfo`: System summary for bug reports
+//! - `tweaks`: ZRAM and swappiness tweak state
 
+pub mod audit;
 pub mod aur;
 pub mod autostart;
 pub mod daemon;
 pub mod download;
+pub mod gpu;
+pub mod kernel;
 pub mod package;
+pub mod pacman_lock;
+pub mod privilege;
+pub mod scx;
+pub mod sysinfo;
 pub mod system_check;
+pub mod tweaks;
 
 // Re-export commonly used items
 pub use aur::get as aur_helper;
-pub use package::{is_flatpak_installed, is_package_installed};
+pub use package::{
+    are_packages_installed, installed_fraction, is_flatpak_installed, is_multilib_enabled,
+    is_package_installed, FlatpakKind,
+};
+pub use pacman_lock::{check_pacman_lock, LockState};
 pub use system_check::check_system_requirements;
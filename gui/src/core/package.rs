@@ -2,15 +2,132 @@
 //!
 //! This module provides utilities for checking installed packages,
 //! flatpaks, and system operations.
+//!
+//! Package checks are answered from an in-memory [`PackageCache`] once it has
+//! been initialized, instead of spawning pacman for every query.
 
 use super::aur;
 use anyhow::Result;
-use log::debug;
+use gtk4::{gio, glib};
+use log::{debug, warn};
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::Path;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+/// Global package cache, None until initialized.
+static PACKAGE_CACHE: RwLock<Option<PackageCache>> = RwLock::new(None);
+
+/// Package providing `checkupdates`.
+pub const CHECKUPDATES_PACKAGE: &str = "pacman-contrib";
+
+/// How long counted updates are reused before checking again.
+const UPDATE_COUNT_TTL: Duration = Duration::from_secs(30 * 60);
+
+/// Last found updates and when they were checked.
+static PENDING_UPDATES: RwLock<Option<(Instant, PendingUpdates)>> = RwLock::new(None);
+
+/// Updates found by [`count_available_updates`].
+#[derive(Debug, Clone)]
+struct PendingUpdates {
+    /// Names of the repo packages with an update
+    repo: Vec<String>,
+    /// Number of AUR packages with an update
+    aur: usize,
+}
+
+/// Names of all installed packages, loaded from `pacman -Qq`.
+#[derive(Debug, Default)]
+pub struct PackageCache {
+    packages: HashSet<String>,
+}
+
+impl PackageCache {
+    /// Load the list of installed packages.
+    pub fn load() -> Result<Self> {
+        let output = std::process::Command::new("pacman").arg("-Qq").output()?;
+        if !output.status.success() {
+            anyhow::bail!("pacman -Qq exited with {}", output.status);
+        }
+
+        let packages: HashSet<String> = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(|line| line.trim().to_string())
+            .filter(|line| !line.is_empty())
+            .collect();
+        debug!("Loaded {} installed packages into cache", packages.len());
+
+        Ok(Self { packages })
+    }
+
+    /// Check if a package is in the cache.
+    pub fn contains(&self, package: &str) -> bool {
+        self.packages.contains(package)
+    }
+}
 
-/// Check if a package is installed using AUR helper or pacman.
+/// Initialize the global package cache, or reload it if already initialized.
+///
+/// Should be called after installs complete so cached results stay accurate.
+/// On failure the cache is cleared and checks fall back to spawning pacman.
+pub fn refresh_cache() {
+    // Installs and upgrades change what is pending
+    *PENDING_UPDATES.write().unwrap() = None;
+
+    let cache = match PackageCache::load() {
+        Ok(cache) => Some(cache),
+        Err(e) => {
+            warn!("Failed to load package cache: {}", e);
+            None
+        }
+    };
+    *PACKAGE_CACHE.write().unwrap() = cache;
+}
+
+/// Check if all given packages are installed.
+///
+/// Uses a single cache lookup per package when the cache is initialized.
+pub fn are_packages_installed(packages: &[&str]) -> bool {
+    if let Some(cache) = PACKAGE_CACHE.read().unwrap().as_ref() {
+        return packages.iter().all(|package| cache.contains(package));
+    }
+
+    packages.iter().all(|package| is_package_installed(package))
+}
+
+/// Share of the given packages that are installed, from 0.0 to 1.0.
+///
+/// Lets pages tell a partially installed group from a missing one. An empty
+/// list counts as installed, as in [`are_packages_installed`].
+pub fn installed_fraction(packages: &[&str]) -> f64 {
+    if packages.is_empty() {
+        return 1.0;
+    }
+
+    let installed = if let Some(cache) = PACKAGE_CACHE.read().unwrap().as_ref() {
+        packages
+            .iter()
+            .filter(|package| cache.contains(package))
+            .count()
+    } else {
+        packages
+            .iter()
+            .filter(|package| is_package_installed(package))
+            .count()
+    };
+    installed as f64 / packages.len() as f64
+}
+
+/// Check if a package is installed using the cache, AUR helper or pacman.
 pub fn is_package_installed(package: &str) -> bool {
     debug!("Checking if package '{}' is installed", package);
 
+    if let Some(cache) = PACKAGE_CACHE.read().unwrap().as_ref() {
+        return cache.contains(package);
+    }
+
     // Try AUR helper first
     if let Some(helper) = aur::detect() {
         if let Ok(output) = std::process::Command::new(helper)
@@ -40,23 +157,51 @@ pub fn is_package_installed(package: &str) -> bool {
     installed
 }
 
-/// Check if a flatpak package is installed.
-pub fn is_flatpak_installed(package: &str) -> bool {
-    debug!("Checking if Flatpak '{}' is installed", package);
+/// Kind of flatpak installation to query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlatpakKind {
+    /// Applications only (`--app`)
+    App,
+    /// Runtimes and extensions only (`--runtime`)
+    Runtime,
+}
+
+impl FlatpakKind {
+    /// `flatpak list` filter argument for this kind.
+    fn list_arg(self) -> &'static str {
+        match self {
+            Self::App => "--app",
+            Self::Runtime => "--runtime",
+        }
+    }
+}
+
+/// Parse `flatpak list --columns=application` output into a set of IDs.
+///
+/// IDs are compared for exact equality, so `org.gimp.GIMP` does not match
+/// `org.gimp.GIMP.Manual`.
+fn parse_flatpak_list(output: &str) -> HashSet<String> {
+    output
+        .lines()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect()
+}
 
-    // Use --columns=application to get only app IDs, one per line
+/// Check if a flatpak application or runtime is installed.
+pub fn is_flatpak_installed(package: &str, kind: FlatpakKind) -> bool {
+    debug!(
+        "Checking if Flatpak '{}' ({:?}) is installed",
+        package, kind
+    );
+
+    // Use --columns=application to get only IDs, one per line
     let installed = std::process::Command::new("flatpak")
-        .args(["list", "--columns=application"])
+        .args(["list", kind.list_arg(), "--columns=application"])
         .output()
         .map(|output| {
-            if output.status.success() {
-                // Check for exact match on any line
-                String::from_utf8_lossy(&output.stdout)
-                    .lines()
-                    .any(|line| line.trim() == package)
-            } else {
-                false
-            }
+            output.status.success()
+                && parse_flatpak_list(&String::from_utf8_lossy(&output.stdout)).contains(package)
         })
         .unwrap_or(false);
 
@@ -69,17 +214,277 @@ pub fn is_flatpak_installed(package: &str) -> bool {
     installed
 }
 
+/// Run a command without blocking the main loop.
+///
+/// Returns whether it exited successfully and its standard output.
+async fn command_output_async(argv: &[&str]) -> Option<(bool, String)> {
+    let argv: Vec<&OsStr> = argv.iter().map(OsStr::new).collect();
+    let process = gio::Subprocess::newv(
+        &argv,
+        gio::SubprocessFlags::STDOUT_PIPE | gio::SubprocessFlags::STDERR_SILENCE,
+    )
+    .inspect_err(|e| warn!("Failed to spawn {:?}: {}", argv, e))
+    .ok()?;
+
+    let (stdout, _) = process.communicate_utf8_future(None).await.ok()?;
+    let stdout = stdout.map(|s| s.to_string()).unwrap_or_default();
+    Some((process.is_successful(), stdout))
+}
+
+/// Async version of [`is_package_installed`] for use from the GTK main loop.
+pub async fn is_package_installed_async(package: &str) -> bool {
+    if let Some(cache) = PACKAGE_CACHE.read().unwrap().as_ref() {
+        return cache.contains(package);
+    }
+
+    command_output_async(&["pacman", "-Q", package])
+        .await
+        .is_some_and(|(success, _)| success)
+}
+
+/// List orphaned packages, dependencies no installed package needs anymore.
+///
+/// Runs `pacman -Qtdq` without blocking the main loop. Returns `None` if pacman
+/// could not be started.
+pub async fn list_orphans_async() -> Option<Vec<String>> {
+    // pacman exits with an error when there are no orphans, so only the output counts
+    let (_, stdout) = command_output_async(&["pacman", "-Qtdq"]).await?;
+    Some(parse_package_list(&stdout))
+}
+
+/// Count pending updates as (repo, AUR) packages without blocking the main loop.
+///
+/// Repo updates come from `checkupdates`, which syncs a temporary copy of the
+/// databases instead of the system ones, and AUR updates from the helper's
+/// `-Qua`. Counts are reused for a while unless `refresh` is set. Returns
+/// `None` if `checkupdates` is not installed or fails.
+pub async fn count_available_updates(refresh: bool) -> Option<(usize, usize)> {
+    if !refresh {
+        if let Some((checked_at, pending)) = PENDING_UPDATES.read().unwrap().as_ref() {
+            if checked_at.elapsed() < UPDATE_COUNT_TTL {
+                return Some((pending.repo.len(), pending.aur));
+            }
+        }
+    }
+
+    if !is_package_installed(CHECKUPDATES_PACKAGE) {
+        return None;
+    }
+    // Exits with 2 when there are no updates, 1 on errors
+    let (_, repo_output) = command_output_async(&["checkupdates"]).await?;
+    let repo = parse_update_names(&repo_output);
+
+    // Helpers exit with an error when nothing is outdated, so only the output counts
+    let aur = match aur::get().or_else(aur::detect) {
+        Some(helper) => command_output_async(&[helper, "-Qua"])
+            .await
+            .map_or(0, |(_, stdout)| parse_update_names(&stdout).len()),
+        None => 0,
+    };
+
+    debug!(
+        "Pending updates: {:?} from repos, {} from the AUR",
+        repo, aur
+    );
+    let counts = (repo.len(), aur);
+    *PENDING_UPDATES.write().unwrap() = Some((Instant::now(), PendingUpdates { repo, aur }));
+    Some(counts)
+}
+
+/// Whether the last update check found an update for any of `packages`.
+///
+/// Only repo packages are tracked. False if updates were never checked.
+pub fn has_pending_update(packages: &[&str]) -> bool {
+    PENDING_UPDATES
+        .read()
+        .unwrap()
+        .as_ref()
+        .is_some_and(|(_, pending)| {
+            pending
+                .repo
+                .iter()
+                .any(|name| packages.contains(&name.as_str()))
+        })
+}
+
+/// Package names of the `name old -> new` lines in `checkupdates` or `-Qua` output.
+fn parse_update_names(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter(|line| line.contains(" -> "))
+        .filter_map(|line| line.split_whitespace().next())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Parse `pacman -Qq`-style output into package names, keeping their order.
+fn parse_package_list(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Get the IDs of all installed flatpaks of a kind without blocking the main loop.
+pub async fn installed_flatpaks_async(kind: FlatpakKind) -> HashSet<String> {
+    match command_output_async(&["flatpak", "list", kind.list_arg(), "--columns=application"]).await
+    {
+        Some((true, stdout)) => parse_flatpak_list(&stdout),
+        _ => HashSet::new(),
+    }
+}
+
+/// Async version of [`is_flatpak_installed`] for use from the GTK main loop.
+#[allow(dead_code)]
+// Single-app counterpart of `installed_flatpaks_async`
+pub async fn is_flatpak_installed_async(package: &str, kind: FlatpakKind) -> bool {
+    installed_flatpaks_async(kind).await.contains(package)
+}
+
+/// Pacman configuration, listing the enabled repositories.
+pub const PACMAN_CONF: &str = "/etc/pacman.conf";
+
+/// Check if the `[multilib]` repository with 32-bit packages is enabled.
+pub fn is_multilib_enabled() -> bool {
+    match fs::read_to_string(PACMAN_CONF) {
+        Ok(conf) => has_repo_section(&conf, "multilib"),
+        Err(e) => {
+            warn!("Failed to read {}: {}", PACMAN_CONF, e);
+            false
+        }
+    }
+}
+
+/// Check if pacman.conf contents have an uncommented `[repo]` section.
+fn has_repo_section(conf: &str, repo: &str) -> bool {
+    let section = format!("[{}]", repo);
+    conf.lines().any(|line| line.trim() == section)
+}
+
+/// Directory where pacman keeps downloaded packages.
+pub const PACMAN_CACHE_DIR: &str = "/var/cache/pacman/pkg";
+
+/// Total size in bytes of the files in a directory and its subdirectories.
+///
+/// Symlinks are not followed and unreadable entries are skipped.
+pub fn directory_size(path: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+
+    entries
+        .flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(metadata) if metadata.is_dir() => directory_size(&entry.path()),
+            Ok(metadata) => metadata.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+/// Size of the pacman package cache, measured on a worker thread.
+///
+/// The cache can hold thousands of packages, so walking it would stall the
+/// main loop.
+pub async fn pacman_cache_size_async() -> u64 {
+    gio::spawn_blocking(|| directory_size(Path::new(PACMAN_CACHE_DIR)))
+        .await
+        .unwrap_or_else(|_| {
+            warn!("Measuring the pacman cache panicked");
+            0
+        })
+}
+
 /// Open a URL in the default browser.
+///
+/// Uses the GIO default handler for the URI, falling back to `xdg-open`,
+/// `gio open` and `$BROWSER` in that order. Fails only if none of them could be
+/// started, listing every attempt in the error. Only `http`, `https` and
+/// `mailto` URLs are accepted.
 pub fn open_url(url: &str) -> Result<()> {
     debug!("Opening URL: {}", url);
-    std::process::Command::new("xdg-open").arg(url).spawn()?;
+    validate_url(url)?;
+    let mut attempts = Vec::new();
+
+    match gio::AppInfo::launch_default_for_uri(url, None::<&gio::AppLaunchContext>) {
+        Ok(()) => return Ok(()),
+        Err(e) => attempts.push(format!("GIO default handler ({})", e)),
+    }
+
+    let browser = std::env::var("BROWSER").ok();
+    for (program, args) in url_openers(browser.as_deref()) {
+        if !aur::is_executable_in_path(program) {
+            attempts.push(format!("{} (not found)", program));
+            continue;
+        }
+
+        match std::process::Command::new(program)
+            .args(&args)
+            .arg(url)
+            .spawn()
+        {
+            Ok(_) => {
+                debug!("Opened URL with {}", program);
+                return Ok(());
+            }
+            Err(e) => attempts.push(format!("{} ({})", program, e)),
+        }
+    }
+
+    anyhow::bail!("Could not open {}, tried: {}", url, attempts.join(", "))
+}
+
+/// URL schemes that may be opened in the browser.
+const ALLOWED_URL_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+/// Check that a URL is well-formed and uses an allowed scheme.
+///
+/// Keeps arbitrary strings, such as ones starting with `--`, from reaching the
+/// opener programs as arguments.
+fn validate_url(url: &str) -> Result<()> {
+    let uri = glib::Uri::parse(url, glib::UriFlags::NONE)
+        .map_err(|e| anyhow::anyhow!("Invalid URL {:?}: {}", url, e))?;
+
+    let scheme = uri.scheme().to_ascii_lowercase();
+    if !ALLOWED_URL_SCHEMES.contains(&scheme.as_str()) {
+        anyhow::bail!("Refusing to open URL with scheme {:?}: {}", scheme, url);
+    }
+    if scheme != "mailto" && uri.host().is_none_or(|host| host.is_empty()) {
+        anyhow::bail!("URL has no host: {}", url);
+    }
     Ok(())
 }
 
+/// Programs to try for opening a URL, with their leading arguments.
+///
+/// `browser` is the value of `$BROWSER`, a colon-separated list of commands.
+fn url_openers(browser: Option<&str>) -> Vec<(&str, Vec<&str>)> {
+    let mut openers = vec![("xdg-open", Vec::new()), ("gio", vec!["open"])];
+    for command in browser.unwrap_or_default().split(':') {
+        let mut words = command.split_whitespace();
+        if let Some(program) = words.next() {
+            openers.push((program, words.collect()));
+        }
+    }
+    openers
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_update_names() {
+        let output = "linux 6.10.5.arch1-1 -> 6.10.6.arch1-1\n\
+                      mesa 1:24.1.5-1 -> 1:24.1.6-1\n\
+                      paru 2.0.3-1 -> 2.0.4-1 [ignored]\n";
+        assert_eq!(parse_update_names(output), vec!["linux", "mesa", "paru"]);
+        assert!(parse_update_names("").is_empty());
+        assert!(parse_update_names(":: Checking for updates...\n").is_empty());
+    }
+
     #[test]
     fn test_is_package_installed_nonexistent() {
         // A package that definitely doesn't exist
@@ -87,4 +492,87 @@ mod tests {
             "this-package-definitely-does-not-exist-12345"
         ));
     }
+
+    #[test]
+    fn test_installed_fraction() {
+        assert_eq!(installed_fraction(&[]), 1.0);
+        assert_eq!(
+            installed_fraction(&["this-package-definitely-does-not-exist-12345"]),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_parse_package_list() {
+        assert_eq!(
+            parse_package_list("python-setuptools\n  gtk2 \n\n"),
+            vec!["python-setuptools".to_string(), "gtk2".to_string()]
+        );
+        assert!(parse_package_list("").is_empty());
+    }
+
+    #[test]
+    fn test_directory_size() {
+        let dir = std::env::temp_dir().join(format!("xero-toolkit-size-{}", std::process::id()));
+        fs::create_dir_all(dir.join("nested")).unwrap();
+        fs::write(dir.join("a.pkg.tar.zst"), [0u8; 100]).unwrap();
+        fs::write(dir.join("nested").join("b.pkg.tar.zst"), [0u8; 28]).unwrap();
+
+        assert_eq!(directory_size(&dir), 128);
+        assert_eq!(directory_size(&dir.join("missing")), 0);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_has_repo_section() {
+        let conf = "[core]\nInclude = /etc/pacman.d/mirrorlist\n\n\
+            #[multilib-testing]\n#Include = /etc/pacman.d/mirrorlist\n\n\
+            #[multilib]\n#Include = /etc/pacman.d/mirrorlist\n";
+        assert!(has_repo_section(conf, "core"));
+        assert!(!has_repo_section(conf, "multilib"));
+        assert!(has_repo_section(
+            "[multilib]\nInclude = /etc/pacman.d/mirrorlist\n",
+            "multilib"
+        ));
+    }
+
+    #[test]
+    fn test_validate_url() {
+        assert!(validate_url("https://xerolinux.xyz/").is_ok());
+        assert!(validate_url("http://example.com/page?q=1").is_ok());
+        assert!(validate_url("mailto:someone@example.com").is_ok());
+
+        assert!(validate_url("--help").is_err());
+        assert!(validate_url("file:///etc/passwd").is_err());
+        assert!(validate_url("javascript:alert(1)").is_err());
+        assert!(validate_url("https://").is_err());
+    }
+
+    #[test]
+    fn test_url_openers() {
+        let openers = url_openers(Some("firefox --new-window:chromium"));
+        assert_eq!(
+            openers,
+            vec![
+                ("xdg-open", vec![]),
+                ("gio", vec!["open"]),
+                ("firefox", vec!["--new-window"]),
+                ("chromium", vec![]),
+            ]
+        );
+        assert_eq!(url_openers(None).len(), 2);
+    }
+
+    #[test]
+    fn test_parse_flatpak_list_exact_match() {
+        let output = "org.gimp.GIMP.Manual\ncom.obsproject.Studio\norg.videolan.VLC\n";
+        let installed = parse_flatpak_list(output);
+
+        // A substring check would report these as installed
+        assert!(!installed.contains("org.gimp.GIMP"));
+        assert!(!installed.contains("Video"));
+        assert!(installed.contains("org.gimp.GIMP.Manual"));
+        assert!(installed.contains("com.obsproject.Studio"));
+    }
 }
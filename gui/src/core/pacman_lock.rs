@@ -0,0 +1,80 @@
+//! Pacman database lock detection.
+//!
+//! A pacman run that gets killed leaves its lock file behind, and every later
+//! run fails with "unable to lock database" until the file is removed.
+
+use log::{info, warn};
+use std::fs;
+use std::path::Path;
+
+/// Lock file pacman holds while it modifies the package database.
+pub const DB_LOCK_PATH: &str = "/var/lib/pacman/db.lck";
+
+/// State of the pacman database lock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockState {
+    /// No lock file present
+    Unlocked,
+    /// Lock file present and a pacman process is running
+    InUse,
+    /// Lock file present, but no pacman process is running
+    Stale,
+}
+
+/// Check whether the pacman database is locked, and by whom.
+pub fn check_pacman_lock() -> LockState {
+    let state = lock_state(Path::new(DB_LOCK_PATH).exists(), is_pacman_running);
+    if state != LockState::Unlocked {
+        info!("Pacman database lock state: {:?}", state);
+    }
+    state
+}
+
+/// Classify the lock, only looking for pacman processes if the lock exists.
+fn lock_state(lock_exists: bool, pacman_running: impl FnOnce() -> bool) -> LockState {
+    if !lock_exists {
+        LockState::Unlocked
+    } else if pacman_running() {
+        LockState::InUse
+    } else {
+        LockState::Stale
+    }
+}
+
+/// Check `/proc` for a running pacman process.
+///
+/// Assumes pacman is running if `/proc` can't be read, so a lock in use is
+/// never reported as stale.
+fn is_pacman_running() -> bool {
+    let entries = match fs::read_dir("/proc") {
+        Ok(entries) => entries,
+        Err(e) => {
+            warn!("Failed to read /proc: {}", e);
+            return true;
+        }
+    };
+
+    entries
+        .flatten()
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| name.chars().all(|c| c.is_ascii_digit()))
+        })
+        .any(|entry| {
+            fs::read_to_string(entry.path().join("comm")).is_ok_and(|comm| comm.trim() == "pacman")
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_state() {
+        assert_eq!(lock_state(false, || unreachable!()), LockState::Unlocked);
+        assert_eq!(lock_state(true, || true), LockState::InUse);
+        assert_eq!(lock_state(true, || false), LockState::Stale);
+    }
+}
@@ -0,0 +1,173 @@
+//! Privilege escalation backend selection.
+//!
+//! Privileged and AUR commands are run through one of several backends.
+//! The default is pkexec, which starts the xero-auth daemon once and reuses
+//! the session for every privileged step. Users on minimal setups can pick
+//! `sudo` (with an askpass helper) or systemd's `run0` instead.
+
+use super::aur::is_executable_in_path;
+use log::{info, warn};
+use std::path::Path;
+use std::sync::RwLock;
+
+/// Environment variable used to select the backend at startup.
+pub const BACKEND_ENV: &str = "XERO_TOOLKIT_PRIVILEGE_BACKEND";
+
+/// Currently selected backend.
+static BACKEND: RwLock<PrivilegeBackend> = RwLock::new(PrivilegeBackend::Pkexec);
+
+/// Available privilege escalation backends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PrivilegeBackend {
+    /// pkexec-started xero-auth daemon (default)
+    #[default]
+    Pkexec,
+    /// sudo, using `SUDO_ASKPASS` since there is no terminal
+    Sudo,
+    /// systemd's run0
+    Run0,
+}
+
+impl PrivilegeBackend {
+    /// Parse a backend from its name (case-insensitive).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "pkexec" => Some(Self::Pkexec),
+            "sudo" => Some(Self::Sudo),
+            "run0" => Some(Self::Run0),
+            _ => None,
+        }
+    }
+
+    /// Binary that has to be present for this backend.
+    pub fn binary(self) -> &'static str {
+        match self {
+            Self::Pkexec => "pkexec",
+            Self::Sudo => "sudo",
+            Self::Run0 => "run0",
+        }
+    }
+
+    /// Whether this backend runs commands through the xero-auth daemon.
+    pub fn uses_daemon(self) -> bool {
+        self == Self::Pkexec
+    }
+
+    /// Arguments placed between the backend binary and the command.
+    pub fn flags(self) -> &'static [&'static str] {
+        match self {
+            // No terminal is attached, so sudo must ask for the password via askpass
+            Self::Sudo => &["-A"],
+            Self::Pkexec | Self::Run0 => &[],
+        }
+    }
+
+    /// Arguments that set environment variables for the escalated command.
+    ///
+    /// Placed after [`flags`](Self::flags) and before the command itself.
+    pub fn env_args(self, env: &[(String, String)]) -> Vec<String> {
+        match self {
+            Self::Pkexec => env
+                .iter()
+                .flat_map(|(key, value)| ["--env".to_string(), format!("{}={}", key, value)])
+                .collect(),
+            Self::Sudo if env.is_empty() => Vec::new(),
+            Self::Sudo => std::iter::once("env".to_string())
+                .chain(env.iter().map(|(key, value)| format!("{}={}", key, value)))
+                .collect(),
+            Self::Run0 => env
+                .iter()
+                .map(|(key, value)| format!("--setenv={}={}", key, value))
+                .collect(),
+        }
+    }
+
+    /// Arguments that set the working directory of the escalated command.
+    ///
+    /// sudo keeps the caller's directory, which is set on the spawned process.
+    pub fn cwd_args(self, cwd: Option<&Path>) -> Vec<String> {
+        let Some(cwd) = cwd else {
+            return Vec::new();
+        };
+
+        match self {
+            Self::Pkexec => vec!["--cwd".to_string(), cwd.display().to_string()],
+            Self::Sudo => Vec::new(),
+            Self::Run0 => vec![format!("--chdir={}", cwd.display())],
+        }
+    }
+
+    /// Check that the backend binary is installed.
+    pub fn validate(self) -> Result<(), String> {
+        if is_executable_in_path(self.binary()) {
+            Ok(())
+        } else {
+            Err(format!(
+                "Privilege backend '{}' is not available ({} not found in PATH)",
+                self.binary(),
+                self.binary()
+            ))
+        }
+    }
+}
+
+/// Initialize the backend from the environment or the user config.
+///
+/// The environment variable takes precedence. Falls back to pkexec when
+/// neither is set or the value is invalid.
+pub fn init() {
+    let (value, source) = match std::env::var(BACKEND_ENV) {
+        Ok(value) => (value, BACKEND_ENV),
+        Err(_) => match &crate::config::user::get().privilege_backend {
+            Some(value) => (value.clone(), "config file"),
+            None => return,
+        },
+    };
+
+    match PrivilegeBackend::from_name(&value) {
+        Some(backend) => {
+            info!("Using privilege backend: {}", backend.binary());
+            set(backend);
+        }
+        None => warn!(
+            "Unknown privilege backend '{}' in {}, using pkexec",
+            value, source
+        ),
+    }
+}
+
+/// Select the backend used for subsequent privileged commands.
+pub fn set(backend: PrivilegeBackend) {
+    *BACKEND.write().unwrap() = backend;
+}
+
+/// Get the currently selected backend.
+pub fn get() -> PrivilegeBackend {
+    *BACKEND.read().unwrap()
+}
+
+/// Check if a user account with the given name exists.
+pub fn user_exists(name: &str) -> bool {
+    let Ok(name) = std::ffi::CString::new(name) else {
+        return false;
+    };
+    // SAFETY: name is a valid C string; the returned entry is only checked for null
+    !unsafe { libc::getpwnam(name.as_ptr()) }.is_null()
+}
+
+/// Name of the user the toolkit runs as.
+pub fn current_user() -> Option<String> {
+    // SAFETY: the entry is checked for null and its name copied before any
+    // other passwd call can overwrite it
+    unsafe {
+        let entry = libc::getpwuid(libc::geteuid());
+        if entry.is_null() {
+            return None;
+        }
+        Some(
+            std::ffi::CStr::from_ptr((*entry).pw_name)
+                .to_string_lossy()
+                .into_owned(),
+        )
+    }
+}
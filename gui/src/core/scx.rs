@@ -0,0 +1,114 @@
+//! sched-ext (SCX) scheduler detection.
+//!
+//! The `scx-scheds` package ships the schedulers and `scx.service`, which
+//! starts the scheduler named in `/etc/default/scx`.
+
+use super::package;
+use log::debug;
+use std::fs;
+use std::path::Path;
+
+/// Package providing the SCX schedulers and service.
+pub const SCX_PACKAGE: &str = "scx-scheds";
+
+/// Systemd service that runs the configured scheduler.
+pub const SCX_SERVICE: &str = "scx.service";
+
+/// Config file read by [`SCX_SERVICE`].
+pub const SCX_CONFIG: &str = "/etc/default/scx";
+
+/// Key of the scheduler setting in [`SCX_CONFIG`].
+const SCHEDULER_KEY: &str = "SCX_SCHEDULER";
+
+/// Schedulers offered for switching, with a short description.
+pub const SCHEDULERS: &[(&str, &str)] = &[
+    (
+        "scx_rusty",
+        "General purpose load balancer, a good default for most workloads",
+    ),
+    (
+        "scx_lavd",
+        "Latency-aware scheduler favouring interactive tasks such as games",
+    ),
+    (
+        "scx_bpfland",
+        "Prioritizes tasks that often sleep, keeping the desktop responsive under load",
+    ),
+];
+
+/// Current SCX setup of the system.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScxStatus {
+    /// Whether `scx-scheds` is installed
+    pub installed: bool,
+    /// Whether the running kernel supports sched-ext
+    pub supported: bool,
+    /// Scheduler configured for the service
+    pub scheduler: Option<String>,
+    /// Whether the service is running
+    pub running: bool,
+}
+
+/// Detect the installed package, configured scheduler and service state.
+pub fn status() -> ScxStatus {
+    let scheduler = fs::read_to_string(SCX_CONFIG)
+        .ok()
+        .and_then(|config| parse_scheduler(&config));
+
+    let running = std::process::Command::new("systemctl")
+        .args(["is-active", "--quiet", SCX_SERVICE])
+        .status()
+        .is_ok_and(|status| status.success());
+
+    let status = ScxStatus {
+        installed: package::is_package_installed(SCX_PACKAGE),
+        supported: Path::new("/sys/kernel/sched_ext").exists(),
+        scheduler,
+        running,
+    };
+    debug!("SCX status: {:?}", status);
+    status
+}
+
+/// Read the configured scheduler from the contents of [`SCX_CONFIG`].
+fn parse_scheduler(config: &str) -> Option<String> {
+    // The last assignment wins, as when the service sources the file
+    let value = config
+        .lines()
+        .rev()
+        .find_map(|line| line.trim().strip_prefix(SCHEDULER_KEY)?.strip_prefix('='))?
+        .trim()
+        .trim_matches(|c| c == '"' || c == '\'');
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// Shell script setting the scheduler in [`SCX_CONFIG`].
+///
+/// Replaces an existing setting or appends one. `scheduler` must come from
+/// [`SCHEDULERS`], it is not quoted.
+pub fn set_scheduler_script(scheduler: &str) -> String {
+    format!(
+        "if grep -q '^{key}=' {config}; then \
+        sed -i 's/^{key}=.*/{key}={scheduler}/' {config}; \
+        else echo '{key}={scheduler}' >> {config}; fi",
+        key = SCHEDULER_KEY,
+        config = SCX_CONFIG,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_scheduler() {
+        let config = "# List of scx_schedulers\nSCX_SCHEDULER=scx_bpfland\n\n#SCX_FLAGS=''\n";
+        assert_eq!(parse_scheduler(config).as_deref(), Some("scx_bpfland"));
+        assert_eq!(
+            parse_scheduler("SCX_SCHEDULER=\"scx_lavd\"\n").as_deref(),
+            Some("scx_lavd")
+        );
+        assert_eq!(parse_scheduler("#SCX_SCHEDULER=scx_rusty\n"), None);
+        assert_eq!(parse_scheduler("SCX_SCHEDULER=\n"), None);
+    }
+}
@@ -0,0 +1,126 @@
+//! System summary for bug reports.
+//!
+//! Collects the distribution, kernel, hardware and desktop in one place so
+//! users can paste them into a report. Every field falls back to
+//! [`UNKNOWN`] when it can't be detected.
+
+use super::{aur, gpu, kernel, system_check};
+use std::fs;
+
+/// Value shown for fields that could not be detected.
+pub const UNKNOWN: &str = "unknown";
+
+/// Summary of the system the toolkit runs on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SystemInfo {
+    pub distribution: String,
+    pub kernel: String,
+    pub cpu: String,
+    pub memory: String,
+    /// One entry per display controller
+    pub gpus: Vec<String>,
+    pub desktop: String,
+    pub aur_helper: String,
+}
+
+impl SystemInfo {
+    /// Field names and values, in display order.
+    pub fn fields(&self) -> Vec<(&'static str, String)> {
+        let gpus = if self.gpus.is_empty() {
+            UNKNOWN.to_string()
+        } else {
+            self.gpus.join("\n")
+        };
+
+        vec![
+            ("Distribution", self.distribution.clone()),
+            ("Kernel", self.kernel.clone()),
+            ("CPU", self.cpu.clone()),
+            ("Memory", self.memory.clone()),
+            ("GPU", gpus),
+            ("Desktop", self.desktop.clone()),
+            ("AUR helper", self.aur_helper.clone()),
+        ]
+    }
+
+    /// Plain text summary for pasting into bug reports.
+    pub fn to_report(&self) -> String {
+        self.fields()
+            .into_iter()
+            .map(|(name, value)| format!("{}: {}", name, value.replace('\n', ", ")))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Collect the system summary.
+///
+/// Runs `uname` and `lspci`, so call it off hot paths.
+pub fn collect_system_info() -> SystemInfo {
+    let or_unknown = |value: Option<String>| value.unwrap_or_else(|| UNKNOWN.to_string());
+
+    SystemInfo {
+        distribution: or_unknown(system_check::get_distribution_name()),
+        kernel: or_unknown(kernel::running_kernel_release()),
+        cpu: or_unknown(
+            fs::read_to_string("/proc/cpuinfo")
+                .ok()
+                .and_then(|cpuinfo| parse_cpu_model(&cpuinfo)),
+        ),
+        memory: or_unknown(
+            fs::read_to_string("/proc/meminfo")
+                .ok()
+                .and_then(|meminfo| parse_total_memory(&meminfo)),
+        ),
+        gpus: gpu::detect_gpus().into_iter().map(|gpu| gpu.name).collect(),
+        desktop: or_unknown(
+            std::env::var("XDG_CURRENT_DESKTOP")
+                .ok()
+                .filter(|desktop| !desktop.is_empty()),
+        ),
+        aur_helper: or_unknown(aur::get().map(str::to_string)),
+    }
+}
+
+/// CPU model name from `/proc/cpuinfo` contents.
+fn parse_cpu_model(cpuinfo: &str) -> Option<String> {
+    cpuinfo.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        (key.trim() == "model name")
+            .then(|| value.trim().to_string())
+            .filter(|model| !model.is_empty())
+    })
+}
+
+/// Total memory from `/proc/meminfo` contents, in GiB.
+fn parse_total_memory(meminfo: &str) -> Option<String> {
+    let kib: u64 = meminfo
+        .lines()
+        .find_map(|line| line.strip_prefix("MemTotal:"))?
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse()
+        .ok()?;
+    Some(format!("{:.1} GiB", kib as f64 / (1024.0 * 1024.0)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_proc_files() {
+        let cpuinfo = "processor\t: 0\nvendor_id\t: AuthenticAMD\n\
+                       model name\t: AMD Ryzen 7 5800X 8-Core Processor\n";
+        assert_eq!(
+            parse_cpu_model(cpuinfo).as_deref(),
+            Some("AMD Ryzen 7 5800X 8-Core Processor")
+        );
+        assert_eq!(parse_cpu_model("processor\t: 0\n"), None);
+
+        let meminfo = "MemTotal:       32768000 kB\nMemFree:         1024 kB\n";
+        assert_eq!(parse_total_memory(meminfo).as_deref(), Some("31.2 GiB"));
+        assert_eq!(parse_total_memory("MemFree: 1 kB\n"), None);
+    }
+}
@@ -7,15 +7,17 @@ use log::{error, info, warn};
 
 /// Result of dependency check containing missing dependencies.
 #[derive(Debug, Clone)]
+///
+/// A missing AUR helper is not fatal, the user is offered to install one
+/// instead, see [`crate::ui::aur_setup`].
 pub struct DependencyCheckResult {
     pub flatpak_missing: bool,
-    pub aur_helper_missing: bool,
 }
 
 impl DependencyCheckResult {
     /// Check if any dependencies are missing.
     pub fn has_missing_dependencies(&self) -> bool {
-        self.flatpak_missing || self.aur_helper_missing
+        self.flatpak_missing
     }
 
     /// Get list of missing dependency names.
@@ -24,9 +26,6 @@ impl DependencyCheckResult {
         if self.flatpak_missing {
             missing.push("flatpak");
         }
-        if self.aur_helper_missing {
-            missing.push("paru or yay");
-        }
         missing
     }
 
@@ -46,9 +45,6 @@ impl DependencyCheckResult {
         if self.flatpak_missing {
             hints.push("Install flatpak: <tt>sudo pacman -S flatpak</tt>");
         }
-        if self.aur_helper_missing {
-            hints.push("AUR Helper repositories:\n• Paru: <a href=\"https://github.com/Morganamilo/paru\">https://github.com/Morganamilo/paru</a>\n• Yay: <a href=\"https://github.com/Jguer/yay\">https://github.com/Jguer/yay</a>");
-        }
 
         if hints.is_empty() {
             return String::new();
@@ -81,30 +77,6 @@ fn check_flatpak() -> bool {
     }
 }
 
-/// Check if an AUR helper (paru or yay) is installed.
-fn check_aur_helper() -> bool {
-    info!("Checking for AUR helper availability");
-
-    if let Ok(output) = std::process::Command::new("paru").arg("--version").output() {
-        if output.status.success() {
-            let version = String::from_utf8_lossy(&output.stdout);
-            info!("paru found: {}", version.trim());
-            return true;
-        }
-    }
-
-    if let Ok(output) = std::process::Command::new("yay").arg("--version").output() {
-        if output.status.success() {
-            let version = String::from_utf8_lossy(&output.stdout);
-            info!("yay found: {}", version.trim());
-            return true;
-        }
-    }
-
-    warn!("No AUR helper (paru or yay) found in PATH");
-    false
-}
-
 /// Check if current distribution is XeroLinux.
 fn is_xerolinux() -> bool {
     get_distribution_name()
@@ -113,7 +85,7 @@ fn is_xerolinux() -> bool {
 }
 
 /// Get distribution name from os-release files.
-fn get_distribution_name() -> Option<String> {
+pub fn get_distribution_name() -> Option<String> {
     use std::fs;
 
     // Try /etc/os-release first (most common)
@@ -194,12 +166,8 @@ pub fn check_dependencies() -> DependencyCheckResult {
     info!("Performing system dependency checks");
 
     let flatpak_missing = !check_flatpak();
-    let aur_helper_missing = !check_aur_helper();
 
-    let result = DependencyCheckResult {
-        flatpak_missing,
-        aur_helper_missing,
-    };
+    let result = DependencyCheckResult { flatpak_missing };
 
     if result.has_missing_dependencies() {
         let issues = result.missing_dependencies();
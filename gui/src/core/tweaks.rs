@@ -0,0 +1,81 @@
+//! State of the memory tweaks offered on the servicing page.
+//!
+//! Reads the ZRAM generator config and the swappiness sysctl drop-in written
+//! by the toolkit, so the page can tell whether a tweak is already applied.
+
+use super::package;
+use std::fs;
+
+/// Package that sets up ZRAM devices from [`ZRAM_CONFIG`].
+pub const ZRAM_PACKAGE: &str = "zram-generator";
+
+/// Config read by `zram-generator` at boot.
+pub const ZRAM_CONFIG: &str = "/etc/systemd/zram-generator.conf";
+
+/// Unit that creates the ZRAM swap device.
+pub const ZRAM_SERVICE: &str = "systemd-zram-setup@zram0.service";
+
+/// Default ZRAM config: compressed swap of half the RAM, capped at 8 GiB.
+pub const DEFAULT_ZRAM_CONFIG: &str =
+    "[zram0]\nzram-size = min(ram / 2, 8192)\ncompression-algorithm = zstd\n";
+
+/// Sysctl drop-in holding the swappiness chosen in the toolkit.
+pub const SWAPPINESS_DROPIN: &str = "/etc/sysctl.d/99-xero-swappiness.conf";
+
+/// Kernel default swappiness, restored on reset.
+pub const DEFAULT_SWAPPINESS: u32 = 60;
+
+/// Whether `zram-generator` is installed and configures a `zram0` device.
+pub fn is_zram_enabled() -> bool {
+    package::is_package_installed(ZRAM_PACKAGE)
+        && fs::read_to_string(ZRAM_CONFIG).is_ok_and(|config| has_zram_device(&config))
+}
+
+/// Check if a zram-generator config sets up `zram0`.
+fn has_zram_device(config: &str) -> bool {
+    config.lines().any(|line| line.trim() == "[zram0]")
+}
+
+/// Swappiness currently used by the kernel.
+pub fn current_swappiness() -> Option<u32> {
+    fs::read_to_string("/proc/sys/vm/swappiness")
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Swappiness set by the toolkit's drop-in, if one was written.
+pub fn configured_swappiness() -> Option<u32> {
+    parse_swappiness(&fs::read_to_string(SWAPPINESS_DROPIN).ok()?)
+}
+
+/// Read `vm.swappiness` from sysctl config contents.
+fn parse_swappiness(config: &str) -> Option<u32> {
+    config.lines().rev().find_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        match key.trim() {
+            "vm.swappiness" | "vm/swappiness" => value.trim().parse().ok(),
+            _ => None,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        assert!(has_zram_device(DEFAULT_ZRAM_CONFIG));
+        assert!(!has_zram_device("# [zram0]\n"));
+
+        assert_eq!(parse_swappiness("vm.swappiness = 10\n"), Some(10));
+        assert_eq!(
+            parse_swappiness("# tuned\nvm.swappiness=100\nvm.swappiness=180\n"),
+            Some(180)
+        );
+        assert_eq!(parse_swappiness("#vm.swappiness = 10\n"), None);
+        assert_eq!(parse_swappiness(""), None);
+    }
+}
@@ -22,7 +22,11 @@ fn main() {
         .application_id(config::app_info::ID)
         .build();
 
-    app.connect_activate(ui::setup_application_ui);
+    app.connect_activate(|app| {
+        if let Err(e) = ui::setup_application_ui(app) {
+            ui::show_startup_error(app, &e);
+        }
+    });
 
     app.run();
 }
@@ -0,0 +1,186 @@
+//! Search across the actions of all pages.
+//!
+//! Every page button is registered as an action when its page is loaded. The
+//! sidebar search entry lists matching actions, and activating one switches to
+//! its page and highlights the button.
+
+use crate::ui::utils::extract_widget;
+use gtk4::prelude::*;
+use gtk4::{glib, Box as GtkBox, Builder, Button, Label, ListBox, ListBoxRow, SearchEntry};
+use log::info;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+/// Maximum number of results listed in the sidebar.
+const MAX_RESULTS: usize = 8;
+
+/// How long a found button stays highlighted.
+const HIGHLIGHT_DURATION: Duration = Duration::from_secs(2);
+
+/// A button on one of the pages that can be found by searching.
+#[derive(Clone, Debug)]
+pub struct ActionEntry {
+    /// Button label shown in the results
+    pub label: String,
+    /// ID of the page the button is on
+    pub page_name: &'static str,
+    /// Title of the page the button is on
+    pub page_title: &'static str,
+    /// Builder ID of the button
+    pub button_id: String,
+    pub button: Button,
+}
+
+/// Check if `text` matches a lowercase query.
+///
+/// Every word of the query must appear in the text, in any order.
+fn matches_query(text: &str, query: &str) -> bool {
+    let text = text.to_lowercase();
+    query.split_whitespace().all(|word| text.contains(word))
+}
+
+/// Collect the labelled `btn_*` buttons of a page as actions.
+pub fn collect_actions(
+    page_builder: &Builder,
+    page_name: &'static str,
+    page_title: &'static str,
+) -> Vec<ActionEntry> {
+    page_builder
+        .objects()
+        .into_iter()
+        .filter_map(|object| object.downcast::<Button>().ok())
+        .filter_map(|button| {
+            let button_id = button.buildable_id()?.to_string();
+            let label = button.label()?.to_string();
+            button_id.starts_with("btn_").then(|| ActionEntry {
+                label,
+                page_name,
+                page_title,
+                button_id,
+                button,
+            })
+        })
+        .collect()
+}
+
+/// Wire the sidebar search entry to the action registry.
+///
+/// `show_page` is called with the page ID of the chosen action and should
+/// navigate there the same way the tabs do.
+pub fn setup(
+    main_builder: &Builder,
+    actions: Vec<ActionEntry>,
+    show_page: impl Fn(&str) + 'static,
+) {
+    let entry: SearchEntry = extract_widget(main_builder, "action_search_entry");
+    let results: ListBox = extract_widget(main_builder, "action_search_results");
+
+    info!("Registered {} searchable actions", actions.len());
+    let actions = Rc::new(actions);
+    // Actions listed in the results, by row index
+    let shown: Rc<RefCell<Vec<ActionEntry>>> = Rc::default();
+
+    let results_clone = results.clone();
+    let shown_clone = shown.clone();
+    entry.connect_search_changed(move |entry| {
+        let query = entry.text().trim().to_lowercase();
+        results_clone.remove_all();
+
+        let matches: Vec<ActionEntry> = if query.is_empty() {
+            Vec::new()
+        } else {
+            actions
+                .iter()
+                .filter(|action| {
+                    matches_query(&format!("{} {}", action.label, action.page_title), &query)
+                })
+                .take(MAX_RESULTS)
+                .cloned()
+                .collect()
+        };
+
+        for action in &matches {
+            results_clone.append(&result_row(action));
+        }
+        results_clone.set_visible(!matches.is_empty());
+        *shown_clone.borrow_mut() = matches;
+    });
+
+    // Enter picks the first result
+    let results_clone = results.clone();
+    entry.connect_activate(move |_| {
+        if let Some(row) = results_clone.row_at_index(0) {
+            row.activate();
+        }
+    });
+
+    entry.connect_stop_search(|entry| {
+        entry.set_text("");
+    });
+
+    let entry_clone = entry.clone();
+    results.connect_row_activated(move |_, row| {
+        let Some(action) = usize::try_from(row.index())
+            .ok()
+            .and_then(|index| shown.borrow().get(index).cloned())
+        else {
+            return;
+        };
+
+        info!(
+            "Search: jumping to '{}' on page '{}'",
+            action.button_id, action.page_name
+        );
+        show_page(action.page_name);
+        entry_clone.set_text("");
+        highlight(&action.button);
+    });
+}
+
+/// Build a result row showing the action label and its page.
+fn result_row(action: &ActionEntry) -> ListBoxRow {
+    let content = GtkBox::new(gtk4::Orientation::Vertical, 2);
+    content.set_margin_top(6);
+    content.set_margin_bottom(6);
+    content.set_margin_start(8);
+    content.set_margin_end(8);
+
+    let label = Label::new(Some(&action.label));
+    label.set_xalign(0.0);
+    label.set_wrap(true);
+
+    let page_label = Label::new(Some(action.page_title));
+    page_label.set_xalign(0.0);
+    page_label.set_css_classes(&["dim", "caption"]);
+
+    content.append(&label);
+    content.append(&page_label);
+
+    let row = ListBoxRow::new();
+    row.set_child(Some(&content));
+    row
+}
+
+/// Focus the button, scrolling it into view, and outline it briefly.
+fn highlight(button: &Button) {
+    button.grab_focus();
+    button.add_css_class("search-highlight");
+
+    let button = button.clone();
+    glib::timeout_add_local_once(HIGHLIGHT_DURATION, move || {
+        button.remove_css_class("search-highlight");
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_query() {
+        assert!(matches_query("Tailscale VPN Drivers", "tailscale"));
+        assert!(matches_query("Tailscale VPN Drivers", "vpn tail"));
+        assert!(!matches_query("Tailscale VPN Drivers", "wireguard"));
+    }
+}
@@ -1,25 +1,30 @@
 //! Application setup and initialization.
 
 use crate::config;
+use crate::config::user::ColorScheme;
 use crate::core;
 use crate::ui::context::AppContext;
 use crate::ui::context::UiComponents;
 use crate::ui::navigation;
-use crate::ui::utils::extract_widget;
+use crate::ui::utils::{extract_widget, load_builder, try_extract_widget};
 use adw::prelude::*;
 use adw::Application;
+use anyhow::Context;
 use gtk4::glib;
 use gtk4::{gio, ApplicationWindow, Builder, CssProvider, Stack};
 use log::{error, info, warn};
 
 /// Initialize and set up main application UI.
-pub fn setup_application_ui(app: &Application) {
+///
+/// Fails if the bundled resources or the main window objects can't be
+/// loaded, see [`show_startup_error`].
+pub fn setup_application_ui(app: &Application) -> anyhow::Result<()> {
     info!("Initializing application components");
 
-    setup_resources_and_theme();
+    setup_resources_and_theme()?;
 
-    let builder = Builder::from_resource(config::resources::MAIN_UI);
-    let window = create_main_window(app, &builder);
+    let builder = load_builder(config::resources::MAIN_UI)?;
+    let window = create_main_window(app, &builder)?;
 
     window.present();
 
@@ -28,11 +33,14 @@ pub fn setup_application_ui(app: &Application) {
     if let Err(e) = config::env::init() {
         error!("Failed to initialize environment variables: {}", e);
         crate::ui::dialogs::error::show_error(&window, &format!("Failed to initialize environment variables: {}\n\nRequired environment variables (USER, HOME) are not set.", e));
-        return;
+        return Ok(());
     }
 
+    // Load installed packages once so pages can check them without spawning pacman
+    core::package::refresh_cache();
+
     // Extract tabs_container first for stack creation
-    let tabs_container = extract_widget(&builder, "tabs_container");
+    let tabs_container = try_extract_widget(&builder, "tabs_container")?;
 
     // Create dynamic stack with all pages and set up navigation tabs
     let stack = navigation::create_stack_and_tabs(&tabs_container, &builder);
@@ -40,9 +48,9 @@ pub fn setup_application_ui(app: &Application) {
     // Set up UI components with the dynamic stack
     let ctx = setup_ui_components(&builder, stack, &window);
 
-    info!("Setting initial view to first page");
-    if let Some(first_page) = navigation::PAGES.first() {
-        ctx.navigate_to_page(first_page.id);
+    if let Some(page) = navigation::initial_page() {
+        info!("Setting initial view to page '{}'", page);
+        ctx.navigate_to_page(page);
     }
 
     // Apply seasonal effects (snow for December, Halloween for October, etc.)
@@ -50,6 +58,7 @@ pub fn setup_application_ui(app: &Application) {
 
     // Perform system checks after UI is ready
     let window_clone = window.clone();
+    let builder_clone = builder.clone();
     glib::idle_add_local(move || {
         info!("Checking system dependencies");
         if !core::check_system_requirements(&window_clone) {
@@ -59,20 +68,57 @@ pub fn setup_application_ui(app: &Application) {
             if core::aur::init() {
                 info!("AUR helper initialized successfully");
             }
+            setup_aur_helper_dropdown(&builder_clone);
+            core::privilege::init();
             info!("Dependency check passed");
+            crate::ui::aur_setup::offer_install(&window_clone, &builder_clone);
         }
         glib::ControlFlow::Break
     });
 
-    info!("Xero Toolkit application startup complete");
+    info!(
+        "{} application startup complete",
+        config::app_info::DISPLAY_NAME
+    );
+    Ok(())
+}
+
+/// Show why the application could not start, in place of the main window.
+pub fn show_startup_error(app: &Application, error: &anyhow::Error) {
+    error!("Failed to set up the application: {:#}", error);
+
+    let status_page = adw::StatusPage::builder()
+        .icon_name("dialog-error-symbolic")
+        .title(format!(
+            "{} Could Not Start",
+            config::app_info::DISPLAY_NAME
+        ))
+        .description(glib::markup_escape_text(&format!(
+            "{:#}\n\nThe application may be installed incorrectly.",
+            error
+        )))
+        .build();
+
+    let content = adw::ToolbarView::new();
+    content.add_top_bar(&adw::HeaderBar::new());
+    content.set_content(Some(&status_page));
+
+    adw::ApplicationWindow::builder()
+        .application(app)
+        .title(config::app_info::DISPLAY_NAME)
+        .default_width(520)
+        .default_height(360)
+        .content(&content)
+        .build()
+        .present();
 }
 
 /// Set up resources and theme.
-fn setup_resources_and_theme() {
+fn setup_resources_and_theme() -> anyhow::Result<()> {
     info!("Setting up resources and theme");
 
     gio::resources_register_include!("xyz.xerolinux.xero-toolkit.gresource")
-        .expect("Failed to register gresources");
+        .context("Failed to register gresources")?;
 
     if let Some(display) = gtk4::gdk::Display::default() {
         info!("Setting up UI theme and styling");
@@ -90,22 +136,25 @@ fn setup_resources_and_theme() {
             &css_provider,
             gtk4::STYLE_PROVIDER_PRIORITY_APPLICATION,
         );
+        apply_color_scheme(config::user::get().color_scheme);
         info!("UI theme and styling loaded successfully");
     } else {
         warn!("No default display found - UI theming may not work properly");
     }
+    Ok(())
 }
 
 /// Create main application window.
-fn create_main_window(app: &Application, builder: &Builder) -> ApplicationWindow {
-    let window: ApplicationWindow = extract_widget(builder, "app_window");
+fn create_main_window(app: &Application, builder: &Builder) -> anyhow::Result<ApplicationWindow> {
+    let window: ApplicationWindow = try_extract_widget(builder, "app_window")?;
 
     window.set_application(Some(app));
-    info!("Setting window icon to xero-toolkit");
-    window.set_icon_name(Some("xero-toolkit"));
+    window.set_title(Some(config::app_info::DISPLAY_NAME));
+    info!("Setting window icon to {}", config::app_info::NAME);
+    window.set_icon_name(Some(config::app_info::NAME));
     info!("Main application window created from UI resource");
 
-    window
+    Ok(window)
 }
 
 /// Set up UI components and return application context.
@@ -117,6 +166,13 @@ fn setup_ui_components(builder: &Builder, stack: Stack, window: &ApplicationWind
     // Set up autostart toggle in sidebar
     setup_autostart_toggle(builder);
 
+    // Set up color scheme selection in sidebar
+    setup_color_scheme_dropdown(builder);
+
+    // Set up system information button
+    setup_system_info_button(builder, window);
+    setup_last_log_button(builder, window);
+
     // Set up about button
     setup_about_button(builder, window);
 
@@ -129,6 +185,7 @@ fn setup_ui_components(builder: &Builder, stack: Stack, window: &ApplicationWind
 
     // Configure sidebar with size constraints from config
     ui.configure_sidebar(config::sidebar::MIN_WIDTH, config::sidebar::MAX_WIDTH);
+    navigation::setup_compact_sidebar(builder);
 
     AppContext::new(ui)
 }
@@ -163,6 +220,110 @@ fn setup_autostart_toggle(builder: &Builder) {
     });
 }
 
+/// Set up the AUR helper dropdown in the sidebar.
+///
+/// Only shown when more than one helper is installed. The choice takes
+/// effect immediately and is saved to the user config.
+fn setup_aur_helper_dropdown(builder: &Builder) {
+    let row = extract_widget::<gtk4::Box>(builder, "aur_helper_row");
+    let dropdown = extract_widget::<gtk4::DropDown>(builder, "aur_helper_dropdown");
+
+    let helpers = core::aur::installed();
+    if helpers.len() < 2 {
+        return;
+    }
+
+    dropdown.set_model(Some(&gtk4::StringList::new(&helpers)));
+    if let Some(position) =
+        core::aur_helper().and_then(|current| helpers.iter().position(|helper| *helper == current))
+    {
+        dropdown.set_selected(position as u32);
+    }
+    row.set_visible(true);
+
+    dropdown.connect_selected_notify(move |dropdown| {
+        let Some(&helper) = helpers.get(dropdown.selected() as usize) else {
+            return;
+        };
+        info!("AUR helper changed to: {}", helper);
+        core::aur::set(helper);
+
+        let mut user_config = config::user::UserConfig::load();
+        user_config.aur_helper = Some(helper.to_string());
+        if let Err(e) = user_config.save() {
+            warn!("Failed to save AUR helper to config: {}", e);
+        }
+    });
+}
+
+/// Color schemes offered in the sidebar, in dropdown order.
+const COLOR_SCHEMES: [(ColorScheme, &str); 3] = [
+    (ColorScheme::System, "System"),
+    (ColorScheme::Light, "Light"),
+    (ColorScheme::Dark, "Dark"),
+];
+
+/// Make libadwaita use the chosen color scheme.
+fn apply_color_scheme(scheme: ColorScheme) {
+    let scheme = match scheme {
+        ColorScheme::System => adw::ColorScheme::Default,
+        ColorScheme::Light => adw::ColorScheme::ForceLight,
+        ColorScheme::Dark => adw::ColorScheme::ForceDark,
+    };
+    adw::StyleManager::default().set_color_scheme(scheme);
+}
+
+/// Set up the color scheme dropdown in the sidebar.
+///
+/// The choice applies immediately and is saved to the user config.
+fn setup_color_scheme_dropdown(builder: &Builder) {
+    let dropdown = extract_widget::<gtk4::DropDown>(builder, "color_scheme_dropdown");
+
+    let labels: Vec<&str> = COLOR_SCHEMES.iter().map(|(_, label)| *label).collect();
+    dropdown.set_model(Some(&gtk4::StringList::new(&labels)));
+    let current = config::user::get().color_scheme;
+    if let Some(position) = COLOR_SCHEMES
+        .iter()
+        .position(|(scheme, _)| *scheme == current)
+    {
+        dropdown.set_selected(position as u32);
+    }
+
+    dropdown.connect_selected_notify(move |dropdown| {
+        let Some(&(scheme, label)) = COLOR_SCHEMES.get(dropdown.selected() as usize) else {
+            return;
+        };
+        info!("Color scheme changed to: {}", label);
+        apply_color_scheme(scheme);
+
+        let mut user_config = config::user::UserConfig::load();
+        user_config.color_scheme = scheme;
+        if let Err(e) = user_config.save() {
+            warn!("Failed to save color scheme to config: {}", e);
+        }
+    });
+}
+
+/// Set up the system information button in the header bar.
+fn setup_system_info_button(builder: &Builder, window: &ApplicationWindow) {
+    let button = extract_widget::<gtk4::Button>(builder, "system_info_button");
+    let window = window.clone();
+    button.connect_clicked(move |_| {
+        info!("System information button clicked");
+        crate::ui::dialogs::system_info::show_system_info_dialog(&window);
+    });
+}
+
+/// Set up the button that reopens the last run's output.
+fn setup_last_log_button(builder: &Builder, window: &ApplicationWindow) {
+    let button = extract_widget::<gtk4::Button>(builder, "last_log_button");
+    let window = window.clone();
+    button.connect_clicked(move |_| {
+        info!("Last run output button clicked");
+        crate::ui::dialogs::last_run::show_last_run_dialog(&window);
+    });
+}
+
 /// Set up the about button in the header bar.
 fn setup_about_button(builder: &Builder, window: &ApplicationWindow) {
     use crate::ui::dialogs::about;
@@ -181,8 +342,8 @@ fn setup_seasonal_effects_toggle(builder: &Builder, _window: &ApplicationWindow)
 
     let toggle = extract_widget::<gtk4::ToggleButton>(builder, "seasonal_effects_toggle");
 
-    // Show/hide button based on whether any effect is active
-    let has_active = seasonal::has_active_effect();
+    // Show/hide button based on whether any effect will be shown
+    let has_active = seasonal::should_enable_seasonal() != seasonal::SeasonalMode::Off;
     toggle.set_visible(has_active);
     toggle.set_active(seasonal::are_effects_enabled());
 
@@ -0,0 +1,210 @@
+//! Setup of an AUR helper when none is installed.
+//!
+//! AUR actions need paru, yay, pikaur or aura. Without one the user is told
+//! at startup and offered to build paru from the AUR, instead of finding out
+//! when an AUR action fails.
+
+use crate::core;
+use crate::ui::dialogs::error::show_error;
+use crate::ui::task_runner::{self, Command, CommandSequence, RunOptions};
+use crate::ui::utils::extract_widget;
+use adw::prelude::*;
+use gtk4::{ApplicationWindow, Builder, Button};
+use log::{info, warn};
+use std::path::{Path, PathBuf};
+
+/// Helper built when none is installed.
+const BOOTSTRAP_HELPER: &str = "paru";
+
+/// AUR repository of [`BOOTSTRAP_HELPER`].
+const BOOTSTRAP_REPO: &str = "https://aur.archlinux.org/paru.git";
+
+/// Steps that build and install paru, with the directory they build in.
+pub struct Bootstrap {
+    commands: CommandSequence,
+    build_dir: PathBuf,
+}
+
+impl Bootstrap {
+    /// Run the steps, then start using paru if it was installed.
+    ///
+    /// The build directory is removed afterwards, whether the run succeeded
+    /// or not.
+    fn run(self, window: &ApplicationWindow, on_installed: impl Fn() + 'static) {
+        let build_dir = self.build_dir;
+        let options = RunOptions::new().on_complete(move |report| {
+            if build_dir.exists() {
+                if let Err(e) = std::fs::remove_dir_all(&build_dir) {
+                    warn!("Failed to remove {}: {}", build_dir.display(), e);
+                }
+            }
+            if report.success && core::aur::init() {
+                info!("AUR helper installed: {:?}", core::aur_helper());
+                on_installed();
+            }
+        });
+
+        task_runner::run_with_options(
+            window.upcast_ref(),
+            self.commands,
+            "Install AUR Helper",
+            options,
+        );
+    }
+}
+
+/// AUR helper in use, or how to get one.
+pub enum AurHelper {
+    /// Name of the helper in use
+    Ready(&'static str),
+    /// No helper is installed, these steps install paru
+    Install(Bootstrap),
+    /// No helper is installed and paru can't be built, with the reason
+    Unavailable(String),
+}
+
+/// Get the AUR helper in use, or the steps that install one.
+pub fn ensure_aur_helper() -> AurHelper {
+    if let Some(helper) = core::aur_helper() {
+        return AurHelper::Ready(helper);
+    }
+
+    let build_dir = std::env::temp_dir().join(format!(
+        "xero-toolkit-{}-{:016x}",
+        BOOTSTRAP_HELPER,
+        rand::random::<u64>()
+    ));
+    match build_user() {
+        Ok(user) => AurHelper::Install(Bootstrap {
+            commands: build_bootstrap_commands(&build_dir, user.as_deref()),
+            build_dir,
+        }),
+        Err(reason) => AurHelper::Unavailable(reason),
+    }
+}
+
+/// User to build as when the toolkit itself runs as root.
+///
+/// makepkg refuses to run as root, so the build drops to the user who
+/// started the toolkit through sudo. None when already unprivileged.
+fn build_user() -> Result<Option<String>, String> {
+    if core::privilege::current_user().as_deref() != Some("root") {
+        return Ok(None);
+    }
+
+    match std::env::var("SUDO_USER") {
+        Ok(user) if !user.is_empty() && user != "root" => Ok(Some(user)),
+        _ => Err(format!(
+            "{} can't be built as root. Start the toolkit as a regular user to install it.",
+            BOOTSTRAP_HELPER
+        )),
+    }
+}
+
+/// Build paru in `build_dir` and install it.
+///
+/// Only fetching the build dependencies and installing the built package
+/// run as root, the sources are fetched and built as the user, or as
+/// `build_user` if set.
+fn build_bootstrap_commands(build_dir: &Path, build_user: Option<&str>) -> CommandSequence {
+    let build_dir_arg = build_dir.to_string_lossy();
+    let unprivileged = || match build_user {
+        Some(user) => Command::builder().privileged().run_as(user),
+        None => Command::builder().normal(),
+    };
+
+    CommandSequence::new()
+        .then(Command::install_native(
+            &["base-devel", "git", "rust"],
+            "paru build dependencies",
+        ))
+        .then(
+            unprivileged()
+                .program("git")
+                .args(&["clone", "--depth", "1", BOOTSTRAP_REPO, &build_dir_arg])
+                .description("Downloading paru sources...")
+                .build(),
+        )
+        .then(
+            unprivileged()
+                .program("makepkg")
+                .args(&["--noconfirm"])
+                .in_dir(build_dir)
+                .description("Building paru...")
+                .weight(5.0)
+                .build(),
+        )
+        .then(
+            Command::builder()
+                .privileged()
+                .program("bash")
+                .args(&[
+                    "-c",
+                    &format!(
+                        "pacman -U --noconfirm '{}'/paru-[0-9]*.pkg.tar.*",
+                        build_dir_arg
+                    ),
+                ])
+                .description("Installing paru...")
+                .build(),
+        )
+        .build()
+}
+
+/// Show the missing AUR helper in the UI and offer to install one.
+///
+/// Does nothing if a helper is in use.
+pub fn offer_install(window: &ApplicationWindow, builder: &Builder) {
+    if core::aur_helper().is_some() {
+        return;
+    }
+    warn!(
+        "No AUR helper installed, offering to install {}",
+        BOOTSTRAP_HELPER
+    );
+
+    let button = extract_widget::<Button>(builder, "install_aur_helper_button");
+    button.set_visible(true);
+    let window_clone = window.clone();
+    button.connect_clicked(move |button| {
+        info!("Install AUR helper button clicked");
+        install(&window_clone, button);
+    });
+
+    let dialog = adw::AlertDialog::builder()
+        .heading("No AUR Helper Installed")
+        .body(
+            "Installing from the AUR needs paru, yay, pikaur or aura, so AUR actions \
+            won't work until one is installed.\n\nBuild and install paru now? You can also \
+            do this later from the sidebar.",
+        )
+        .build();
+    dialog.add_responses(&[("later", "Later"), ("install", "Install paru")]);
+    dialog.set_response_appearance("install", adw::ResponseAppearance::Suggested);
+    dialog.set_default_response(Some("install"));
+    dialog.set_close_response("later");
+
+    let window_clone = window.clone();
+    dialog.connect_response(None, move |_, response| {
+        if response == "install" {
+            install(&window_clone, &button);
+        }
+    });
+
+    dialog.present(Some(window));
+}
+
+/// Install paru, hiding `button` once an AUR helper is in use.
+fn install(window: &ApplicationWindow, button: &Button) {
+    match ensure_aur_helper() {
+        AurHelper::Ready(helper) => {
+            info!("AUR helper already installed: {}", helper);
+            button.set_visible(false);
+        }
+        AurHelper::Install(bootstrap) => {
+            let button = button.clone();
+            bootstrap.run(window, move || button.set_visible(false));
+        }
+        AurHelper::Unavailable(reason) => show_error(window, &reason),
+    }
+}
@@ -1,5 +1,6 @@
-//! About dialog showing creator information and credits.
+//! About dialog showing the application version, creator information and credits.
 
+use crate::config::{app_info, links};
 use crate::core::package;
 use crate::ui::utils::extract_widget;
 use gtk4::glib;
@@ -14,6 +15,15 @@ pub fn show_about_dialog(parent: &Window) {
     // Get the dialog window
     let dialog: Window = extract_widget(&builder, "about_window");
 
+    // Fill in the application identity
+    dialog.set_title(Some(&format!("About {}", app_info::DISPLAY_NAME)));
+    extract_widget::<Label>(&builder, "title_label").set_label(app_info::DISPLAY_NAME);
+    extract_widget::<Label>(&builder, "version_label").set_label(&format!(
+        "Version {} · {}",
+        app_info::VERSION,
+        app_info::ID
+    ));
+
     // Get the close button
     let close_button: Button = extract_widget(&builder, "close_button");
 
@@ -33,6 +43,14 @@ pub fn show_about_dialog(parent: &Window) {
     let synse_label = extract_widget::<Label>(&builder, "synse_donate_label");
     setup_link_handler(&synse_label);
 
+    let repository_label = extract_widget::<Label>(&builder, "repository_label");
+    repository_label.set_markup(&format!(
+        "Source code: <a href=\"{}\">{}</a>",
+        glib::markup_escape_text(links::REPOSITORY),
+        glib::markup_escape_text(links::REPOSITORY.trim_start_matches("https://"))
+    ));
+    setup_link_handler(&repository_label);
+
     // Set dialog as transient for parent
     dialog.set_transient_for(Some(parent));
 
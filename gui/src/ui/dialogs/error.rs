@@ -2,7 +2,7 @@
 
 use adw::prelude::*;
 use adw::AlertDialog;
-use gtk4::ApplicationWindow;
+use gtk4::{ApplicationWindow, Button, Expander, ScrolledWindow, TextView};
 
 /// Show an error message dialog transient for the provided window.
 pub fn show_error(window: &ApplicationWindow, message: &str) {
@@ -13,3 +13,63 @@ pub fn show_error(window: &ApplicationWindow, message: &str) {
 
     dialog.present(Some(window));
 }
+
+/// Show an error dialog with technical details below the summary.
+///
+/// The details, such as command output, are folded away in a selectable
+/// text area with a button to copy them for a bug report.
+pub fn show_error_with_details(window: &ApplicationWindow, summary: &str, details: &str) {
+    let dialog = AlertDialog::builder()
+        .heading("Error")
+        .body(summary)
+        .build();
+    dialog.add_response("close", "Close");
+
+    let text_view = TextView::builder()
+        .editable(false)
+        .monospace(true)
+        .wrap_mode(gtk4::WrapMode::WordChar)
+        .top_margin(6)
+        .bottom_margin(6)
+        .left_margin(6)
+        .right_margin(6)
+        .build();
+    text_view.buffer().set_text(details);
+
+    let scrolled = ScrolledWindow::builder()
+        .child(&text_view)
+        .min_content_height(160)
+        .max_content_height(320)
+        .propagate_natural_height(true)
+        .css_classes(["card"])
+        .build();
+
+    let copy_button = Button::builder()
+        .label("Copy Details")
+        .halign(gtk4::Align::End)
+        .build();
+    let details = details.to_string();
+    copy_button.connect_clicked(move |button| {
+        button.clipboard().set_text(&details);
+        button.set_label("Copied");
+    });
+
+    let content = gtk4::Box::new(gtk4::Orientation::Vertical, 6);
+    content.append(&scrolled);
+    content.append(&copy_button);
+
+    let expander = Expander::builder().label("Details").child(&content).build();
+    dialog.set_extra_child(Some(&expander));
+
+    dialog.present(Some(window));
+}
+
+/// Show an informational message dialog transient for the provided window.
+pub fn show_info(window: &ApplicationWindow, heading: &str, message: &str) {
+    let dialog = AlertDialog::builder()
+        .heading(heading)
+        .body(message)
+        .build();
+
+    dialog.present(Some(window));
+}
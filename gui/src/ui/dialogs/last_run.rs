@@ -0,0 +1,65 @@
+//! Read-only view of the last run's output.
+
+use crate::ui::dialogs::error::show_info;
+use crate::ui::task_runner;
+use adw::prelude::*;
+use gtk4::{ApplicationWindow, ScrolledWindow, TextView, WrapMode};
+
+/// Show the output of the last finished run.
+///
+/// Tells the user when no run has finished yet.
+pub fn show_last_run_dialog(window: &ApplicationWindow) {
+    let Some(last_run) = task_runner::last_run() else {
+        show_info(
+            window,
+            "No Output Yet",
+            "The output of the last operation is shown here once it finishes.",
+        );
+        return;
+    };
+
+    let steps = last_run.report.steps.len();
+    let summary = format!(
+        "{} after {} step{}",
+        if last_run.report.success {
+            "Completed"
+        } else {
+            "Failed"
+        },
+        steps,
+        if steps == 1 { "" } else { "s" }
+    );
+
+    let text_view = TextView::builder()
+        .editable(false)
+        .cursor_visible(false)
+        .monospace(true)
+        .wrap_mode(WrapMode::WordChar)
+        .top_margin(12)
+        .bottom_margin(12)
+        .left_margin(12)
+        .right_margin(12)
+        .build();
+    text_view.buffer().set_text(&last_run.output);
+
+    let scrolled = ScrolledWindow::builder()
+        .hexpand(true)
+        .vexpand(true)
+        .child(&text_view)
+        .build();
+
+    let header = adw::HeaderBar::builder()
+        .title_widget(&adw::WindowTitle::new(&last_run.title, &summary))
+        .build();
+    let toolbar = adw::ToolbarView::new();
+    toolbar.add_top_bar(&header);
+    toolbar.set_content(Some(&scrolled));
+
+    let dialog = adw::Dialog::builder()
+        .title(&last_run.title)
+        .content_width(720)
+        .content_height(480)
+        .child(&toolbar)
+        .build();
+    dialog.present(Some(window));
+}
@@ -2,14 +2,18 @@
 //!
 //! This module contains all dialog-related UI components:
 //! - `about`: About dialog with creator information
-//! - `error`: Simple error message dialogs
+//! - `error`: Error and info message dialogs, optionally with copyable details
+//! - `last_run`: Output of the last finished operation
 //! - `selection`: Multi-choice selection dialogs
+//! - `system_info`: System summary for bug reports
 //! - `download`: ISO download dialogs
 //! - `terminal`: Interactive terminal dialogs
 
 pub mod about;
 pub mod download;
 pub mod error;
+pub mod last_run;
 pub mod selection;
+pub mod system_info;
 pub mod terminal;
 pub mod warning;
@@ -5,9 +5,10 @@
 
 use crate::ui::utils::extract_widget;
 use gtk4::prelude::*;
-use gtk4::{Box as GtkBox, Builder, Button, CheckButton, Label, Separator, Window};
+use gtk4::{Box as GtkBox, Builder, Button, CheckButton, Label, SearchEntry, Separator, Window};
 use log::info;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::rc::Rc;
 
 /// Represents a selectable option in the dialog
@@ -17,6 +18,16 @@ pub struct SelectionOption {
     pub label: String,
     pub description: String,
     pub installed: bool,
+    /// Pre-selected options start checked but can still be deselected
+    pub selected: bool,
+    /// Options sharing a group are mutually exclusive and render as radio buttons
+    pub group: Option<String>,
+    /// Disabled options are greyed out and can't be selected
+    pub enabled: bool,
+    /// Tooltip explaining why the option is disabled
+    pub disabled_reason: Option<String>,
+    /// IDs of options that must be selected along with this one
+    pub requires: Vec<String>,
 }
 
 impl SelectionOption {
@@ -27,8 +38,49 @@ impl SelectionOption {
             label: label.to_string(),
             description: description.to_string(),
             installed,
+            selected: false,
+            group: None,
+            enabled: true,
+            disabled_reason: None,
+            requires: Vec::new(),
         }
     }
+
+    /// Require other options, by ID, to be selected along with this one
+    ///
+    /// How unmet requirements are handled is set by [`DependencyMode`].
+    #[allow(dead_code)]
+    // For dialogs whose options build on each other
+    pub fn requires(mut self, ids: &[&str]) -> Self {
+        self.requires = ids.iter().map(|id| id.to_string()).collect();
+        self
+    }
+
+    /// Start with the option checked, leaving it up to the user to deselect
+    pub fn selected(mut self) -> Self {
+        self.selected = true;
+        self
+    }
+
+    /// Disable the option, showing `reason` as its tooltip
+    ///
+    /// Use for options that are incompatible with the current system.
+    pub fn disabled(mut self, reason: &str) -> Self {
+        self.enabled = false;
+        self.disabled_reason = Some(reason.to_string());
+        self
+    }
+
+    /// Put the option in a mutually exclusive group
+    ///
+    /// Only applies to multi-selection dialogs, where at most one option per
+    /// group can be selected alongside any standalone options.
+    #[allow(dead_code)]
+    // For dialogs mixing exclusive choices with optional extras
+    pub fn in_group(mut self, group: &str) -> Self {
+        self.group = Some(group.to_string());
+        self
+    }
 }
 
 /// Selection type for the dialog
@@ -40,6 +92,17 @@ pub enum SelectionType {
     Multi,
 }
 
+/// How the dialog handles options whose requirements are not selected
+#[allow(dead_code)]
+// `Warn` is only constructed by dialogs that opt into it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DependencyMode {
+    /// Select required options automatically
+    AutoSelect,
+    /// Block confirming and explain which requirement is missing
+    Warn,
+}
+
 /// Configuration for the selection dialog
 pub struct SelectionDialogConfig {
     pub title: String,
@@ -48,6 +111,8 @@ pub struct SelectionDialogConfig {
     pub confirm_label: String,
     pub selection_type: SelectionType,
     pub selection_required: bool,
+    pub searchable: bool,
+    pub dependency_mode: DependencyMode,
 }
 
 impl SelectionDialogConfig {
@@ -60,6 +125,8 @@ impl SelectionDialogConfig {
             confirm_label: "Install".to_string(),
             selection_type: SelectionType::Multi,
             selection_required: true,
+            searchable: false,
+            dependency_mode: DependencyMode::AutoSelect,
         }
     }
 
@@ -86,6 +153,50 @@ impl SelectionDialogConfig {
         self.selection_required = required;
         self
     }
+
+    /// Set whether to show a search entry that filters the options
+    ///
+    /// Options are matched by label and description. Hidden options keep
+    /// their selection and are still returned on confirm.
+    pub fn searchable(mut self, searchable: bool) -> Self {
+        self.searchable = searchable;
+        self
+    }
+
+    /// Set how options with unmet requirements are handled
+    #[allow(dead_code)]
+    // Dialogs use auto-selection unless they opt into warnings
+    pub fn dependency_mode(mut self, mode: DependencyMode) -> Self {
+        self.dependency_mode = mode;
+        self
+    }
+}
+
+/// Find the first selected option with a requirement that isn't selected
+///
+/// Returns a message naming the option and the missing requirement.
+fn unmet_requirement(
+    options: &[SelectionOption],
+    is_selected: impl Fn(&str) -> bool,
+) -> Option<String> {
+    options
+        .iter()
+        .filter(|option| is_selected(&option.id))
+        .find_map(|option| {
+            let missing = option.requires.iter().find(|id| !is_selected(id))?;
+            let missing_label = options
+                .iter()
+                .find(|other| &other.id == missing)
+                .map_or(missing.as_str(), |other| other.label.as_str());
+            Some(format!("{} requires {}", option.label, missing_label))
+        })
+}
+
+/// Check if an option matches a lowercase search query
+fn matches_query(option: &SelectionOption, query: &str) -> bool {
+    query.is_empty()
+        || option.label.to_lowercase().contains(query)
+        || option.description.to_lowercase().contains(query)
 }
 
 /// Show a selection dialog and call the callback with selected option IDs
@@ -110,6 +221,8 @@ where
     let options_container: GtkBox = extract_widget(&builder, "options_container");
     let cancel_button: Button = extract_widget(&builder, "cancel_button");
     let confirm_button: Button = extract_widget(&builder, "confirm_button");
+    let search_entry: SearchEntry = extract_widget(&builder, "search_entry");
+    let requirement_label: Label = extract_widget(&builder, "requirement_label");
 
     // Set title and description
     title_label.set_label(&config.title);
@@ -122,6 +235,9 @@ where
     let selection_required = config.selection_required;
 
     let mut first_radio: Option<CheckButton> = None;
+    let mut group_leaders: HashMap<String, CheckButton> = HashMap::new();
+    let mut option_rows: Vec<GtkBox> = Vec::new();
+    let mut separators: Vec<Separator> = Vec::new();
 
     for (i, option) in config.options.iter().enumerate() {
         // Horizontal box: checkbox/radio on left, text on right
@@ -135,8 +251,17 @@ where
         match selection_type {
             SelectionType::Multi => {
                 let checkbox = CheckButton::new();
-                checkbox.set_active(option.installed);
-                checkbox.set_sensitive(!option.installed);
+                // Grouped options become radio buttons of their group's first option
+                if let Some(group) = &option.group {
+                    match group_leaders.get(group) {
+                        Some(leader) => checkbox.set_group(Some(leader)),
+                        None => {
+                            group_leaders.insert(group.clone(), checkbox.clone());
+                        }
+                    }
+                }
+                checkbox.set_active(option.installed || option.selected);
+                checkbox.set_sensitive(option.enabled && !option.installed);
                 checkboxes
                     .borrow_mut()
                     .push((option.id.clone(), checkbox.clone()));
@@ -148,7 +273,7 @@ where
                 let title_label = Label::new(Some(&option.label));
                 title_label.set_halign(gtk4::Align::Start);
                 title_label.set_wrap(true);
-                if option.installed {
+                if option.installed || !option.enabled {
                     title_label.set_css_classes(&["dim"]);
                 }
 
@@ -173,8 +298,8 @@ where
                     first_radio = Some(radio.clone());
                     radio
                 };
-                radio.set_active(option.installed);
-                radio.set_sensitive(!option.installed);
+                radio.set_active(option.installed || option.selected);
+                radio.set_sensitive(option.enabled && !option.installed);
                 radio_buttons
                     .borrow_mut()
                     .push((option.id.clone(), radio.clone()));
@@ -186,7 +311,7 @@ where
                 let title_label = Label::new(Some(&option.label));
                 title_label.set_halign(gtk4::Align::Start);
                 title_label.set_wrap(true);
-                if option.installed {
+                if option.installed || !option.enabled {
                     title_label.set_css_classes(&["dim"]);
                 }
 
@@ -203,17 +328,45 @@ where
             }
         }
 
+        if let Some(reason) = &option.disabled_reason {
+            option_row.set_tooltip_text(Some(reason));
+        }
+
         options_container.append(&option_row);
+        option_rows.push(option_row);
 
         // Add separator between options (not after the last one)
         if i < config.options.len() - 1 {
             let sep = Separator::new(gtk4::Orientation::Horizontal);
             options_container.append(&sep);
+            separators.push(sep);
         }
     }
 
+    // Filter rows by hiding them, so their check state is left untouched
+    if config.searchable {
+        search_entry.set_visible(true);
+
+        let options = config.options.clone();
+        search_entry.connect_search_changed(move |entry| {
+            let query = entry.text().trim().to_lowercase();
+
+            for (option, row) in options.iter().zip(&option_rows) {
+                row.set_visible(matches_query(option, &query));
+            }
+            // Separators would pile up between hidden rows
+            for sep in &separators {
+                sep.set_visible(query.is_empty());
+            }
+        });
+    }
+
     // Set initial state of confirm button based on selection_required
-    if selection_required {
+    let has_preselection = config
+        .options
+        .iter()
+        .any(|option| option.selected && option.enabled && !option.installed);
+    if selection_required && !has_preselection {
         confirm_button.set_sensitive(false);
     }
 
@@ -224,11 +377,19 @@ where
         dialog_clone.close();
     });
 
+    let buttons = match selection_type {
+        SelectionType::Multi => checkboxes.clone(),
+        SelectionType::Single => radio_buttons.clone(),
+    };
+
     // Update confirm button sensitivity based on selection
     let update_confirm_button = {
         let confirm_button_clone = confirm_button.clone();
         let checkboxes_clone = checkboxes.clone();
         let radio_buttons_clone = radio_buttons.clone();
+        let buttons = buttons.clone();
+        let options = config.options.clone();
+        let requirement_label = requirement_label.clone();
 
         move || {
             let has_selection = match selection_type {
@@ -242,7 +403,18 @@ where
                     .any(|(_, radio)| radio.is_active()),
             };
 
-            if selection_required {
+            let unmet = unmet_requirement(&options, |id| {
+                buttons
+                    .borrow()
+                    .iter()
+                    .any(|(option_id, button)| option_id == id && button.is_active())
+            });
+            requirement_label.set_label(unmet.as_deref().unwrap_or_default());
+            requirement_label.set_visible(unmet.is_some());
+
+            if unmet.is_some() {
+                confirm_button_clone.set_sensitive(false);
+            } else if selection_required {
                 confirm_button_clone.set_sensitive(has_selection);
             } else {
                 confirm_button_clone.set_sensitive(true);
@@ -266,6 +438,34 @@ where
         connect_toggle_handler(radio);
     }
 
+    // Select requirements along with the options that need them
+    if config.dependency_mode == DependencyMode::AutoSelect {
+        for option in config.options.iter().filter(|o| !o.requires.is_empty()) {
+            let Some(button) = buttons
+                .borrow()
+                .iter()
+                .find(|(id, _)| *id == option.id)
+                .map(|(_, button)| button.clone())
+            else {
+                continue;
+            };
+
+            let buttons = buttons.clone();
+            let requires = option.requires.clone();
+            button.connect_toggled(move |button| {
+                if !button.is_active() {
+                    return;
+                }
+                for (id, required) in buttons.borrow().iter() {
+                    // Disabled requirements stay unmet and block confirming
+                    if requires.contains(id) && required.is_sensitive() {
+                        required.set_active(true);
+                    }
+                }
+            });
+        }
+    }
+
     // Confirm button - collect selected options and call callback
     let dialog_clone = dialog.clone();
     let checkboxes_clone = checkboxes.clone();
@@ -301,3 +501,23 @@ where
     // Show the dialog
     dialog.present();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unmet_requirement() {
+        let options = vec![
+            SelectionOption::new("driver", "NVIDIA Driver", "", false),
+            SelectionOption::new("cuda", "CUDA", "", false).requires(&["driver"]),
+        ];
+
+        assert_eq!(unmet_requirement(&options, |_| false), None);
+        assert_eq!(
+            unmet_requirement(&options, |id| id == "cuda").as_deref(),
+            Some("CUDA requires NVIDIA Driver")
+        );
+        assert_eq!(unmet_requirement(&options, |_| true), None);
+    }
+}
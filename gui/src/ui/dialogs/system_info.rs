@@ -0,0 +1,43 @@
+//! System information dialog with a copyable summary for bug reports.
+
+use crate::core::sysinfo::collect_system_info;
+use adw::prelude::*;
+use gtk4::glib;
+use gtk4::{ApplicationWindow, ListBox, SelectionMode};
+
+/// Show the system summary with a button that copies it as text.
+pub fn show_system_info_dialog(window: &ApplicationWindow) {
+    let info = collect_system_info();
+
+    let list = ListBox::builder()
+        .selection_mode(SelectionMode::None)
+        .css_classes(["boxed-list"])
+        .build();
+    for (name, value) in info.fields() {
+        let row = adw::ActionRow::builder()
+            .title(name)
+            .subtitle(glib::markup_escape_text(&value))
+            .subtitle_selectable(true)
+            .css_classes(["property"])
+            .build();
+        list.append(&row);
+    }
+
+    let dialog = adw::AlertDialog::builder()
+        .heading("System Information")
+        .body("Include this summary when reporting a problem.")
+        .extra_child(&list)
+        .build();
+    dialog.add_responses(&[("close", "Close"), ("copy", "Copy")]);
+    dialog.set_response_appearance("copy", adw::ResponseAppearance::Suggested);
+    dialog.set_default_response(Some("copy"));
+    dialog.set_close_response("close");
+
+    let report = info.to_report();
+    let window_clone = window.clone();
+    dialog.connect_response(Some("copy"), move |_, _| {
+        window_clone.clipboard().set_text(&report);
+    });
+
+    dialog.present(Some(window));
+}
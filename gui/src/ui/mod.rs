@@ -1,21 +1,27 @@
 //! User interface components and functionality.
 //!
 //! This module contains all UI-related components organized by functionality:
+//! - `action_search`: Search across the actions of all pages
 //! - `app`: Application setup and initialization
+//! - `aur_setup`: Installing an AUR helper when none is found
 //! - `context`: Application state and UI components
 //! - `navigation`: Tab navigation and sidebar management
 //! - `dialogs`: Dialog windows (error, selection, download)
+//! - `state`: UI state persisted across restarts
 //! - `task_runner`: Command execution with progress UI
 //! - `pages`: Page-specific button handlers
 
+pub mod action_search;
 pub mod app;
+pub mod aur_setup;
 pub mod context;
 pub mod dialogs;
 pub mod navigation;
 pub mod pages;
 pub mod seasonal;
+pub mod state;
 pub mod task_runner;
 pub mod utils;
 
 // Re-export the main entry point
-pub use app::setup_application_ui;
+pub use app::{setup_application_ui, show_startup_error};
@@ -3,10 +3,18 @@
 //! This module handles the sidebar navigation tabs that allow users
 //! to switch between different pages in the application.
 
+use crate::config;
+use crate::ui::action_search::{self, ActionEntry};
 use crate::ui::pages;
-use gtk4::prelude::*;
-use gtk4::{ApplicationWindow, Box as GtkBox, Builder, Button, Image, Label, Orientation, Stack};
+use crate::ui::state::UiState;
+use adw::prelude::*;
+use gtk4::{
+    accessible, glib, AccessibleRole, ApplicationWindow, Box as GtkBox, Builder, Button,
+    CallbackAction, EventControllerMotion, Image, Label, ListBox, Orientation, SelectionMode,
+    Shortcut, ShortcutController, ShortcutScope, ShortcutTrigger, Stack, ToggleButton,
+};
 use log::{info, warn};
+use std::rc::Rc;
 
 /// Configuration for a single page in the application.
 pub struct PageConfig {
@@ -74,13 +82,13 @@ pub const PAGES: &[PageConfig] = &[
         ui_resource: crate::config::resources::tabs::MULTIMEDIA_TOOLS,
         setup_handler: Some(pages::multimedia_tools::setup_handlers),
     },
-    // PageConfig {
-    //     id: "kernel_manager_scx",
-    //     title: "Kernel Manager/SCX",
-    //     icon: "hammer-symbolic",
-    //     ui_resource: "/xyz/xerolinux/xero-toolkit/ui/tabs/kernel_manager_scx.ui",
-    //     setup_handler: None,
-    // },
+    PageConfig {
+        id: "kernel_manager_scx",
+        title: "Kernel Manager/SCX",
+        icon: "hammer-symbolic",
+        ui_resource: crate::config::resources::tabs::KERNEL_MANAGER_SCX,
+        setup_handler: Some(pages::kernel_manager_scx::setup_handlers),
+    },
     PageConfig {
         id: "servicing_system_tweaks",
         title: "Servicing/System tweaks",
@@ -90,6 +98,56 @@ pub const PAGES: &[PageConfig] = &[
     },
 ];
 
+/// Number of tabs reachable with Ctrl+1 to Ctrl+8.
+const NUMBERED_SHORTCUTS: usize = 8;
+
+/// Sidebar tab buttons with their pages, in tab order.
+type TabButtons = Rc<Vec<(&'static PageConfig, Button)>>;
+
+/// Pages in the tab order of the user config, with whether their tab is shown.
+///
+/// Pages missing from `order` follow in their default order. Unknown ids are
+/// logged and skipped, and if every tab would be hidden all are shown.
+fn configured_tabs(order: &[String], hidden: &[String]) -> Vec<(&'static PageConfig, bool)> {
+    let mut pages: Vec<&'static PageConfig> = Vec::with_capacity(PAGES.len());
+    for id in order {
+        match PAGES.iter().find(|page| page.id == id.as_str()) {
+            Some(page) if pages.iter().any(|listed| listed.id == page.id) => {
+                warn!("Tab '{}' is listed more than once in tab_order", id);
+            }
+            Some(page) => pages.push(page),
+            None => warn!("Ignoring unknown tab '{}' in tab_order", id),
+        }
+    }
+    let remaining: Vec<&'static PageConfig> = PAGES
+        .iter()
+        .filter(|page| !pages.iter().any(|listed| listed.id == page.id))
+        .collect();
+    pages.extend(remaining);
+
+    for id in hidden {
+        if !PAGES.iter().any(|page| page.id == id.as_str()) {
+            warn!("Ignoring unknown tab '{}' in hidden_tabs", id);
+        }
+    }
+    let is_hidden = |page: &PageConfig| hidden.iter().any(|id| id == page.id);
+    let all_hidden = pages.iter().all(|page| is_hidden(page));
+    if all_hidden {
+        warn!("hidden_tabs hides every tab, showing all of them");
+    }
+
+    pages
+        .into_iter()
+        .map(|page| (page, all_hidden || !is_hidden(page)))
+        .collect()
+}
+
+/// Tabs of the user config, see [`configured_tabs`].
+fn user_tabs() -> Vec<(&'static PageConfig, bool)> {
+    let user_config = config::user::get();
+    configured_tabs(&user_config.tab_order, &user_config.hidden_tabs)
+}
+
 /// Represents a single tab in the navigation sidebar.
 struct Tab {
     page_name: String,
@@ -117,9 +175,13 @@ impl Tab {
         let button = Button::builder()
             .hexpand(true)
             .css_classes(vec!["tab-button".to_string()])
+            .accessible_role(AccessibleRole::Tab)
             .build();
 
         button.set_child(Some(&content_box));
+        // Named even when the label is hidden in the compact sidebar
+        button.update_property(&[accessible::Property::Label(label)]);
+        button.update_state(&[accessible::State::Selected(Some(false))]);
 
         Tab {
             page_name: page_name.to_string(),
@@ -138,56 +200,374 @@ impl Tab {
             info!("Navigating to page '{}'", page_name);
             stack_clone.set_visible_child_name(&page_name);
             update_active_tab(&tabs_clone, &button_clone);
+
+            let page_name = page_name.clone();
+            UiState::update(move |state| state.last_page = Some(page_name));
         });
     }
 }
 
+/// Page to show on startup.
+///
+/// Restores the last visited page, falling back to the first shown tab if it
+/// was never saved, no longer exists or its tab is hidden.
+pub fn initial_page() -> Option<&'static str> {
+    let last_page = UiState::load().last_page;
+    let shown: Vec<&'static str> = user_tabs()
+        .into_iter()
+        .filter_map(|(page, shown)| shown.then_some(page.id))
+        .collect();
+    shown
+        .iter()
+        .find(|id| last_page.as_deref() == Some(**id))
+        .or(shown.first())
+        .copied()
+}
+
 /// Create dynamic stack with pages and set up navigation tabs.
 /// Returns the fully configured stack with all pages and tabs ready.
 pub fn create_stack_and_tabs(tabs_container: &GtkBox, main_builder: &Builder) -> Stack {
     info!("Creating dynamic stack and loading pages");
 
     // Create new stack and populate with pages
-    let stack = create_dynamic_stack(main_builder);
+    let (stack, actions) = create_dynamic_stack(main_builder);
 
     info!("Dynamic stack created with {} pages", PAGES.len());
 
-    // Set up navigation tabs
+    // Set up navigation tabs in the configured order
     info!("Setting up navigation tabs");
-    let mut first_button: Option<Button> = None;
+    let mut tabs = Vec::new();
 
-    for page_config in PAGES {
+    for (page_config, shown) in user_tabs() {
         let tab = Tab::new(page_config.title, page_config.id, page_config.icon);
         tab.connect(&stack, tabs_container);
-
-        if first_button.is_none() {
-            first_button = Some(tab.button.clone());
-        }
+        tab.button.set_visible(shown);
 
         tabs_container.append(&tab.button);
         info!("Added tab: {} -> '{}'", page_config.title, page_config.id);
+        tabs.push((page_config, tab));
     }
 
-    // Set first tab as active
-    if let Some(button) = first_button {
-        button.add_css_class("active");
+    // Mark the tab of the initially shown page as active
+    if let Some(page) = initial_page() {
+        if let Some((_, tab)) = tabs.iter().find(|(_, tab)| tab.page_name == page) {
+            set_tab_active(&tab.button, true);
+        }
     }
 
+    let tab_buttons: TabButtons = Rc::new(
+        tabs.into_iter()
+            .map(|(page_config, tab)| (page_config, tab.button))
+            .collect(),
+    );
+    update_tab_tooltips(&tab_buttons);
+
+    let window: ApplicationWindow = crate::ui::utils::extract_widget(main_builder, "app_window");
+    setup_shortcuts(&window, &stack, &tab_buttons);
+
+    let customize_button: Button =
+        crate::ui::utils::extract_widget(main_builder, "customize_tabs_button");
+    let window_clone = window.clone();
+    let stack_clone = stack.clone();
+    let tabs_clone = tab_buttons.clone();
+    customize_button.connect_clicked(move |_| {
+        info!("Customize tabs button clicked");
+        show_tab_settings(&window_clone, &stack_clone, &tabs_clone);
+    });
+
+    // Search results navigate by clicking the tab, like the mouse would
+    action_search::setup(main_builder, actions, move |page_name| {
+        if let Some((_, button)) = tab_buttons.iter().find(|(page, _)| page.id == page_name) {
+            button.emit_clicked();
+        }
+    });
+
     stack
 }
 
+/// Tabs currently shown in the sidebar, in tab order.
+fn shown_tabs(tabs: &TabButtons) -> Vec<(&'static PageConfig, Button)> {
+    tabs.iter()
+        .filter(|(_, button)| button.is_visible())
+        .cloned()
+        .collect()
+}
+
+/// Mention the Ctrl+N shortcut in the tooltips of the first shown tabs.
+fn update_tab_tooltips(tabs: &TabButtons) {
+    let shown = shown_tabs(tabs);
+    for (page, button) in tabs.iter() {
+        let tooltip = match shown
+            .iter()
+            .position(|(shown_page, _)| shown_page.id == page.id)
+        {
+            Some(i) if i < NUMBERED_SHORTCUTS => format!("{} (Ctrl+{})", page.title, i + 1),
+            _ => page.title.to_string(),
+        };
+        button.set_tooltip_text(Some(&tooltip));
+    }
+}
+
+/// Register window-scoped tab shortcuts.
+///
+/// Ctrl+1 to Ctrl+8 jump to a shown tab, Ctrl+Page Up/Down cycle through
+/// them. The shortcuts click the tab buttons, so navigation behaves as with
+/// the mouse.
+fn setup_shortcuts(window: &ApplicationWindow, stack: &Stack, tabs: &TabButtons) {
+    if tabs.is_empty() {
+        return;
+    }
+
+    let controller = ShortcutController::new();
+    controller.set_scope(ShortcutScope::Local);
+
+    for i in 0..NUMBERED_SHORTCUTS.min(tabs.len()) {
+        let tabs = tabs.clone();
+        add_shortcut(&controller, &format!("<Control>{}", i + 1), move || {
+            if let Some((_, button)) = shown_tabs(&tabs).get(i) {
+                button.emit_clicked();
+            }
+        });
+    }
+
+    for (accelerator, step) in [("<Control>Page_Down", 1), ("<Control>Page_Up", -1)] {
+        let stack = stack.clone();
+        let tabs = tabs.clone();
+        add_shortcut(&controller, accelerator, move || {
+            let shown = shown_tabs(&tabs);
+            if shown.is_empty() {
+                return;
+            }
+            let current = stack.visible_child_name();
+            let index = shown
+                .iter()
+                .position(|(page, _)| current.as_deref() == Some(page.id))
+                .unwrap_or(0);
+            let next = (index as isize + step).rem_euclid(shown.len() as isize) as usize;
+            shown[next].1.emit_clicked();
+        });
+    }
+
+    window.add_controller(controller);
+}
+
+/// Let the user choose which tabs are shown in the sidebar.
+///
+/// Changes apply immediately and are saved to the user config. The last
+/// shown tab can't be hidden.
+fn show_tab_settings(window: &ApplicationWindow, stack: &Stack, tabs: &TabButtons) {
+    let list = ListBox::builder()
+        .selection_mode(SelectionMode::None)
+        .css_classes(["boxed-list"])
+        .build();
+
+    for &(page, ref button) in tabs.iter() {
+        let row = adw::SwitchRow::builder()
+            .title(page.title)
+            .active(button.is_visible())
+            .build();
+        list.append(&row);
+
+        let stack = stack.clone();
+        let tabs = tabs.clone();
+        row.connect_active_notify(move |row| {
+            if !row.is_active() && shown_tabs(&tabs).len() <= 1 {
+                row.set_active(true);
+                return;
+            }
+            set_tab_shown(&stack, &tabs, page, row.is_active());
+        });
+    }
+
+    let dialog = adw::AlertDialog::builder()
+        .heading("Sidebar Tabs")
+        .body(format!(
+            "Choose the tabs shown in the sidebar. Their order can be changed with \
+            tab_order in {}.",
+            config::paths::config_file().display()
+        ))
+        .extra_child(&list)
+        .build();
+    dialog.add_response("close", "Close");
+    dialog.set_close_response("close");
+    dialog.present(Some(window));
+}
+
+/// Show or hide the tab of `page` and save the hidden tabs.
+///
+/// Hiding the tab of the visible page moves to the first shown tab.
+fn set_tab_shown(stack: &Stack, tabs: &TabButtons, page: &PageConfig, shown: bool) {
+    let Some((_, button)) = tabs.iter().find(|(tab_page, _)| tab_page.id == page.id) else {
+        return;
+    };
+    info!(
+        "{} tab '{}'",
+        if shown { "Showing" } else { "Hiding" },
+        page.id
+    );
+    button.set_visible(shown);
+
+    if !shown && stack.visible_child_name().as_deref() == Some(page.id) {
+        if let Some((_, first)) = shown_tabs(tabs).first() {
+            first.emit_clicked();
+        }
+    }
+    update_tab_tooltips(tabs);
+
+    let mut user_config = config::user::UserConfig::load();
+    user_config.hidden_tabs = tabs
+        .iter()
+        .filter(|(_, button)| !button.is_visible())
+        .map(|(page, _)| page.id.to_string())
+        .collect();
+    if let Err(e) = user_config.save() {
+        warn!("Failed to save hidden tabs to config: {}", e);
+    }
+}
+
+/// Add a shortcut running `callback` when `accelerator` is pressed.
+fn add_shortcut(controller: &ShortcutController, accelerator: &str, callback: impl Fn() + 'static) {
+    let action = CallbackAction::new(move |_, _| {
+        callback();
+        glib::Propagation::Stop
+    });
+    controller.add_shortcut(Shortcut::new(
+        ShortcutTrigger::parse_string(accelerator),
+        Some(action),
+    ));
+}
+
+/// Sidebar widgets changed when collapsing it to the tab icons.
+#[derive(Clone)]
+struct CompactSidebar {
+    sidebar: GtkBox,
+    header: GtkBox,
+    footer: GtkBox,
+    tabs_container: GtkBox,
+    split_view: adw::OverlaySplitView,
+}
+
+impl CompactSidebar {
+    /// Show only the tab icons, or the whole sidebar.
+    ///
+    /// The tab tooltips keep naming the pages while their labels are hidden.
+    fn set_compact(&self, compact: bool) {
+        self.header.set_visible(!compact);
+        self.footer.set_visible(!compact);
+
+        let mut child = self.tabs_container.first_child();
+        while let Some(widget) = child {
+            if let Some(label) = widget
+                .downcast_ref::<Button>()
+                .and_then(|button| button.child())
+                .and_then(|content| content.last_child())
+                .and_then(|label| label.downcast::<Label>().ok())
+            {
+                label.set_visible(!compact);
+            }
+            child = widget.next_sibling();
+        }
+
+        let (min_width, max_width) = if compact {
+            (
+                config::sidebar::COMPACT_WIDTH,
+                config::sidebar::COMPACT_WIDTH,
+            )
+        } else {
+            (config::sidebar::MIN_WIDTH, config::sidebar::MAX_WIDTH)
+        };
+        self.split_view.set_min_sidebar_width(min_width as f64);
+        self.split_view.set_max_sidebar_width(max_width as f64);
+
+        if compact {
+            self.sidebar.add_css_class("compact");
+        } else {
+            self.sidebar.remove_css_class("compact");
+        }
+    }
+}
+
+/// Set up collapsing the sidebar to the tab icons.
+///
+/// The pin button keeps the sidebar expanded; unpinned, it shows only the
+/// tab icons and expands while the pointer is over it. The choice is saved
+/// to the user config.
+pub fn setup_compact_sidebar(main_builder: &Builder) {
+    use crate::ui::utils::extract_widget;
+
+    let sidebar = CompactSidebar {
+        sidebar: extract_widget(main_builder, "sidebar"),
+        header: extract_widget(main_builder, "sidebar_header"),
+        footer: extract_widget(main_builder, "sidebar_footer"),
+        tabs_container: extract_widget(main_builder, "tabs_container"),
+        split_view: extract_widget(main_builder, "main_split_view"),
+    };
+    let pin_button: ToggleButton = extract_widget(main_builder, "sidebar_pin_button");
+
+    let update_pin_tooltip = |button: &ToggleButton| {
+        button.set_tooltip_text(Some(if button.is_active() {
+            "Collapse sidebar to icons"
+        } else {
+            "Keep sidebar expanded"
+        }));
+    };
+
+    let compact = config::user::get().compact_sidebar;
+    pin_button.set_active(!compact);
+    update_pin_tooltip(&pin_button);
+    sidebar.set_compact(compact);
+
+    let sidebar_clone = sidebar.clone();
+    pin_button.connect_toggled(move |button| {
+        let compact = !button.is_active();
+        info!(
+            "Compact sidebar {}",
+            if compact { "enabled" } else { "disabled" }
+        );
+        sidebar_clone.set_compact(compact);
+        update_pin_tooltip(button);
+
+        let mut user_config = config::user::UserConfig::load();
+        user_config.compact_sidebar = compact;
+        if let Err(e) = user_config.save() {
+            warn!("Failed to save compact sidebar to config: {}", e);
+        }
+    });
+
+    // Expand an unpinned sidebar while hovered
+    let motion = EventControllerMotion::new();
+    let (sidebar_clone, pin_clone) = (sidebar.clone(), pin_button.clone());
+    motion.connect_enter(move |_, _, _| {
+        if !pin_clone.is_active() {
+            sidebar_clone.set_compact(false);
+        }
+    });
+    let (sidebar_clone, pin_clone) = (sidebar.clone(), pin_button);
+    motion.connect_leave(move |_| {
+        if !pin_clone.is_active() {
+            sidebar_clone.set_compact(true);
+        }
+    });
+    sidebar.sidebar.add_controller(motion);
+}
+
 /// Create a dynamic stack with pages from PAGES configuration.
-fn create_dynamic_stack(main_builder: &Builder) -> Stack {
+///
+/// Also returns the searchable actions of all loaded pages.
+fn create_dynamic_stack(main_builder: &Builder) -> (Stack, Vec<ActionEntry>) {
     let stack = Stack::new();
     stack.set_hexpand(true);
     stack.set_vexpand(true);
     stack.set_transition_type(gtk4::StackTransitionType::Crossfade);
 
+    let mut actions = Vec::new();
+
     // Dynamically create stack pages from PAGES configuration
     for page_config in PAGES {
         match create_page_from_config(page_config, main_builder) {
-            Ok(page_widget) => {
+            Ok((page_widget, page_actions)) => {
                 stack.add_titled(&page_widget, Some(page_config.id), page_config.title);
+                actions.extend(page_actions);
                 info!("Successfully loaded page: {}", page_config.id);
             }
             Err(e) => {
@@ -209,15 +589,18 @@ fn create_dynamic_stack(main_builder: &Builder) -> Stack {
     right_container.append(&stack);
     info!("Dynamic stack added to right container");
 
-    stack
+    (stack, actions)
 }
 
 /// Create a page widget from PageConfig.
-fn create_page_from_config(config: &PageConfig, main_builder: &Builder) -> anyhow::Result<GtkBox> {
+fn create_page_from_config(
+    config: &PageConfig,
+    main_builder: &Builder,
+) -> anyhow::Result<(GtkBox, Vec<ActionEntry>)> {
     use crate::ui::utils::extract_widget;
     use gtk4::ApplicationWindow;
 
-    let page_builder = Builder::from_resource(config.ui_resource);
+    let page_builder = crate::ui::utils::load_builder(config.ui_resource)?;
 
     let page_widget: gtk4::Widget = page_builder
         .object(format!("page_{}", config.id))
@@ -242,7 +625,9 @@ fn create_page_from_config(config: &PageConfig, main_builder: &Builder) -> anyho
         setup_fn(&page_builder, main_builder, &window);
     }
 
-    Ok(container)
+    let actions = action_search::collect_actions(&page_builder, config.id, config.title);
+
+    Ok((container, actions))
 }
 
 /// Update which tab is marked as active.
@@ -251,12 +636,53 @@ fn update_active_tab(tabs_container: &GtkBox, clicked_button: &Button) {
 
     while let Some(widget) = child {
         if let Ok(button) = widget.clone().downcast::<Button>() {
-            if button == *clicked_button {
-                button.add_css_class("active");
-            } else {
-                button.remove_css_class("active");
-            }
+            set_tab_active(&button, button == *clicked_button);
         }
         child = widget.next_sibling();
     }
 }
+
+/// Mark a tab as the active one, for styling and assistive technologies.
+fn set_tab_active(button: &Button, active: bool) {
+    if active {
+        button.add_css_class("active");
+    } else {
+        button.remove_css_class("active");
+    }
+    button.update_state(&[accessible::State::Selected(Some(active))]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(tabs: &[(&'static PageConfig, bool)]) -> Vec<(&'static str, bool)> {
+        tabs.iter().map(|(page, shown)| (page.id, *shown)).collect()
+    }
+
+    #[test]
+    fn test_configured_tabs_order_and_visibility() {
+        let default = configured_tabs(&[], &[]);
+        assert_eq!(default.len(), PAGES.len());
+        assert!(default
+            .iter()
+            .zip(PAGES)
+            .all(|((page, shown), expected)| page.id == expected.id && *shown));
+
+        let order = ["drivers", "no_such_tab", "main_page", "drivers"].map(String::from);
+        let hidden = ["main_page".to_string()];
+        let tabs = ids(&configured_tabs(&order, &hidden));
+        assert_eq!(tabs.len(), PAGES.len());
+        assert_eq!(
+            &tabs[..3],
+            &[
+                ("drivers", true),
+                ("main_page", false),
+                ("customization", true)
+            ]
+        );
+
+        let all: Vec<String> = PAGES.iter().map(|page| page.id.to_string()).collect();
+        assert!(configured_tabs(&[], &all).iter().all(|(_, shown)| *shown));
+    }
+}
@@ -6,13 +6,14 @@
 //! - VirtualBox installation
 //! - DistroBox installation
 //! - KVM/QEMU virtualization setup
+//! - Docker and libvirt/QEMU stacks picked from one dialog
 
 use crate::core;
 use crate::ui::dialogs::selection::{
     show_selection_dialog, SelectionDialogConfig, SelectionOption, SelectionType,
 };
 use crate::ui::task_runner::{self, Command, CommandSequence};
-use crate::ui::utils::extract_widget;
+use crate::ui::utils::{extract_widget, set_command_tooltip};
 use gtk4::prelude::*;
 use gtk4::{ApplicationWindow, Builder, Button};
 use log::info;
@@ -24,62 +25,58 @@ pub fn setup_handlers(page_builder: &Builder, _main_builder: &Builder, window: &
     setup_vbox(page_builder, window);
     setup_distrobox(page_builder, window);
     setup_kvm(page_builder, window);
+    setup_container_stacks(page_builder, window);
 }
 
 fn setup_docker(builder: &Builder, window: &ApplicationWindow) {
     let button = extract_widget::<Button>(builder, "btn_docker");
+    set_command_tooltip(&button, || build_docker_commands(CommandSequence::new()));
     let window = window.clone();
 
     button.connect_clicked(move |_| {
         info!("Docker button clicked");
 
-        let user = crate::config::env::get().user.clone();
-
-        let commands = CommandSequence::new()
-            .then(
-                Command::builder()
-                    .aur()
-                    .args(&[
-                        "-S",
-                        "--noconfirm",
-                        "--needed",
-                        "docker",
-                        "docker-compose",
-                        "docker-buildx",
-                    ])
-                    .description("Installing Docker engine and tools...")
-                    .build(),
-            )
-            .then(
-                Command::builder()
-                    .privileged()
-                    .program("systemctl")
-                    .args(&["enable", "--now", "docker.service"])
-                    .description("Enabling Docker service...")
-                    .build(),
-            )
-            .then(
-                Command::builder()
-                    .privileged()
-                    .program("groupadd")
-                    .args(&["-f", "docker"])
-                    .description("Ensuring docker group exists...")
-                    .build(),
-            )
-            .then(
-                Command::builder()
-                    .privileged()
-                    .program("usermod")
-                    .args(&["-aG", "docker", &user])
-                    .description("Adding your user to docker group...")
-                    .build(),
-            )
-            .build();
+        let commands = build_docker_commands(CommandSequence::new()).build();
 
         task_runner::run(window.upcast_ref(), commands, "Docker Setup");
     });
 }
 
+/// Append steps installing Docker and giving the current user access to it.
+fn build_docker_commands(commands: CommandSequence) -> CommandSequence {
+    let user = crate::config::env::get().user.clone();
+
+    commands
+        .then(Command::install(
+            &["docker", "docker-compose", "docker-buildx"],
+            "Docker engine and tools",
+        ))
+        .then(
+            Command::builder()
+                .privileged()
+                .program("systemctl")
+                .args(&["enable", "--now", "docker.socket"])
+                .description("Enabling Docker socket...")
+                .build(),
+        )
+        .then(
+            Command::builder()
+                .privileged()
+                .program("groupadd")
+                .args(&["-f", "docker"])
+                .description("Ensuring docker group exists...")
+                .build(),
+        )
+        .then(
+            Command::builder()
+                .privileged()
+                .program("usermod")
+                .args(&["-aG", "docker", &user])
+                .description("Adding your user to docker group (log out and back in to apply)...")
+                .build(),
+        )
+}
+
 fn setup_podman(builder: &Builder, window: &ApplicationWindow) {
     let button = extract_widget::<Button>(builder, "btn_podman");
     let window = window.clone();
@@ -96,20 +93,17 @@ fn setup_podman(builder: &Builder, window: &ApplicationWindow) {
             "podman_desktop",
             "Podman Desktop",
             "Graphical interface for managing containers",
-            core::is_flatpak_installed("io.podman_desktop.PodmanDesktop"),
+            core::is_flatpak_installed("io.podman_desktop.PodmanDesktop", core::FlatpakKind::App),
         ))
         .confirm_label("Install");
 
         let window_for_closure = window.clone();
         show_selection_dialog(window.upcast_ref(), config, move |selected| {
             let mut commands = CommandSequence::new()
-                .then(
-                    Command::builder()
-                        .aur()
-                        .args(&["-S", "--noconfirm", "--needed", "podman", "podman-docker"])
-                        .description("Installing Podman container engine...")
-                        .build(),
-                )
+                .then(Command::install(
+                    &["podman", "podman-docker"],
+                    "Podman container engine",
+                ))
                 .then(
                     Command::builder()
                         .privileged()
@@ -122,8 +116,7 @@ fn setup_podman(builder: &Builder, window: &ApplicationWindow) {
             if selected.iter().any(|s| s == "podman_desktop") {
                 commands = commands.then(
                     Command::builder()
-                        .normal()
-                        .program("flatpak")
+                        .flatpak()
                         .args(&[
                             "install",
                             "-y",
@@ -148,119 +141,167 @@ fn setup_podman(builder: &Builder, window: &ApplicationWindow) {
 
 fn setup_vbox(builder: &Builder, window: &ApplicationWindow) {
     let button = extract_widget::<Button>(builder, "btn_vbox");
+    set_command_tooltip(&button, build_vbox_commands);
     let window = window.clone();
 
     button.connect_clicked(move |_| {
         info!("VirtualBox button clicked");
-
-        let commands = CommandSequence::new()
-            .then(
-                Command::builder()
-                    .aur()
-                    .args(&["-S", "--noconfirm", "--needed", "virtualbox-meta"])
-                    .description("Installing VirtualBox...")
-                    .build(),
-            )
-            .build();
+        let commands = build_vbox_commands();
 
         task_runner::run(window.upcast_ref(), commands, "VirtualBox Setup");
     });
 }
 
+/// Install VirtualBox.
+fn build_vbox_commands() -> CommandSequence {
+    CommandSequence::new()
+        .then(Command::install(&["virtualbox-meta"], "VirtualBox"))
+        .build()
+}
+
 fn setup_distrobox(builder: &Builder, window: &ApplicationWindow) {
     let button = extract_widget::<Button>(builder, "btn_distrobox");
+    set_command_tooltip(&button, build_distrobox_commands);
     let window = window.clone();
 
     button.connect_clicked(move |_| {
         info!("DistroBox button clicked");
-
-        let commands = CommandSequence::new()
-            .then(
-                Command::builder()
-                    .aur()
-                    .args(&["-S", "--noconfirm", "--needed", "distrobox"])
-                    .description("Installing DistroBox...")
-                    .build(),
-            )
-            .then(
-                Command::builder()
-                    .normal()
-                    .program("flatpak")
-                    .args(&["install", "-y", "io.github.dvlv.boxbuddyrs"])
-                    .description("Installing BoxBuddy GUI...")
-                    .build(),
-            )
-            .build();
+        let commands = build_distrobox_commands();
 
         task_runner::run(window.upcast_ref(), commands, "DistroBox Setup");
     });
 }
 
+/// Install DistroBox and the BoxBuddy GUI.
+fn build_distrobox_commands() -> CommandSequence {
+    CommandSequence::new()
+        .then(Command::install(&["distrobox"], "DistroBox"))
+        .then(
+            Command::builder()
+                .flatpak()
+                .args(&["install", "-y", "io.github.dvlv.boxbuddyrs"])
+                .description("Installing BoxBuddy GUI...")
+                .build(),
+        )
+        .build()
+}
+
 fn setup_kvm(builder: &Builder, window: &ApplicationWindow) {
     let button = extract_widget::<Button>(builder, "btn_kvm");
+    set_command_tooltip(&button, || build_libvirt_commands(CommandSequence::new()));
     let window = window.clone();
 
     button.connect_clicked(move |_| {
         info!("KVM button clicked");
 
-        let mut commands = CommandSequence::new();
-
-        // Remove conflicting packages if installed
-        if core::is_package_installed("iptables") {
-            commands = commands.then(
-                Command::builder()
-                    .aur()
-                    .args(&["-Rdd", "--noconfirm", "iptables"])
-                    .description("Removing conflicting iptables...")
-                    .build(),
-            );
-        }
-
-        if core::is_package_installed("gnu-netcat") {
-            commands = commands.then(
-                Command::builder()
-                    .aur()
-                    .args(&["-Rdd", "--noconfirm", "gnu-netcat"])
-                    .description("Removing conflicting gnu-netcat...")
-                    .build(),
-            );
-        }
+        let commands = build_libvirt_commands(CommandSequence::new()).build();
+        task_runner::run(window.upcast_ref(), commands, "KVM / QEMU Setup");
+    });
+}
 
+/// Append steps installing the libvirt/QEMU stack and enabling libvirtd.
+fn build_libvirt_commands(mut commands: CommandSequence) -> CommandSequence {
+    // Remove conflicting packages if installed
+    if core::is_package_installed("iptables") {
         commands = commands.then(
             Command::builder()
                 .aur()
-                .args(&[
-                    "-S",
-                    "--noconfirm",
-                    "--needed",
-                    "virt-manager-meta",
-                    "openbsd-netcat",
-                ])
-                .description("Installing virtualization packages...")
+                .args(&["-Rdd", "--noconfirm", "iptables"])
+                .description("Removing conflicting iptables...")
                 .build(),
         );
+    }
 
+    if core::is_package_installed("gnu-netcat") {
         commands = commands.then(
             Command::builder()
-                .privileged()
-                .program("sh")
-                .args(&[
-                    "-c",
-                    "echo 'options kvm-intel nested=1' > /etc/modprobe.d/kvm-intel.conf",
-                ])
-                .description("Enabling nested virtualization...")
+                .aur()
+                .args(&["-Rdd", "--noconfirm", "gnu-netcat"])
+                .description("Removing conflicting gnu-netcat...")
                 .build(),
         );
+    }
 
-        commands = commands.then(
-            Command::builder()
-                .privileged()
-                .program("systemctl")
-                .args(&["restart", "libvirtd.service"])
-                .description("Restarting libvirtd service...")
-                .build(),
-        );
+    commands = commands.then(Command::install(
+        &[
+            "qemu-full",
+            "libvirt",
+            "virt-manager",
+            "dnsmasq",
+            "iptables-nft",
+            "openbsd-netcat",
+        ],
+        "virtualization packages",
+    ));
+
+    commands = commands.then(
+        Command::builder()
+            .privileged()
+            .program("sh")
+            .args(&[
+                "-c",
+                "echo 'options kvm-intel nested=1' > /etc/modprobe.d/kvm-intel.conf",
+            ])
+            .description("Enabling nested virtualization...")
+            .build(),
+    );
+
+    commands = commands.then(
+        Command::builder()
+            .privileged()
+            .program("systemctl")
+            .args(&["enable", "--now", "libvirtd.service"])
+            .description("Enabling libvirtd service...")
+            .build(),
+    );
+
+    commands
+}
+
+fn setup_container_stacks(builder: &Builder, window: &ApplicationWindow) {
+    let button = extract_widget::<Button>(builder, "btn_container_stacks");
+    let window = window.clone();
+
+    button.connect_clicked(move |_| {
+        info!("Container/VM Stacks button clicked");
+
+        let config = SelectionDialogConfig::new(
+            "Container/VM Stacks",
+            "Select the stacks to install and enable.",
+        )
+        .selection_type(SelectionType::Multi)
+        .selection_required(true)
+        .add_option(SelectionOption::new(
+            "docker",
+            "Docker",
+            "Docker engine with Compose, enabled through docker.socket",
+            core::are_packages_installed(&["docker", "docker-compose"]),
+        ))
+        .add_option(SelectionOption::new(
+            "libvirt",
+            "libvirt/QEMU",
+            "QEMU with libvirt and Virtual Machine Manager",
+            core::are_packages_installed(&["qemu-full", "libvirt", "virt-manager"]),
+        ))
+        .confirm_label("Install");
+
+        let window_clone = window.clone();
+        show_selection_dialog(window.upcast_ref(), config, move |selected| {
+            let mut commands = CommandSequence::new();
+            if selected.iter().any(|s| s == "docker") {
+                commands = build_docker_commands(commands);
+            }
+            if selected.iter().any(|s| s == "libvirt") {
+                commands = build_libvirt_commands(commands);
+            }
 
-        task_runner::run(window.upcast_ref(), commands.build(), "KVM / QEMU Setup");
+            if !commands.is_empty() {
+                task_runner::run(
+                    window_clone.upcast_ref(),
+                    commands.build(),
+                    "Container/VM Stacks Setup",
+                );
+            }
+        });
     });
 }
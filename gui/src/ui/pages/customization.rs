@@ -9,7 +9,7 @@
 
 use crate::ui::dialogs::terminal;
 use crate::ui::task_runner::{self, Command, CommandSequence};
-use crate::ui::utils::extract_widget;
+use crate::ui::utils::{extract_widget, set_command_tooltip};
 use gtk4::prelude::*;
 use gtk4::{ApplicationWindow, Builder, Button};
 use log::info;
@@ -25,156 +25,139 @@ pub fn setup_handlers(page_builder: &Builder, _main_builder: &Builder, window: &
 
 fn setup_zsh_aio(builder: &Builder, window: &ApplicationWindow) {
     let button = extract_widget::<Button>(builder, "btn_zsh_aio");
+    set_command_tooltip(&button, build_zsh_aio_commands);
     let window = window.clone();
 
     button.connect_clicked(move |_| {
         info!("ZSH AiO button clicked");
+        let commands = build_zsh_aio_commands();
 
-        let env = crate::config::env::get();
-        let home = env.home.clone();
-        let user = env.user.clone();
+        task_runner::run(window.upcast_ref(), commands, "ZSH All-in-One Setup");
+    });
+}
 
-        let commands = CommandSequence::new()
-            .then(Command::builder()
-                .aur()
-                .args(&[
-                    "-S",
-                    "--needed",
-                    "--noconfirm",
-                    "zsh",
-                    "grml-zsh-config",
-                    "fastfetch",
-                ])
-                .description("Installing ZSH and dependencies...")
-                .build())
-            .then(Command::builder()
-                .normal()
-                .program("sh")
-                .args(&[
-                    "-c",
-                    "curl -fsSL https://raw.githubusercontent.com/ohmyzsh/ohmyzsh/master/tools/install.sh | sh -s -- --unattended",
-                ])
-                .description("Installing Oh My Zsh framework...")
-                .build())
-            .then(Command::builder()
-                .aur()
-                .args(&[
-                    "-S",
-                    "--noconfirm",
-                    "--needed",
-                    "pacseek",
-                    "ttf-meslo-nerd",
-                    "siji-git",
-                    "otf-unifont",
-                    "bdf-unifont",
-                    "noto-color-emoji-fontconfig",
-                    "xorg-fonts-misc",
-                    "ttf-dejavu",
-                    "ttf-meslo-nerd-font-powerlevel10k",
-                    "noto-fonts-emoji",
-                    "powerline-fonts",
-                    "oh-my-posh-bin",
-                ])
-                .description("Installing fonts and terminal enhancements...")
-                .build())
-            .then(Command::builder()
-                .normal()
-                .program("git")
-                .args(&[
-                    "clone",
-                    "https://github.com/zsh-users/zsh-completions",
-                    &format!("{}/.oh-my-zsh/custom/plugins/zsh-completions", home),
-                ])
-                .description("Installing ZSH completions plugin...")
-                .build())
-            .then(Command::builder()
-                .normal()
-                .program("git")
-                .args(&[
-                    "clone",
-                    "https://github.com/zsh-users/zsh-autosuggestions",
-                    &format!("{}/.oh-my-zsh/custom/plugins/zsh-autosuggestions", home),
-                ])
-                .description("Installing ZSH autosuggestions plugin...")
-                .build())
-            .then(Command::builder()
-                .normal()
-                .program("git")
-                .args(&[
-                    "clone",
-                    "https://github.com/zsh-users/zsh-syntax-highlighting.git",
-                    &format!("{}/.oh-my-zsh/custom/plugins/zsh-syntax-highlighting", home),
-                ])
-                .description("Installing ZSH syntax highlighting plugin...")
-                .build())
-            .then(Command::builder()
-                .normal()
-                .program("sh")
-                .args(&[
-                    "-c",
-                    &format!(
-                        "mv -f {}/.zshrc {}/.zshrc.user 2>/dev/null || true",
-                        home, home
-                    ),
-                ])
-                .description("Backing up existing ZSH configuration...")
-                .build())
-            .then(Command::builder()
-                .normal()
-                .program("wget")
-                .args(&[
-                    "-q",
-                    "-P",
-                    &home,
-                    "https://raw.githubusercontent.com/xerolinux/xero-fixes/main/conf/.zshrc",
-                ])
-                .description("Downloading XeroLinux ZSH configuration...")
-                .build())
-            .then(Command::builder()
-                .normal()
-                .program("sh")
-                .args(&[
-                    "-c",
-                    &format!(
-                        "sed -i 's|Command=/bin/bash|Command=/bin/zsh|g' {}/.local/share/konsole/XeroLinux.profile 2>/dev/null || true",
-                        home
-                    ),
-                ])
-                .description("Updating Konsole profile to use ZSH...")
-                .build())
-            .then(Command::builder()
-                .privileged()
-                .program("chsh")
-                .args(&[&user, "-s", "/bin/zsh"])
-                .description("Setting ZSH as default shell...")
-                .build())
-            .build();
+/// Install ZSH with Oh My Zsh, plugins and fonts, and make it the default shell.
+fn build_zsh_aio_commands() -> CommandSequence {
+    let env = crate::config::env::get();
+    let home = env.home.clone();
+    let user = env.user.clone();
 
-        task_runner::run(
-            window.upcast_ref(),
-            commands,
-            "ZSH All-in-One Setup",
-        );
-    });
+    CommandSequence::new()
+        .then(Command::install(
+            &["zsh", "grml-zsh-config", "fastfetch"],
+            "ZSH and dependencies",
+        ))
+        .then(Command::builder()
+            .normal()
+            .program("sh")
+            .args(&[
+                "-c",
+                "curl -fsSL https://raw.githubusercontent.com/ohmyzsh/ohmyzsh/master/tools/install.sh | sh -s -- --unattended",
+            ])
+            .description("Installing Oh My Zsh framework...")
+            .build())
+        .then(Command::install(
+            &[
+                "pacseek",
+                "ttf-meslo-nerd",
+                "siji-git",
+                "otf-unifont",
+                "bdf-unifont",
+                "noto-color-emoji-fontconfig",
+                "xorg-fonts-misc",
+                "ttf-dejavu",
+                "ttf-meslo-nerd-font-powerlevel10k",
+                "noto-fonts-emoji",
+                "powerline-fonts",
+                "oh-my-posh-bin",
+            ],
+            "fonts and terminal enhancements",
+        ))
+        .then(Command::builder()
+            .normal()
+            .program("git")
+            .args(&[
+                "clone",
+                "https://github.com/zsh-users/zsh-completions",
+                &format!("{}/.oh-my-zsh/custom/plugins/zsh-completions", home),
+            ])
+            .description("Installing ZSH completions plugin...")
+            .with_env("GIT_TERMINAL_PROMPT", "0")
+            .build())
+        .then(Command::builder()
+            .normal()
+            .program("git")
+            .args(&[
+                "clone",
+                "https://github.com/zsh-users/zsh-autosuggestions",
+                &format!("{}/.oh-my-zsh/custom/plugins/zsh-autosuggestions", home),
+            ])
+            .description("Installing ZSH autosuggestions plugin...")
+            .with_env("GIT_TERMINAL_PROMPT", "0")
+            .build())
+        .then(Command::builder()
+            .normal()
+            .program("git")
+            .args(&[
+                "clone",
+                "https://github.com/zsh-users/zsh-syntax-highlighting.git",
+                &format!("{}/.oh-my-zsh/custom/plugins/zsh-syntax-highlighting", home),
+            ])
+            .description("Installing ZSH syntax highlighting plugin...")
+            .with_env("GIT_TERMINAL_PROMPT", "0")
+            .build())
+        .then(Command::builder()
+            .normal()
+            .program("sh")
+            .args(&[
+                "-c",
+                &format!(
+                    "mv -f {}/.zshrc {}/.zshrc.user 2>/dev/null || true",
+                    home, home
+                ),
+            ])
+            .description("Backing up existing ZSH configuration...")
+            .build())
+        .then(Command::builder()
+            .normal()
+            .program("wget")
+            .args(&[
+                "-q",
+                "-P",
+                &home,
+                "https://raw.githubusercontent.com/xerolinux/xero-fixes/main/conf/.zshrc",
+            ])
+            .description("Downloading XeroLinux ZSH configuration...")
+            .build())
+        .then(Command::builder()
+            .normal()
+            .program("sh")
+            .args(&[
+                "-c",
+                &format!(
+                    "sed -i 's|Command=/bin/bash|Command=/bin/zsh|g' {}/.local/share/konsole/XeroLinux.profile 2>/dev/null || true",
+                    home
+                ),
+            ])
+            .description("Updating Konsole profile to use ZSH...")
+            .build())
+        .then(Command::builder()
+            .privileged()
+            .program("chsh")
+            .args(&[&user, "-s", "/bin/zsh"])
+            .description("Setting ZSH as default shell...")
+            .build())
+        .build()
 }
 
 fn setup_save_desktop(builder: &Builder, window: &ApplicationWindow) {
     let button = extract_widget::<Button>(builder, "btn_save_desktop");
+    set_command_tooltip(&button, build_save_desktop_commands);
     let window = window.clone();
 
     button.connect_clicked(move |_| {
         info!("Save Desktop Tool button clicked");
-
-        let commands = CommandSequence::new()
-            .then(
-                Command::builder()
-                    .normal()
-                    .program("flatpak")
-                    .args(&["install", "-y", "io.github.vikdevelop.SaveDesktop"])
-                    .description("Installing Save Desktop tool from Flathub...")
-                    .build(),
-            )
-            .build();
+        let commands = build_save_desktop_commands();
 
         task_runner::run(
             window.upcast_ref(),
@@ -184,6 +167,19 @@ fn setup_save_desktop(builder: &Builder, window: &ApplicationWindow) {
     });
 }
 
+/// Install the Save Desktop tool.
+fn build_save_desktop_commands() -> CommandSequence {
+    CommandSequence::new()
+        .then(
+            Command::builder()
+                .flatpak()
+                .args(&["install", "-y", "io.github.vikdevelop.SaveDesktop"])
+                .description("Installing Save Desktop tool from Flathub...")
+                .build(),
+        )
+        .build()
+}
+
 fn setup_grub_theme(builder: &Builder, window: &ApplicationWindow) {
     let button = extract_widget::<Button>(builder, "btn_grub_theme");
     let window = window.clone();
@@ -227,46 +223,54 @@ fn setup_plymouth_manager(builder: &Builder, window: &ApplicationWindow) {
 
 fn setup_layan_patch(builder: &Builder, window: &ApplicationWindow) {
     let button = extract_widget::<Button>(builder, "btn_layan_patch");
+    set_command_tooltip(&button, build_layan_patch_commands);
     let window = window.clone();
 
     button.connect_clicked(move |_| {
         info!("Update Layan Theme button clicked");
-
-        let home = crate::config::env::get().home.clone();
-
-        let commands = CommandSequence::new()
-            .then(
-                Command::builder()
-                    .normal()
-                    .program("git")
-                    .args(&[
-                        "clone",
-                        "--depth",
-                        "1",
-                        "https://github.com/vinceliuice/Layan-kde.git",
-                        &format!("{}/Layan-kde", home),
-                    ])
-                    .description("Downloading Layan KDE theme...")
-                    .build(),
-            )
-            .then(
-                Command::builder()
-                    .privileged()
-                    .program("sh")
-                    .args(&["-c", &format!("cd {}/Layan-kde && sh install.sh", home)])
-                    .description("Installing Layan KDE theme...")
-                    .build(),
-            )
-            .then(
-                Command::builder()
-                    .normal()
-                    .program("rm")
-                    .args(&["-rf", &format!("{}/Layan-kde", home)])
-                    .description("Cleaning up KDE theme files...")
-                    .build(),
-            )
-            .build();
+        let commands = build_layan_patch_commands();
 
         task_runner::run(window.upcast_ref(), commands, "Update Layan Theme");
     });
 }
+
+/// Reinstall the latest Layan KDE theme from git.
+fn build_layan_patch_commands() -> CommandSequence {
+    let home = crate::config::env::get().home.clone();
+
+    CommandSequence::new()
+        .then(
+            Command::builder()
+                .normal()
+                .program("git")
+                .args(&[
+                    "clone",
+                    "--depth",
+                    "1",
+                    "https://github.com/vinceliuice/Layan-kde.git",
+                    &format!("{}/Layan-kde", home),
+                ])
+                .description("Downloading Layan KDE theme...")
+                .with_env("GIT_TERMINAL_PROMPT", "0")
+                .build(),
+        )
+        .then(
+            Command::builder()
+                .privileged()
+                .program("sh")
+                .args(&["install.sh"])
+                .in_dir(format!("{}/Layan-kde", home))
+                .description("Installing Layan KDE theme...")
+                .build(),
+        )
+        .then(
+            Command::builder()
+                .normal()
+                .program("rm")
+                .args(&["-rf", &format!("{}/Layan-kde", home)])
+                .description("Cleaning up KDE theme files...")
+                .allow_failure()
+                .build(),
+        )
+        .build()
+}
@@ -5,14 +5,20 @@
 //! - ASUS ROG laptop tools
 //! - OpenRazer drivers
 //! - Fingerprint GUI Tool
+//! - Printing and scanning (CUPS, SANE, vendor drivers)
+//! - Bluetooth stack, with optional Blueman tray applet
+//! - AMD GPU drivers (Mesa/RADV, optional AMDVLK)
+//! - Intel GPU media and Vulkan drivers
+//! - GPU-specific drivers and toolkits, enabled based on detected hardware
 
 use crate::core;
+use crate::core::gpu::{GpuVendor, NvidiaGeneration};
 use crate::ui::dialogs::selection::{
     show_selection_dialog, SelectionDialogConfig, SelectionOption, SelectionType,
 };
 use crate::ui::dialogs::warning::show_warning_confirmation;
-use crate::ui::task_runner::{self, Command, CommandSequence};
-use crate::ui::utils::extract_widget;
+use crate::ui::task_runner::{self, Command, CommandSequence, RunOptions};
+use crate::ui::utils::{extract_widget, set_command_tooltip};
 use gtk4::prelude::*;
 use gtk4::{ApplicationWindow, Builder, Button};
 use log::info;
@@ -24,70 +30,90 @@ pub fn setup_handlers(page_builder: &Builder, _main_builder: &Builder, window: &
     setup_openrazer(page_builder, window);
     setup_fingerprint(page_builder, window);
     setup_zenergy(page_builder, window);
-    setup_nvidia_legacy(page_builder, window);
-    setup_rocm(page_builder, window);
-    setup_cuda(page_builder, window);
+    setup_printing(page_builder, window);
+    setup_bluetooth(page_builder, window);
+
+    let gpu_vendors = core::gpu::detect_gpu_vendors();
+    setup_nvidia_legacy(page_builder, window, &gpu_vendors);
+    setup_amd_drivers(page_builder, window, &gpu_vendors);
+    setup_rocm(page_builder, window, &gpu_vendors);
+    setup_cuda(page_builder, window, &gpu_vendors);
+    setup_intel_graphics(page_builder, window, &gpu_vendors);
 }
 
+/// Grey out a vendor-specific button when no GPU from that vendor is present.
+///
+/// Nothing is disabled if detection failed and no GPU was found at all.
+fn require_gpu_vendor(button: &Button, detected: &[GpuVendor], vendor: GpuVendor, name: &str) {
+    if !detected.is_empty() && !detected.contains(&vendor) {
+        info!("No {} GPU detected, disabling {:?}", name, button.label());
+        button.set_sensitive(false);
+        button.set_tooltip_text(Some(&format!("No {} GPU detected", name)));
+    }
+}
+
+/// Downloads the XeroLinux Tailscale installer and runs it.
+const TAILSCALE_INSTALL_SCRIPT: &str = r#"set -euo pipefail
+installer=$(mktemp)
+trap 'rm -f "$installer"' EXIT
+curl -fsSL https://raw.githubusercontent.com/xerolinux/xero-fixes/main/conf/install.sh -o "$installer"
+bash "$installer"
+"#;
+
 fn setup_tailscale(builder: &Builder, window: &ApplicationWindow) {
     let button = extract_widget::<Button>(builder, "btn_tailscale");
+    set_command_tooltip(&button, build_tailscale_commands);
     let window = window.clone();
 
     button.connect_clicked(move |_| {
         info!("Tailscale VPN button clicked");
-
-        let commands = CommandSequence::new()
-            .then(Command::builder()
-                .privileged()
-                .program("bash")
-                .args(&[
-                    "-c",
-                    "curl -fsSL https://raw.githubusercontent.com/xerolinux/xero-fixes/main/conf/install.sh | bash",
-                ])
-                .description("Installing Tailscale VPN...")
-                .build())
-            .build();
+        let commands = build_tailscale_commands();
 
         task_runner::run(window.upcast_ref(), commands, "Install Tailscale VPN");
     });
 }
 
+/// Install Tailscale with the XeroLinux installer script.
+fn build_tailscale_commands() -> CommandSequence {
+    CommandSequence::new()
+        .then(Command::script(
+            TAILSCALE_INSTALL_SCRIPT,
+            "Tailscale VPN installer",
+        ))
+        .build()
+}
+
 fn setup_asus_rog(builder: &Builder, window: &ApplicationWindow) {
     let button = extract_widget::<Button>(builder, "btn_asus_rog");
+    set_command_tooltip(&button, build_asus_rog_commands);
     let window = window.clone();
 
     button.connect_clicked(move |_| {
         info!("ASUS ROG Tools button clicked");
-
-        let commands = CommandSequence::new()
-            .then(
-                Command::builder()
-                    .aur()
-                    .args(&[
-                        "-S",
-                        "--noconfirm",
-                        "--needed",
-                        "rog-control-center",
-                        "asusctl",
-                        "supergfxctl",
-                    ])
-                    .description("Installing ASUS ROG control tools...")
-                    .build(),
-            )
-            .then(
-                Command::builder()
-                    .privileged()
-                    .program("systemctl")
-                    .args(&["enable", "--now", "asusd", "supergfxd"])
-                    .description("Enabling ASUS ROG services...")
-                    .build(),
-            )
-            .build();
+        let commands = build_asus_rog_commands();
 
         task_runner::run(window.upcast_ref(), commands, "Install ASUS ROG Tools");
     });
 }
 
+/// Install the ASUS ROG tools and enable their services.
+fn build_asus_rog_commands() -> CommandSequence {
+    CommandSequence::new()
+        .then(Command::install(
+            &["rog-control-center", "asusctl", "supergfxctl"],
+            "ASUS ROG control tools",
+        ))
+        .then(
+            Command::builder()
+                .privileged()
+                .program("systemctl")
+                .args(&["enable", "--now", "asusd", "supergfxd"])
+                .description("Enabling ASUS ROG services...")
+                .build(),
+        )
+        .build()
+}
+
 fn setup_openrazer(builder: &Builder, window: &ApplicationWindow) {
     let button = extract_widget::<Button>(builder, "btn_openrazer");
     let window = window.clone();
@@ -134,13 +160,10 @@ fn build_openrazer_commands(selected_frontends: &[String]) -> CommandSequence {
     let mut commands = CommandSequence::new();
 
     // Always install openrazer-meta-git
-    commands = commands.then(
-        Command::builder()
-            .aur()
-            .args(&["-S", "--noconfirm", "--needed", "openrazer-meta-git"])
-            .description("Installing OpenRazer drivers...")
-            .build(),
-    );
+    commands = commands.then(Command::install(
+        &["openrazer-meta-git"],
+        "OpenRazer drivers",
+    ));
 
     // Add user to plugdev group
     commands = commands.then(
@@ -154,23 +177,14 @@ fn build_openrazer_commands(selected_frontends: &[String]) -> CommandSequence {
 
     // Optionally install selected frontends
     if selected_frontends.iter().any(|s| s == "polychromatic") {
-        commands = commands.then(
-            Command::builder()
-                .aur()
-                .args(&["-S", "--noconfirm", "--needed", "polychromatic"])
-                .description("Installing Polychromatic frontend...")
-                .build(),
-        );
+        commands = commands.then(Command::install(
+            &["polychromatic"],
+            "Polychromatic frontend",
+        ));
     }
 
     if selected_frontends.iter().any(|s| s == "razergenie") {
-        commands = commands.then(
-            Command::builder()
-                .aur()
-                .args(&["-S", "--noconfirm", "--needed", "razergenie"])
-                .description("Installing RazerGenie frontend...")
-                .build(),
-        );
+        commands = commands.then(Command::install(&["razergenie"], "RazerGenie frontend"));
     }
 
     commands
@@ -183,13 +197,30 @@ fn setup_fingerprint(builder: &Builder, window: &ApplicationWindow) {
     button.connect_clicked(move |_| {
         info!("Fingerprint GUI Tool button clicked");
 
+        // Offer removal when the tool is already installed
+        if core::is_package_installed("xfprintd-gui") {
+            let window_clone = window.clone();
+            show_warning_confirmation(
+                window.upcast_ref(),
+                "Remove Fingerprint GUI Tool",
+                "The Fingerprint GUI Tool is already installed.\nDo you want to <span foreground=\"red\" weight=\"bold\">remove</span> it?",
+                move || {
+                    let commands = CommandSequence::new()
+                        .then(Command::aur_remove(&["xfprintd-gui"], "Fingerprint GUI Tool"))
+                        .build();
+                    task_runner::run(
+                        window_clone.upcast_ref(),
+                        commands,
+                        "Remove Fingerprint GUI Tool",
+                    );
+                },
+            );
+            return;
+        }
+
         let commands = CommandSequence::new()
             .then(
-                Command::builder()
-                    .aur()
-                    .args(&["-S", "--noconfirm", "--needed", "xfprintd-gui"])
-                    .description("Installing Fingerprint GUI Tool...")
-                    .build(),
+                Command::install(&["xfprintd-gui"], "Fingerprint GUI Tool"),
             )
             .build();
 
@@ -203,39 +234,192 @@ fn setup_fingerprint(builder: &Builder, window: &ApplicationWindow) {
 
 fn setup_zenergy(builder: &Builder, window: &ApplicationWindow) {
     let button = extract_widget::<Button>(builder, "btn_zenergy");
+    set_command_tooltip(&button, build_zenergy_commands);
     let window = window.clone();
 
     button.connect_clicked(move |_| {
         info!("Zenergy Driver button clicked");
-
-        let commands = CommandSequence::new()
-            .then(
-                Command::builder()
-                    .aur()
-                    .args(&["-S", "--noconfirm", "--needed", "zenergy-dkms-git"])
-                    .description("Installing Zenergy Driver...")
-                    .build(),
-            )
-            .build();
+        let commands = build_zenergy_commands();
 
         task_runner::run(window.upcast_ref(), commands, "Install Zenergy Driver");
     });
 }
 
-fn setup_nvidia_legacy(builder: &Builder, window: &ApplicationWindow) {
+/// Install the Zenergy power reporting driver.
+fn build_zenergy_commands() -> CommandSequence {
+    CommandSequence::new()
+        .then(Command::install(&["zenergy-dkms-git"], "Zenergy Driver"))
+        .build()
+}
+
+fn setup_printing(builder: &Builder, window: &ApplicationWindow) {
+    let button = extract_widget::<Button>(builder, "btn_printing");
+    let window = window.clone();
+
+    button.connect_clicked(move |_| {
+        info!("Printing & Scanning button clicked");
+
+        // Show selection dialog for vendor drivers
+        let window_clone = window.clone();
+        let config = SelectionDialogConfig::new(
+            "Printing & Scanning",
+            "CUPS, a PDF printer, the printer settings tool and SANE will be installed. \
+            Select drivers for your printer brand if needed.",
+        )
+        .selection_type(SelectionType::Multi)
+        .selection_required(false)
+        .add_option(SelectionOption::new(
+            "hplip",
+            "HP",
+            "HP Linux Imaging and Printing drivers",
+            core::is_package_installed("hplip"),
+        ))
+        .add_option(SelectionOption::new(
+            "brlaser",
+            "Brother",
+            "Driver for Brother monochrome laser printers",
+            core::is_package_installed("brlaser"),
+        ))
+        .add_option(SelectionOption::new(
+            "epson-inkjet-printer-escpr",
+            "Epson",
+            "Epson ESC/P-R driver for Epson inkjet printers",
+            core::is_package_installed("epson-inkjet-printer-escpr"),
+        ))
+        .add_option(SelectionOption::new(
+            "gutenprint",
+            "Gutenprint",
+            "Drivers for a wide range of Canon, Epson and other printers",
+            core::is_package_installed("gutenprint"),
+        ))
+        .confirm_label("Install");
+
+        show_selection_dialog(window.upcast_ref(), config, move |selected| {
+            task_runner::run(
+                window_clone.upcast_ref(),
+                build_printing_commands(&selected),
+                "Install Printing & Scanning",
+            );
+        });
+    });
+}
+
+/// Build commands for printing and scanning setup with the selected drivers.
+fn build_printing_commands(drivers: &[String]) -> CommandSequence {
+    let mut packages = vec![
+        "-S",
+        "--noconfirm",
+        "--needed",
+        "cups",
+        "cups-pdf",
+        "system-config-printer",
+        "sane",
+    ];
+    packages.extend(drivers.iter().map(String::as_str));
+
+    CommandSequence::new()
+        .then(
+            Command::builder()
+                .aur()
+                .args(&packages)
+                .description("Installing printing and scanning packages...")
+                .build(),
+        )
+        .then(
+            Command::builder()
+                .privileged()
+                .program("systemctl")
+                .args(&["enable", "--now", "cups.socket"])
+                .description("Enabling CUPS printing service...")
+                .build(),
+        )
+        .build()
+}
+
+fn setup_bluetooth(builder: &Builder, window: &ApplicationWindow) {
+    let button = extract_widget::<Button>(builder, "btn_bluetooth");
+    let window = window.clone();
+
+    button.connect_clicked(move |_| {
+        info!("Bluetooth button clicked");
+
+        // Show selection dialog for the optional tray applet
+        let window_clone = window.clone();
+        let config = SelectionDialogConfig::new(
+            "Bluetooth",
+            "BlueZ will be installed and the Bluetooth service enabled. \
+            Optionally add Blueman for a tray icon and device manager.",
+        )
+        .selection_type(SelectionType::Multi)
+        .selection_required(false)
+        .add_option(SelectionOption::new(
+            "blueman",
+            "Blueman",
+            "GTK Bluetooth manager with a tray applet",
+            core::is_package_installed("blueman"),
+        ))
+        .confirm_label("Install");
+
+        show_selection_dialog(window.upcast_ref(), config, move |selected| {
+            let with_blueman = selected.iter().any(|s| s == "blueman");
+            task_runner::run(
+                window_clone.upcast_ref(),
+                build_bluetooth_commands(with_blueman),
+                "Set Up Bluetooth",
+            );
+        });
+    });
+}
+
+/// Build commands for Bluetooth setup.
+///
+/// Enabling the service may fail without a Bluetooth adapter, which is shown
+/// as a warning on that step while the installed packages are kept.
+fn build_bluetooth_commands(with_blueman: bool) -> CommandSequence {
+    let mut packages = vec!["-S", "--noconfirm", "--needed", "bluez", "bluez-utils"];
+    if with_blueman {
+        packages.push("blueman");
+    }
+
+    CommandSequence::new()
+        .then(
+            Command::builder()
+                .aur()
+                .args(&packages)
+                .description("Installing Bluetooth packages...")
+                .build(),
+        )
+        .then(
+            Command::builder()
+                .privileged()
+                .program("systemctl")
+                .args(&["enable", "--now", "bluetooth.service"])
+                .description("Enabling Bluetooth service...")
+                .allow_failure()
+                .build(),
+        )
+        .build()
+}
+
+fn setup_nvidia_legacy(builder: &Builder, window: &ApplicationWindow, gpu_vendors: &[GpuVendor]) {
     let button = extract_widget::<Button>(builder, "btn_nvidia_legacy");
+    require_gpu_vendor(&button, gpu_vendors, GpuVendor::Nvidia, "NVIDIA");
     let window = window.clone();
 
     button.connect_clicked(move |_| {
         info!("Nvidia Legacy Drivers button clicked");
 
         let window_clone = window.clone();
+        let message = format!(
+            "{}This is only intended for <span foreground=\"red\" weight=\"bold\">GTX900/1000</span> Series Legacy GPUs\n\
+            For <span foreground=\"cyan\" weight=\"bold\">RTX/Turing+</span> GPUs download the <span foreground=\"green\" weight=\"bold\">nVidia</span> ISO instead.\n\n\
+            <span foreground=\"red\" weight=\"bold\">No Support/Help</span> will be provided for those Legacy GPUs !",
+            open_module_gpu_notice()
+        );
         show_warning_confirmation(
             window.upcast_ref(),
             "Nvidia Legacy Drivers",
-            "This is only intended for <span foreground=\"red\" weight=\"bold\">GTX900/1000</span> Series Legacy GPUs\n\
-            For <span foreground=\"cyan\" weight=\"bold\">RTX/Turing+</span> GPUs download the <span foreground=\"green\" weight=\"bold\">nVidia</span> ISO instead.\n\n\
-            <span foreground=\"red\" weight=\"bold\">No Support/Help</span> will be provided for those Legacy GPUs !",
+            &message,
             move || {
                 // Use configured path
                 let script_dir = crate::config::paths::scripts();
@@ -246,6 +430,7 @@ fn setup_nvidia_legacy(builder: &Builder, window: &ApplicationWindow) {
                     .into_owned();
 
                 let commands = CommandSequence::new()
+                    .ensure_multilib()
                     .then(
                         Command::builder()
                             .aur()
@@ -260,6 +445,7 @@ fn setup_nvidia_legacy(builder: &Builder, window: &ApplicationWindow) {
                                 "opencl-nvidia-580xx",
                             ])
                             .description("Installing Nvidia Legacy Drivers...")
+                            .requires_reboot()
                             .build(),
                     )
                     .then(
@@ -268,6 +454,9 @@ fn setup_nvidia_legacy(builder: &Builder, window: &ApplicationWindow) {
                             .program("bash")
                             .args(&[&grub_script])
                             .description("Configuring GRUB (nvidia-drm.modeset=1)...")
+                            .confirm_before(
+                                "This will modify /etc/default/grub and regenerate the GRUB configuration. Continue?",
+                            )
                             .build(),
                     )
                     .then(
@@ -297,54 +486,193 @@ fn setup_nvidia_legacy(builder: &Builder, window: &ApplicationWindow) {
                             .program("mkinitcpio")
                             .args(&["-P"])
                             .description("Rebuilding initramfs...")
+                            .requires_reboot()
                             .build(),
                     )
                     .build();
 
-                task_runner::run(
+                // The DKMS build can fail without failing the package install
+                let options = RunOptions::new().verify(|| {
+                    if core::kernel::is_module_available("nvidia") {
+                        Ok(())
+                    } else {
+                        Err("the nvidia module was not built for the running kernel".to_string())
+                    }
+                });
+                task_runner::run_with_options(
                     window_clone.upcast_ref(),
                     commands,
                     "Install Nvidia Legacy Drivers",
+                    options,
                 );
             },
         );
     });
 }
 
-fn setup_rocm(builder: &Builder, window: &ApplicationWindow) {
+/// Notice shown when the installed NVIDIA card is Turing or newer.
+///
+/// On hybrid laptops only the NVIDIA cards are considered.
+fn open_module_gpu_notice() -> String {
+    let gpus = core::gpu::detect_gpus();
+    let nvidia: Vec<_> = gpus
+        .iter()
+        .filter(|gpu| gpu.vendor == GpuVendor::Nvidia)
+        .collect();
+
+    let all_modern = !nvidia.is_empty()
+        && nvidia.iter().all(|gpu| {
+            gpu.nvidia_generation
+                .is_some_and(NvidiaGeneration::supports_open_modules)
+        });
+    if !all_modern {
+        return String::new();
+    }
+
+    let names = nvidia
+        .iter()
+        .map(|gpu| gtk4::glib::markup_escape_text(&gpu.name).to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(
+        "Detected <b>{}</b>, which is supported by the open-source driver. \
+        The <span foreground=\"green\" weight=\"bold\">nVidia</span> ISO is recommended for this GPU.\n\n",
+        names
+    )
+}
+
+fn setup_amd_drivers(builder: &Builder, window: &ApplicationWindow, gpu_vendors: &[GpuVendor]) {
+    let button = extract_widget::<Button>(builder, "btn_amd_drivers");
+    require_gpu_vendor(&button, gpu_vendors, GpuVendor::Amd, "AMD");
+    let window = window.clone();
+
+    button.connect_clicked(move |_| {
+        info!("AMD Drivers button clicked");
+
+        // Show selection dialog for the optional AMDVLK stack
+        let window_clone = window.clone();
+        let config = SelectionDialogConfig::new(
+            "AMD GPU Drivers",
+            "Mesa with the RADV Vulkan driver will be installed. Optionally add AMD's AMDVLK Vulkan driver.",
+        )
+        .selection_type(SelectionType::Multi)
+        .selection_required(false)
+        .add_option(SelectionOption::new(
+            "amdvlk",
+            "AMDVLK",
+            "AMD's open-source Vulkan driver, installed alongside RADV",
+            core::are_packages_installed(&["amdvlk", "lib32-amdvlk"]),
+        ))
+        .confirm_label("Install");
+
+        show_selection_dialog(window.upcast_ref(), config, move |selected| {
+            let with_amdvlk = selected.iter().any(|s| s == "amdvlk");
+            if !with_amdvlk {
+                task_runner::run(
+                    window_clone.upcast_ref(),
+                    build_amd_driver_commands(false),
+                    "Install AMD Drivers",
+                );
+                return;
+            }
+
+            // RADV is always installed, so picking AMDVLK means two Vulkan drivers
+            let window_for_run = window_clone.clone();
+            show_warning_confirmation(
+                window_clone.upcast_ref(),
+                "Multiple Vulkan Drivers",
+                "Both <span foreground=\"cyan\" weight=\"bold\">RADV</span> and <span foreground=\"red\" weight=\"bold\">AMDVLK</span> will be installed.\n\
+                AMDVLK takes priority unless <tt>AMD_VULKAN_ICD=RADV</tt> is set, which can break games tuned for RADV.",
+                move || {
+                    task_runner::run(
+                        window_for_run.upcast_ref(),
+                        build_amd_driver_commands(true),
+                        "Install AMD Drivers",
+                    );
+                },
+            );
+        });
+    });
+}
+
+/// Build commands for AMD driver installation.
+fn build_amd_driver_commands(with_amdvlk: bool) -> CommandSequence {
+    let mut commands = CommandSequence::new().ensure_multilib().then(
+        Command::builder()
+            .aur()
+            .args(&[
+                "-S",
+                "--noconfirm",
+                "--needed",
+                "mesa",
+                "lib32-mesa",
+                "vulkan-radeon",
+                "lib32-vulkan-radeon",
+                "libva-mesa-driver",
+            ])
+            .description("Installing Mesa and RADV drivers...")
+            .requires_reboot()
+            .build(),
+    );
+
+    if with_amdvlk {
+        commands = commands.then(Command::install(
+            &["amdvlk", "lib32-amdvlk"],
+            "AMDVLK driver",
+        ));
+    }
+
+    commands.build()
+}
+
+fn setup_rocm(builder: &Builder, window: &ApplicationWindow, gpu_vendors: &[GpuVendor]) {
     let button = extract_widget::<Button>(builder, "btn_rocm");
+    set_command_tooltip(&button, build_rocm_commands);
+    require_gpu_vendor(&button, gpu_vendors, GpuVendor::Amd, "AMD");
     let window = window.clone();
 
     button.connect_clicked(move |_| {
         info!("AMD ROCm button clicked");
-
-        let commands = CommandSequence::new()
-            .then(
-                Command::builder()
-                    .aur()
-                    .args(&[
-                        "-S",
-                        "--noconfirm",
-                        "--needed",
-                        "rocm-hip-sdk",
-                        "rocm-opencl-sdk",
-                    ])
-                    .description("Installing AMD ROCm SDK...")
-                    .build(),
-            )
-            .build();
+        let commands = build_rocm_commands();
 
         task_runner::run(window.upcast_ref(), commands, "Install AMD ROCm");
     });
 }
 
-fn setup_cuda(builder: &Builder, window: &ApplicationWindow) {
+/// Install the AMD ROCm SDK.
+fn build_rocm_commands() -> CommandSequence {
+    CommandSequence::new()
+        .then(Command::install(
+            &["rocm-hip-sdk", "rocm-opencl-sdk"],
+            "AMD ROCm SDK",
+        ))
+        .build()
+}
+
+fn setup_cuda(builder: &Builder, window: &ApplicationWindow, gpu_vendors: &[GpuVendor]) {
     let button = extract_widget::<Button>(builder, "btn_cuda");
+    require_gpu_vendor(&button, gpu_vendors, GpuVendor::Nvidia, "NVIDIA");
     let window = window.clone();
 
     button.connect_clicked(move |_| {
         info!("NVIDIA CUDA button clicked");
 
+        // nvidia-smi ships with every NVIDIA driver package
+        let driver_installed = core::aur::is_executable_in_path("nvidia-smi");
+        let cuda_option = |package: &str, label: &str, description: &str| {
+            let option = SelectionOption::new(
+                package,
+                label,
+                description,
+                core::is_package_installed(package),
+            );
+            if driver_installed {
+                option
+            } else {
+                option.disabled("CUDA requires an NVIDIA driver, install one first")
+            }
+        };
+
         // Show selection dialog for CUDA version
         let window_clone = window.clone();
         let config = SelectionDialogConfig::new(
@@ -353,17 +681,15 @@ fn setup_cuda(builder: &Builder, window: &ApplicationWindow) {
         )
         .selection_type(SelectionType::Single)
         .selection_required(true)
-        .add_option(SelectionOption::new(
+        .add_option(cuda_option(
             "cuda",
             "CUDA (Latest)",
             "Install the latest CUDA toolkit from official repositories",
-            core::is_package_installed("cuda"),
         ))
-        .add_option(SelectionOption::new(
+        .add_option(cuda_option(
             "cuda-12.9",
             "CUDA 12.9",
             "Install CUDA Toolkit version 12.9 specifically",
-            core::is_package_installed("cuda-12.9"),
         ))
         .confirm_label("Install");
 
@@ -385,3 +711,63 @@ fn setup_cuda(builder: &Builder, window: &ApplicationWindow) {
         });
     });
 }
+
+fn setup_intel_graphics(builder: &Builder, window: &ApplicationWindow, gpu_vendors: &[GpuVendor]) {
+    let button = extract_widget::<Button>(builder, "btn_intel_graphics");
+    require_gpu_vendor(&button, gpu_vendors, GpuVendor::Intel, "Intel");
+    let window = window.clone();
+
+    button.connect_clicked(move |_| {
+        info!("Intel Graphics button clicked");
+
+        // Show selection dialog for the optional legacy VA-API driver
+        let window_clone = window.clone();
+        let config = SelectionDialogConfig::new(
+            "Intel Graphics Drivers",
+            "The Intel media driver, Vulkan driver and GPU tools will be installed. \
+            GPUs older than Broadwell also need the legacy VA-API driver for video acceleration.",
+        )
+        .selection_type(SelectionType::Multi)
+        .selection_required(false)
+        .add_option(SelectionOption::new(
+            "libva-intel-driver",
+            "Legacy VA-API Driver",
+            "For Intel GPUs from before Broadwell (2014), not needed on newer ones",
+            core::is_package_installed("libva-intel-driver"),
+        ))
+        .confirm_label("Install");
+
+        show_selection_dialog(window.upcast_ref(), config, move |selected| {
+            let with_legacy = selected.iter().any(|s| s == "libva-intel-driver");
+            task_runner::run(
+                window_clone.upcast_ref(),
+                build_intel_graphics_commands(with_legacy),
+                "Install Intel Graphics Drivers",
+            );
+        });
+    });
+}
+
+/// Build commands for Intel graphics driver installation.
+fn build_intel_graphics_commands(with_legacy: bool) -> CommandSequence {
+    let mut commands = CommandSequence::new()
+        .ensure_multilib()
+        .then(Command::install(
+            &[
+                "intel-media-driver",
+                "vulkan-intel",
+                "lib32-vulkan-intel",
+                "intel-gpu-tools",
+            ],
+            "Intel media and Vulkan drivers",
+        ));
+
+    if with_legacy {
+        commands = commands.then(Command::install(
+            &["libva-intel-driver"],
+            "legacy VA-API driver",
+        ));
+    }
+
+    commands.build()
+}
@@ -2,13 +2,18 @@
 //!
 //! Handles:
 //! - Steam AiO installation
+//! - Gaming components (Steam, Gamescope, GameMode, MangoHud, ProtonGE)
 //! - LACT GPU overclocking
 //! - Game launchers (Lutris, Heroic, Bottles)
 //! - Controller tools
 //! - Falcond gaming utility
 
+use crate::core;
+use crate::ui::dialogs::selection::{
+    show_selection_dialog, SelectionDialogConfig, SelectionOption, SelectionType,
+};
 use crate::ui::task_runner::{self, Command, CommandSequence};
-use crate::ui::utils::extract_widget;
+use crate::ui::utils::{extract_widget, set_command_tooltip};
 use gtk4::prelude::*;
 use gtk4::{ApplicationWindow, Builder, Button};
 use log::info;
@@ -16,6 +21,7 @@ use log::info;
 /// Set up all button handlers for the gaming tools page.
 pub fn setup_handlers(page_builder: &Builder, _main_builder: &Builder, window: &ApplicationWindow) {
     setup_steam_aio(page_builder, window);
+    setup_gaming_components(page_builder, window);
     setup_lact_oc(page_builder, window);
     setup_lutris(page_builder, window);
     setup_heroic(page_builder, window);
@@ -26,171 +32,307 @@ pub fn setup_handlers(page_builder: &Builder, _main_builder: &Builder, window: &
 
 fn setup_steam_aio(builder: &Builder, window: &ApplicationWindow) {
     let button = extract_widget::<Button>(builder, "btn_steam_aio");
+    set_command_tooltip(&button, build_steam_aio_commands);
     let window = window.clone();
 
     button.connect_clicked(move |_| {
         info!("Steam AiO button clicked");
+        let commands = build_steam_aio_commands();
 
-        let commands = CommandSequence::new()
-            .then(
-                Command::builder()
-                    .aur()
-                    .args(&[
-                        "-S",
-                        "--noconfirm",
-                        "--needed",
-                        "steam",
-                        "gamescope",
-                        "mangohud",
-                        "mangoverlay",
-                        "lib32-mangohud",
-                        "wine-meta",
-                        "wine-nine",
-                        "ttf-liberation",
-                        "lib32-fontconfig",
-                        "wqy-zenhei",
-                        "vkd3d",
-                        "giflib",
-                        "lib32-giflib",
-                        "libpng",
-                        "lib32-libpng",
-                        "libldap",
-                        "lib32-libldap",
-                        "gnutls",
-                        "lib32-gnutls",
-                        "mpg123",
-                        "lib32-mpg123",
-                        "openal",
-                        "lib32-openal",
-                        "v4l-utils",
-                        "lib32-v4l-utils",
-                        "libpulse",
-                        "lib32-libpulse",
-                        "libgpg-error",
-                        "lib32-libgpg-error",
-                        "alsa-plugins",
-                        "lib32-alsa-plugins",
-                        "alsa-lib",
-                        "lib32-alsa-lib",
-                        "libjpeg-turbo",
-                        "lib32-libjpeg-turbo",
-                        "sqlite",
-                        "lib32-sqlite",
-                        "libxcomposite",
-                        "lib32-libxcomposite",
-                        "libxinerama",
-                        "lib32-libgcrypt",
-                        "libgcrypt",
-                        "lib32-libxinerama",
-                        "ncurses",
-                        "lib32-ncurses",
-                        "ocl-icd",
-                        "lib32-ocl-icd",
-                        "libxslt",
-                        "lib32-libxslt",
-                        "libva",
-                        "lib32-libva",
-                        "gtk3",
-                        "lib32-gtk3",
-                        "gst-plugins-base-libs",
-                        "lib32-gst-plugins-base-libs",
-                        "vulkan-icd-loader",
-                        "lib32-vulkan-icd-loader",
-                        "cups",
-                        "dosbox",
-                        "lib32-opencl-icd-loader",
-                        "lib32-vkd3d",
-                        "opencl-icd-loader",
-                    ])
-                    .description("Installing Steam and gaming dependencies...")
-                    .build(),
+        task_runner::run(window.upcast_ref(), commands, "Steam AiO Installation");
+    });
+}
+
+/// Install Steam with the libraries games commonly need.
+fn build_steam_aio_commands() -> CommandSequence {
+    CommandSequence::new()
+        .ensure_multilib()
+        .then(Command::install(
+            &[
+                "steam",
+                "gamescope",
+                "mangohud",
+                "mangoverlay",
+                "lib32-mangohud",
+                "wine-meta",
+                "wine-nine",
+                "ttf-liberation",
+                "lib32-fontconfig",
+                "wqy-zenhei",
+                "vkd3d",
+                "giflib",
+                "lib32-giflib",
+                "libpng",
+                "lib32-libpng",
+                "libldap",
+                "lib32-libldap",
+                "gnutls",
+                "lib32-gnutls",
+                "mpg123",
+                "lib32-mpg123",
+                "openal",
+                "lib32-openal",
+                "v4l-utils",
+                "lib32-v4l-utils",
+                "libpulse",
+                "lib32-libpulse",
+                "libgpg-error",
+                "lib32-libgpg-error",
+                "alsa-plugins",
+                "lib32-alsa-plugins",
+                "alsa-lib",
+                "lib32-alsa-lib",
+                "libjpeg-turbo",
+                "lib32-libjpeg-turbo",
+                "sqlite",
+                "lib32-sqlite",
+                "libxcomposite",
+                "lib32-libxcomposite",
+                "libxinerama",
+                "lib32-libgcrypt",
+                "libgcrypt",
+                "lib32-libxinerama",
+                "ncurses",
+                "lib32-ncurses",
+                "ocl-icd",
+                "lib32-ocl-icd",
+                "libxslt",
+                "lib32-libxslt",
+                "libva",
+                "lib32-libva",
+                "gtk3",
+                "lib32-gtk3",
+                "gst-plugins-base-libs",
+                "lib32-gst-plugins-base-libs",
+                "vulkan-icd-loader",
+                "lib32-vulkan-icd-loader",
+                "cups",
+                "dosbox",
+                "lib32-opencl-icd-loader",
+                "lib32-vkd3d",
+                "opencl-icd-loader",
+            ],
+            "Steam and gaming dependencies",
+        ))
+        .build()
+}
+
+/// A component offered by the gaming components dialog.
+struct GamingComponent {
+    id: &'static str,
+    label: &'static str,
+    description: &'static str,
+    packages: &'static [&'static str],
+    /// Whether the packages come from, or depend on, the multilib repository
+    multilib: bool,
+}
+
+/// Components of the gaming components dialog, in install order.
+const GAMING_COMPONENTS: &[GamingComponent] = &[
+    GamingComponent {
+        id: "steam",
+        label: "Steam",
+        description: "Valve's game store and launcher, including Proton",
+        packages: &["steam"],
+        multilib: true,
+    },
+    GamingComponent {
+        id: "gamescope",
+        label: "Gamescope",
+        description: "Micro-compositor for upscaling and frame limiting games",
+        packages: &["gamescope"],
+        multilib: false,
+    },
+    GamingComponent {
+        id: "gamemode",
+        label: "GameMode",
+        description: "Applies performance tweaks to the system while games run",
+        packages: &["gamemode", "lib32-gamemode"],
+        multilib: true,
+    },
+    GamingComponent {
+        id: "mangohud",
+        label: "MangoHud",
+        description: "Overlay showing FPS, temperatures and CPU/GPU load",
+        packages: &["mangohud", "lib32-mangohud"],
+        multilib: true,
+    },
+    GamingComponent {
+        id: "protonge",
+        label: "ProtonGE",
+        description: "Community Proton build with extra game fixes and codecs",
+        packages: &["proton-ge-custom-bin"],
+        multilib: false,
+    },
+];
+
+fn setup_gaming_components(builder: &Builder, window: &ApplicationWindow) {
+    let button = extract_widget::<Button>(builder, "btn_gaming_components");
+    let window = window.clone();
+
+    button.connect_clicked(move |_| {
+        info!("Gaming Components button clicked");
+
+        let config = GAMING_COMPONENTS.iter().fold(
+            SelectionDialogConfig::new(
+                "Gaming Components",
+                "Select the gaming components to install.",
             )
-            .build();
+            .selection_type(SelectionType::Multi)
+            .selection_required(true)
+            .confirm_label("Install"),
+            |config, component| {
+                let installed = core::installed_fraction(component.packages);
+                let description = if installed > 0.0 && installed < 1.0 {
+                    format!(
+                        "{} · Partially installed, select to repair",
+                        component.description
+                    )
+                } else {
+                    component.description.to_string()
+                };
+                config.add_option(SelectionOption::new(
+                    component.id,
+                    component.label,
+                    &description,
+                    installed == 1.0,
+                ))
+            },
+        );
 
-        task_runner::run(window.upcast_ref(), commands, "Steam AiO Installation");
+        let window_clone = window.clone();
+        show_selection_dialog(window.upcast_ref(), config, move |selected| {
+            let components: Vec<&GamingComponent> = GAMING_COMPONENTS
+                .iter()
+                .filter(|component| selected.iter().any(|id| id == component.id))
+                .collect();
+            if components.is_empty() {
+                return;
+            }
+
+            task_runner::run(
+                window_clone.upcast_ref(),
+                build_gaming_components_commands(&components),
+                "Gaming Components Installation",
+            );
+        });
     });
 }
 
+/// Build install steps for the chosen components.
+///
+/// ProtonGE gets its own step, so a failed AUR build doesn't hide whether
+/// the repository packages were installed.
+fn build_gaming_components_commands(components: &[&GamingComponent]) -> CommandSequence {
+    let (proton, repo): (Vec<&GamingComponent>, Vec<&GamingComponent>) = components
+        .iter()
+        .copied()
+        .partition(|component| component.id == "protonge");
+
+    let mut commands = CommandSequence::new();
+    if components.iter().any(|component| component.multilib) {
+        commands = commands.ensure_multilib();
+    }
+
+    if !repo.is_empty() {
+        let mut args = vec!["-S", "--noconfirm", "--needed"];
+        args.extend(
+            repo.iter()
+                .flat_map(|component| component.packages.iter().copied()),
+        );
+        commands = commands.then(
+            Command::builder()
+                .aur()
+                .args(&args)
+                .description("Installing gaming components...")
+                .build(),
+        );
+    }
+
+    if let Some(proton) = proton.first() {
+        let mut args = vec!["-S", "--noconfirm", "--needed"];
+        args.extend(proton.packages.iter().copied());
+        commands = commands.then(
+            Command::builder()
+                .aur()
+                .args(&args)
+                .description("Installing ProtonGE...")
+                .build(),
+        );
+    }
+
+    commands.build()
+}
+
 fn setup_lact_oc(builder: &Builder, window: &ApplicationWindow) {
     let button = extract_widget::<Button>(builder, "btn_lact_oc");
+    set_command_tooltip(&button, build_lact_oc_commands);
     let window = window.clone();
 
     button.connect_clicked(move |_| {
         info!("LACT OC button clicked");
-
-        let commands = CommandSequence::new()
-            .then(
-                Command::builder()
-                    .aur()
-                    .args(&["-S", "--noconfirm", "--needed", "lact"])
-                    .description("Installing LACT GPU control utility...")
-                    .build(),
-            )
-            .then(
-                Command::builder()
-                    .privileged()
-                    .program("systemctl")
-                    .args(&["enable", "--now", "lactd"])
-                    .description("Enabling LACT background service...")
-                    .build(),
-            )
-            .build();
+        let commands = build_lact_oc_commands();
 
         task_runner::run(window.upcast_ref(), commands, "LACT GPU Tools");
     });
 }
 
+/// Install LACT and enable its daemon.
+fn build_lact_oc_commands() -> CommandSequence {
+    CommandSequence::new()
+        .then(Command::install(&["lact"], "LACT GPU control utility"))
+        .then(
+            Command::builder()
+                .privileged()
+                .program("systemctl")
+                .args(&["enable", "--now", "lactd"])
+                .description("Enabling LACT background service...")
+                .build(),
+        )
+        .build()
+}
+
 fn setup_lutris(builder: &Builder, window: &ApplicationWindow) {
     let button = extract_widget::<Button>(builder, "btn_lutris");
+    set_command_tooltip(&button, build_lutris_commands);
     let window = window.clone();
 
     button.connect_clicked(move |_| {
         info!("Lutris button clicked");
-
-        let commands = CommandSequence::new()
-            .then(
-                Command::builder()
-                    .normal()
-                    .program("flatpak")
-                    .args(&[
-                        "install",
-                        "-y",
-                        "net.lutris.Lutris",
-                        "org.freedesktop.Platform.VulkanLayer.gamescope/x86_64/25.08",
-                        "org.freedesktop.Platform.VulkanLayer.MangoHud/x86_64/25.08",
-                    ])
-                    .description("Installing Lutris and Vulkan layers...")
-                    .build(),
-            )
-            .build();
+        let commands = build_lutris_commands();
 
         task_runner::run(window.upcast_ref(), commands, "Lutris Installation");
     });
 }
 
+/// Install Lutris with the gamescope and MangoHud Vulkan layers.
+fn build_lutris_commands() -> CommandSequence {
+    CommandSequence::new()
+        .then(
+            Command::builder()
+                .normal()
+                .program("flatpak")
+                .args(&[
+                    "install",
+                    "-y",
+                    "net.lutris.Lutris",
+                    "org.freedesktop.Platform.VulkanLayer.gamescope/x86_64/25.08",
+                    "org.freedesktop.Platform.VulkanLayer.MangoHud/x86_64/25.08",
+                ])
+                .description("Installing Lutris and Vulkan layers...")
+                .build(),
+        )
+        .build()
+}
+
 fn setup_heroic(builder: &Builder, window: &ApplicationWindow) {
     let button = extract_widget::<Button>(builder, "btn_heroic");
+    set_command_tooltip(&button, build_heroic_commands);
     let window = window.clone();
 
     button.connect_clicked(move |_| {
         info!("Heroic button clicked");
-
-        let commands = CommandSequence::new()
-            .then(
-                Command::builder()
-                    .normal()
-                    .program("flatpak")
-                    .args(&[
-                        "install",
-                        "-y",
-                        "com.heroicgameslauncher.hgl",
-                        "org.freedesktop.Platform.VulkanLayer.gamescope/x86_64/25.08",
-                        "org.freedesktop.Platform.VulkanLayer.MangoHud/x86_64/25.08",
-                    ])
-                    .description("Installing Heroic Games Launcher...")
-                    .build(),
-            )
-            .build();
+        let commands = build_heroic_commands();
 
         task_runner::run(
             window.upcast_ref(),
@@ -200,59 +342,67 @@ fn setup_heroic(builder: &Builder, window: &ApplicationWindow) {
     });
 }
 
+/// Install Heroic with the gamescope and MangoHud Vulkan layers.
+fn build_heroic_commands() -> CommandSequence {
+    CommandSequence::new()
+        .then(
+            Command::builder()
+                .normal()
+                .program("flatpak")
+                .args(&[
+                    "install",
+                    "-y",
+                    "com.heroicgameslauncher.hgl",
+                    "org.freedesktop.Platform.VulkanLayer.gamescope/x86_64/25.08",
+                    "org.freedesktop.Platform.VulkanLayer.MangoHud/x86_64/25.08",
+                ])
+                .description("Installing Heroic Games Launcher...")
+                .build(),
+        )
+        .build()
+}
+
 fn setup_bottles(builder: &Builder, window: &ApplicationWindow) {
     let button = extract_widget::<Button>(builder, "btn_bottles");
+    set_command_tooltip(&button, build_bottles_commands);
     let window = window.clone();
 
     button.connect_clicked(move |_| {
         info!("Bottles button clicked");
-
-        let commands = CommandSequence::new()
-            .then(
-                Command::builder()
-                    .normal()
-                    .program("flatpak")
-                    .args(&[
-                        "install",
-                        "-y",
-                        "com.usebottles.bottles",
-                        "org.freedesktop.Platform.VulkanLayer.gamescope/x86_64/25.08",
-                        "org.freedesktop.Platform.VulkanLayer.MangoHud/x86_64/25.08",
-                    ])
-                    .description("Installing Bottles and Vulkan layers...")
-                    .build(),
-            )
-            .build();
+        let commands = build_bottles_commands();
 
         task_runner::run(window.upcast_ref(), commands, "Bottles Installation");
     });
 }
 
+/// Install Bottles with the gamescope and MangoHud Vulkan layers.
+fn build_bottles_commands() -> CommandSequence {
+    CommandSequence::new()
+        .then(
+            Command::builder()
+                .normal()
+                .program("flatpak")
+                .args(&[
+                    "install",
+                    "-y",
+                    "com.usebottles.bottles",
+                    "org.freedesktop.Platform.VulkanLayer.gamescope/x86_64/25.08",
+                    "org.freedesktop.Platform.VulkanLayer.MangoHud/x86_64/25.08",
+                ])
+                .description("Installing Bottles and Vulkan layers...")
+                .build(),
+        )
+        .build()
+}
+
 fn setup_controller(builder: &Builder, window: &ApplicationWindow) {
     let button = extract_widget::<Button>(builder, "btn_controller");
+    set_command_tooltip(&button, build_controller_commands);
     let window = window.clone();
 
     button.connect_clicked(move |_| {
         info!("Controller Tools button clicked");
-
-        let commands = CommandSequence::new()
-            .then(
-                Command::builder()
-                    .aur()
-                    .args(&[
-                        "-S",
-                        "--noconfirm",
-                        "--needed",
-                        "gamepad-tool-bin",
-                        "sc-controller",
-                        "xone-dkms-git",
-                        "dualsensectl-git",
-                        "xone-dongle-firmware",
-                    ])
-                    .description("Installing controller tools and drivers...")
-                    .build(),
-            )
-            .build();
+        let commands = build_controller_commands();
 
         task_runner::run(
             window.upcast_ref(),
@@ -262,38 +412,49 @@ fn setup_controller(builder: &Builder, window: &ApplicationWindow) {
     });
 }
 
+/// Install controller tools and drivers.
+fn build_controller_commands() -> CommandSequence {
+    CommandSequence::new()
+        .then(Command::install(
+            &[
+                "gamepad-tool-bin",
+                "sc-controller",
+                "xone-dkms-git",
+                "dualsensectl-git",
+                "xone-dongle-firmware",
+            ],
+            "controller tools and drivers",
+        ))
+        .build()
+}
+
 fn setup_falcond(builder: &Builder, window: &ApplicationWindow) {
     let button = extract_widget::<Button>(builder, "btn_falcond");
+    set_command_tooltip(&button, build_falcond_commands);
     let window = window.clone();
 
     button.connect_clicked(move |_| {
         info!("Falcond button clicked");
-
-        let commands = CommandSequence::new()
-            .then(
-                Command::builder()
-                    .aur()
-                    .args(&[
-                        "-S",
-                        "--noconfirm",
-                        "--needed",
-                        "falcond",
-                        "falcond-gui",
-                        "falcond-profiles",
-                    ])
-                    .description("Installing Falcond Gaming utility...")
-                    .build(),
-            )
-            .then(
-                Command::builder()
-                    .privileged()
-                    .program("systemctl")
-                    .args(&["enable", "--now", "falcond"])
-                    .description("Enabling falcond background service...")
-                    .build(),
-            )
-            .build();
+        let commands = build_falcond_commands();
 
         task_runner::run(window.upcast_ref(), commands, "Falcond Installation");
     });
 }
+
+/// Install falcond and enable its service.
+fn build_falcond_commands() -> CommandSequence {
+    CommandSequence::new()
+        .then(Command::install(
+            &["falcond", "falcond-gui", "falcond-profiles"],
+            "Falcond Gaming utility",
+        ))
+        .then(
+            Command::builder()
+                .privileged()
+                .program("systemctl")
+                .args(&["enable", "--now", "falcond"])
+                .description("Enabling falcond background service...")
+                .build(),
+        )
+        .build()
+}
@@ -0,0 +1,362 @@
+//! Kernel manager page button handlers.
+//!
+//! Handles:
+//! - Listing installed kernels, marking the running one
+//! - Installing additional kernels with their headers
+//! - Removing kernels, with a warning before removing the running one
+//! - Installing the SCX schedulers and switching the active one
+
+use crate::core;
+use crate::core::kernel::{KernelInfo, KNOWN_KERNELS};
+use crate::core::scx::{self, SCHEDULERS, SCX_PACKAGE, SCX_SERVICE};
+use crate::ui::dialogs::selection::{
+    show_selection_dialog, SelectionDialogConfig, SelectionOption, SelectionType,
+};
+use crate::ui::dialogs::warning::show_warning_confirmation;
+use crate::ui::task_runner::{self, Command, CommandSequence, RunOptions};
+use crate::ui::utils::extract_widget;
+use adw::prelude::*;
+use gtk4::{ApplicationWindow, Builder, Button, Label, ListBox};
+use log::info;
+use std::path::Path;
+
+/// GRUB menu regenerated after kernels are added or removed.
+const GRUB_CONFIG: &str = "/boot/grub/grub.cfg";
+
+/// Set up all button handlers for the kernel manager page.
+pub fn setup_handlers(page_builder: &Builder, _main_builder: &Builder, window: &ApplicationWindow) {
+    let kernel_list = extract_widget::<ListBox>(page_builder, "kernel_list");
+    refresh_kernel_list(&kernel_list, window);
+
+    setup_install_kernel(page_builder, window, &kernel_list);
+
+    let scx_controls = ScxControls::new(page_builder);
+    scx_controls.refresh();
+    setup_scx_install(window, &scx_controls);
+    setup_scx_scheduler(window, &scx_controls);
+}
+
+/// Rebuild the rows of the installed kernel list.
+fn refresh_kernel_list(kernel_list: &ListBox, window: &ApplicationWindow) {
+    kernel_list.remove_all();
+
+    let kernels = core::kernel::list_installed_kernels();
+    // Never offer to remove the last kernel
+    let removable = kernels.len() > 1;
+    for kernel in kernels {
+        kernel_list.append(&kernel_row(kernel, removable, kernel_list, window));
+    }
+}
+
+/// Build the list row of an installed kernel, with its remove button.
+fn kernel_row(
+    kernel: KernelInfo,
+    removable: bool,
+    kernel_list: &ListBox,
+    window: &ApplicationWindow,
+) -> adw::ActionRow {
+    let subtitle = if kernel.running {
+        format!("{} · Running", kernel.version)
+    } else {
+        kernel.version.clone()
+    };
+    let row = adw::ActionRow::builder()
+        .title(kernel.name.as_str())
+        .subtitle(subtitle.as_str())
+        .build();
+
+    let remove_button = Button::builder()
+        .icon_name("user-trash-symbolic")
+        .valign(gtk4::Align::Center)
+        .sensitive(removable)
+        .css_classes(["flat", "destructive-action"])
+        .build();
+    remove_button.set_tooltip_text(Some(if removable {
+        "Remove this kernel"
+    } else {
+        "The only installed kernel can't be removed"
+    }));
+    remove_button.update_property(&[gtk4::accessible::Property::Label(&format!(
+        "Remove {}",
+        kernel.name
+    ))]);
+    row.add_suffix(&remove_button);
+
+    let kernel_list = kernel_list.clone();
+    let window = window.clone();
+    remove_button.connect_clicked(move |_| {
+        info!("Kernel Manager: remove {} clicked", kernel.name);
+
+        if !kernel.running {
+            remove_kernel(&kernel, &kernel_list, &window);
+            return;
+        }
+
+        let kernel = kernel.clone();
+        let kernel_list = kernel_list.clone();
+        let window_clone = window.clone();
+        show_warning_confirmation(
+            window.upcast_ref(),
+            "Remove Running Kernel",
+            &format!(
+                "<b>{}</b> is the kernel you are running right now.\n\
+                Removing it deletes its modules, so devices may stop working until you \
+                <span foreground=\"red\" weight=\"bold\">reboot into another kernel</span>.",
+                kernel.name
+            ),
+            move || remove_kernel(&kernel, &kernel_list, &window_clone),
+        );
+    });
+
+    row
+}
+
+/// Remove a kernel together with its headers.
+fn remove_kernel(kernel: &KernelInfo, kernel_list: &ListBox, window: &ApplicationWindow) {
+    let headers = kernel.headers();
+    let mut packages = vec![kernel.name.as_str()];
+    if core::is_package_installed(&headers) {
+        packages.push(&headers);
+    }
+
+    let commands =
+        with_grub_update(CommandSequence::new().then(Command::aur_remove(&packages, &kernel.name)));
+    let kernel_list = kernel_list.clone();
+    let window_clone = window.clone();
+    run_then(window, commands, "Remove Kernel", move || {
+        refresh_kernel_list(&kernel_list, &window_clone)
+    });
+}
+
+fn setup_install_kernel(page_builder: &Builder, window: &ApplicationWindow, kernel_list: &ListBox) {
+    let button = extract_widget::<Button>(page_builder, "btn_install_kernel");
+    let window = window.clone();
+    let kernel_list = kernel_list.clone();
+
+    button.connect_clicked(move |_| {
+        info!("Kernel Manager: Install Kernel button clicked");
+
+        let config = KNOWN_KERNELS.iter().fold(
+            SelectionDialogConfig::new(
+                "Install Kernel",
+                "Select the kernels to install. Headers are installed along with them \
+                so DKMS modules keep building.",
+            )
+            .selection_type(SelectionType::Multi)
+            .selection_required(true)
+            .confirm_label("Install"),
+            |config, (name, description)| {
+                config.add_option(SelectionOption::new(
+                    name,
+                    name,
+                    description,
+                    core::is_package_installed(name),
+                ))
+            },
+        );
+
+        let window_clone = window.clone();
+        let kernel_list = kernel_list.clone();
+        show_selection_dialog(window.upcast_ref(), config, move |selected| {
+            let kernel_list = kernel_list.clone();
+            let window = window_clone.clone();
+            run_then(
+                &window_clone,
+                build_install_kernel_commands(&selected),
+                "Install Kernel",
+                move || refresh_kernel_list(&kernel_list, &window),
+            );
+        });
+    });
+}
+
+/// Install the selected kernels and their headers.
+fn build_install_kernel_commands(kernels: &[String]) -> CommandSequence {
+    let mut args = vec!["-S", "--noconfirm", "--needed"];
+    args.extend(kernels.iter().map(String::as_str));
+    let headers: Vec<String> = kernels.iter().map(|k| format!("{}-headers", k)).collect();
+    args.extend(headers.iter().map(String::as_str));
+
+    with_grub_update(
+        CommandSequence::new().then(
+            Command::builder()
+                .aur()
+                .args(&args)
+                .description("Installing kernels and headers...")
+                .weight(5.0)
+                .requires_reboot()
+                .build(),
+        ),
+    )
+}
+
+/// Regenerate the GRUB menu after a kernel change, if GRUB is in use.
+fn with_grub_update(commands: CommandSequence) -> CommandSequence {
+    if !Path::new(GRUB_CONFIG).exists() {
+        return commands.build();
+    }
+
+    commands
+        .then(
+            Command::builder()
+                .privileged()
+                .program("grub-mkconfig")
+                .args(&["-o", GRUB_CONFIG])
+                .description("Updating boot menu...")
+                .build(),
+        )
+        .build()
+}
+
+/// SCX widgets that reflect the detected scheduler state.
+#[derive(Clone)]
+struct ScxControls {
+    status_label: Label,
+    install_button: Button,
+    scheduler_button: Button,
+}
+
+impl ScxControls {
+    fn new(page_builder: &Builder) -> Self {
+        Self {
+            status_label: extract_widget(page_builder, "scx_status_label"),
+            install_button: extract_widget(page_builder, "btn_scx_install"),
+            scheduler_button: extract_widget(page_builder, "btn_scx_scheduler"),
+        }
+    }
+
+    /// Update the status text and buttons from the current SCX state.
+    fn refresh(&self) {
+        let status = scx::status();
+
+        let text = if !status.installed {
+            format!("{} is not installed.", SCX_PACKAGE)
+        } else {
+            let scheduler = status.scheduler.as_deref().unwrap_or("none configured");
+            let state = if status.running { "running" } else { "stopped" };
+            format!("Scheduler: {} · {}", scheduler, state)
+        };
+        let text = if status.supported {
+            text
+        } else {
+            format!("{}\nThe running kernel does not support sched-ext.", text)
+        };
+        self.status_label.set_label(&text);
+
+        self.install_button.set_sensitive(!status.installed);
+        self.scheduler_button.set_sensitive(status.installed);
+    }
+}
+
+fn setup_scx_install(window: &ApplicationWindow, controls: &ScxControls) {
+    let window = window.clone();
+    let controls = controls.clone();
+
+    controls.install_button.clone().connect_clicked(move |_| {
+        info!("Kernel Manager: Install SCX button clicked");
+
+        let commands = CommandSequence::new()
+            .then(Command::install(&[SCX_PACKAGE], "SCX schedulers"))
+            .then(
+                Command::builder()
+                    .privileged()
+                    .program("systemctl")
+                    .args(&["enable", "--now", SCX_SERVICE])
+                    .description("Enabling SCX scheduler service...")
+                    .build(),
+            )
+            .build();
+
+        let controls = controls.clone();
+        run_then(&window, commands, "Install SCX", move || controls.refresh());
+    });
+}
+
+fn setup_scx_scheduler(window: &ApplicationWindow, controls: &ScxControls) {
+    let window = window.clone();
+    let controls = controls.clone();
+
+    controls.scheduler_button.clone().connect_clicked(move |_| {
+        info!("Kernel Manager: Switch Scheduler button clicked");
+
+        let current = scx::status().scheduler;
+        let config = SCHEDULERS.iter().fold(
+            SelectionDialogConfig::new(
+                "Switch Scheduler",
+                "Select the sched-ext scheduler to run. The SCX service is restarted \
+                with the new scheduler.",
+            )
+            .selection_type(SelectionType::Single)
+            .selection_required(true)
+            .confirm_label("Switch"),
+            |config, (name, description)| {
+                config.add_option(SelectionOption::new(
+                    name,
+                    name,
+                    description,
+                    current.as_deref() == Some(*name),
+                ))
+            },
+        );
+
+        let window_clone = window.clone();
+        let controls = controls.clone();
+        show_selection_dialog(window.upcast_ref(), config, move |selected| {
+            let Some(scheduler) = selected.first() else {
+                return;
+            };
+            let controls = controls.clone();
+            run_then(
+                &window_clone,
+                build_switch_scheduler_commands(scheduler),
+                "Switch Scheduler",
+                move || controls.refresh(),
+            );
+        });
+    });
+}
+
+/// Configure the scheduler for the SCX service and restart it.
+fn build_switch_scheduler_commands(scheduler: &str) -> CommandSequence {
+    CommandSequence::new()
+        .then(
+            Command::builder()
+                .privileged()
+                .program("bash")
+                .args(&["-c", &scx::set_scheduler_script(scheduler)])
+                .description(&format!("Setting scheduler to {}...", scheduler))
+                .build(),
+        )
+        .then(
+            Command::builder()
+                .privileged()
+                .program("systemctl")
+                .args(&["enable", SCX_SERVICE])
+                .description("Enabling SCX scheduler service...")
+                .build(),
+        )
+        .then(
+            Command::builder()
+                .privileged()
+                .program("systemctl")
+                .args(&["restart", SCX_SERVICE])
+                .description("Restarting SCX scheduler service...")
+                .build(),
+        )
+        .build()
+}
+
+/// Run commands, calling `on_done` once the run has ended.
+///
+/// `on_done` runs after failures too, since earlier steps may have changed
+/// the system already.
+fn run_then(
+    window: &ApplicationWindow,
+    commands: CommandSequence,
+    title: &str,
+    on_done: impl Fn() + 'static,
+) {
+    let options = RunOptions::new().on_complete(move |_| on_done());
+
+    task_runner::run_with_options(window.upcast_ref(), commands, title, options);
+}
@@ -1,8 +1,9 @@
 //! Main page button handlers.
 //!
 //! Handles:
-//! - System update
+//! - System update, with the number of pending updates
 //! - Package manager GUI installation
+//! - Flatpak apps from Flathub, installed per user
 //! - Download Arch ISO
 //! - External links (Discord, YouTube, Website, Donate)
 
@@ -14,22 +15,27 @@ use crate::ui::dialogs::selection::{
 };
 use crate::ui::dialogs::terminal;
 use crate::ui::dialogs::warning::show_warning_confirmation;
-use crate::ui::task_runner::{self, Command, CommandSequence};
+use crate::ui::task_runner::{self, Command, CommandSequence, RunOptions};
 use crate::ui::utils::extract_widget;
-use gtk4::prelude::*;
-use gtk4::{ApplicationWindow, Builder, Button};
-use log::info;
+use adw::prelude::*;
+use gtk4::{glib, ApplicationWindow, Builder, Button, Label};
+use log::{info, warn};
 
 /// Set up all button handlers for the main page.
 pub fn setup_handlers(page_builder: &Builder, _main_builder: &Builder, window: &ApplicationWindow) {
     setup_update_system(page_builder, window);
+    setup_update_count(page_builder, window);
     setup_pkg_manager(page_builder, window);
+    setup_flatpak_apps(page_builder, window);
     setup_download_arch_iso(page_builder, window);
     setup_install_nix(page_builder, window);
     setup_external_links(page_builder);
 }
 
 /// Setup system update button.
+///
+/// Shows the pending update count before updating repo packages and then
+/// AUR packages in the task runner.
 fn setup_update_system(builder: &Builder, window: &ApplicationWindow) {
     let button = extract_widget::<Button>(builder, "btn_update_system");
     let window = window.clone();
@@ -37,14 +43,149 @@ fn setup_update_system(builder: &Builder, window: &ApplicationWindow) {
     button.connect_clicked(move |_| {
         info!("Update System button clicked");
 
-        // Use terminal dialog for interactive system update
-        terminal::show_terminal_dialog(
-            window.upcast_ref(),
+        let window = window.clone();
+        glib::spawn_future_local(async move {
+            let counts = core::package::count_available_updates(false).await;
+            confirm_update_system(&window, counts);
+        });
+    });
+}
+
+/// Ask before updating, naming how many updates are pending if known.
+fn confirm_update_system(window: &ApplicationWindow, counts: Option<(usize, usize)>) {
+    let pending = match counts {
+        Some((0, 0)) => "No updates were found when last checked, but the package \
+            databases will be synced again."
+            .to_string(),
+        Some(counts) => format!("{}.", update_count_text(counts)),
+        None => "Pending updates could not be counted.".to_string(),
+    };
+
+    let dialog = adw::AlertDialog::builder()
+        .heading("Update System?")
+        .body(format!(
+            "{}\n\nRepository packages are updated first, then AUR packages.",
+            pending
+        ))
+        .build();
+    dialog.add_responses(&[("cancel", "Cancel"), ("update", "Update")]);
+    dialog.set_response_appearance("update", adw::ResponseAppearance::Suggested);
+    dialog.set_default_response(Some("update"));
+    dialog.set_close_response("cancel");
+
+    let window_clone = window.clone();
+    dialog.connect_response(Some("update"), move |_, _| {
+        task_runner::run(
+            window_clone.upcast_ref(),
+            build_update_system_commands(),
             "System Update",
-            "/usr/local/bin/upd",
-            &[],
         );
     });
+
+    dialog.present(Some(window));
+}
+
+/// Update repo packages, then AUR packages if an AUR helper is in use.
+///
+/// Asks for a reboot if the last update check found a kernel update.
+fn build_update_system_commands() -> CommandSequence {
+    let kernels: Vec<&str> = core::kernel::KNOWN_KERNELS
+        .iter()
+        .map(|(name, _)| *name)
+        .collect();
+    let mut repo_update = Command::builder()
+        .privileged()
+        .program("pacman")
+        .args(&["-Syu", "--noconfirm"])
+        .description("Updating system packages...")
+        .weight(5.0);
+    if core::package::has_pending_update(&kernels) {
+        repo_update = repo_update.requires_reboot();
+    }
+
+    let mut commands = CommandSequence::new().then(repo_update.build());
+    if core::aur_helper().is_some() {
+        commands = commands.then(
+            Command::builder()
+                .aur()
+                .args(&["-Sua", "--noconfirm"])
+                .description("Updating AUR packages...")
+                .weight(3.0)
+                .build(),
+        );
+    }
+    commands.build()
+}
+
+/// Setup the pending update count below the update button.
+///
+/// Counts are cached, the button checks again. Without pacman-contrib the
+/// button installs it first.
+fn setup_update_count(builder: &Builder, window: &ApplicationWindow) {
+    let label = extract_widget::<Label>(builder, "update_status_label");
+    let button = extract_widget::<Button>(builder, "btn_check_updates");
+    show_update_count(&label, &button, false);
+
+    let window = window.clone();
+    button.connect_clicked(move |button| {
+        info!("Check for Updates button clicked");
+        if core::is_package_installed(core::package::CHECKUPDATES_PACKAGE) {
+            show_update_count(&label, button, true);
+            return;
+        }
+
+        let commands = CommandSequence::new()
+            .then(Command::install_native(
+                &[core::package::CHECKUPDATES_PACKAGE],
+                "pacman-contrib for checkupdates",
+            ))
+            .build();
+        let label = label.clone();
+        let button = button.clone();
+        let options =
+            RunOptions::new().on_complete(move |_| show_update_count(&label, &button, true));
+        task_runner::run_with_options(
+            window.upcast_ref(),
+            commands,
+            "Install pacman-contrib",
+            options,
+        );
+    });
+}
+
+/// Count pending updates in the background and show the result in `label`.
+fn show_update_count(label: &Label, button: &Button, refresh: bool) {
+    if !core::is_package_installed(core::package::CHECKUPDATES_PACKAGE) {
+        label.set_label("Install pacman-contrib to see pending updates");
+        button.set_label("Install pacman-contrib");
+        return;
+    }
+
+    label.set_label("Checking for updates...");
+    button.set_label("Check for Updates");
+    button.set_sensitive(false);
+
+    let label = label.clone();
+    let button = button.clone();
+    glib::spawn_future_local(async move {
+        let text = match core::package::count_available_updates(refresh).await {
+            Some(counts) => update_count_text(counts),
+            None => "Could not check for updates".to_string(),
+        };
+        label.set_label(&text);
+        button.set_sensitive(true);
+    });
+}
+
+/// Describe (repo, AUR) pending update counts.
+fn update_count_text((repo, aur): (usize, usize)) -> String {
+    let total = repo + aur;
+    let updates = if total == 1 { "update" } else { "updates" };
+    match (total, aur) {
+        (0, _) => "System is up to date".to_string(),
+        (_, 0) => format!("{} {} available", total, updates),
+        _ => format!("{} {} available, {} from the AUR", total, updates, aur),
+    }
 }
 
 /// Setup package manager GUI button.
@@ -62,6 +203,7 @@ fn setup_pkg_manager(builder: &Builder, window: &ApplicationWindow) {
         )
         .selection_type(SelectionType::Multi)
         .selection_required(true)
+        .searchable(true)
         .add_option(SelectionOption::new(
             "octopi",
             "Octopi",
@@ -84,19 +226,19 @@ fn setup_pkg_manager(builder: &Builder, window: &ApplicationWindow) {
             "warehouse",
             "Warehouse",
             "Flatpak package manager (Flatpak)",
-            core::is_flatpak_installed("io.github.flattool.Warehouse"),
+            core::is_flatpak_installed("io.github.flattool.Warehouse", core::FlatpakKind::App),
         ))
         .add_option(SelectionOption::new(
             "flatseal",
             "Flatseal",
             "Flatpak permissions manager (Flatpak)",
-            core::is_flatpak_installed("com.github.tchx84.Flatseal"),
+            core::is_flatpak_installed("com.github.tchx84.Flatseal", core::FlatpakKind::App),
         ))
         .add_option(SelectionOption::new(
             "bazaar",
             "Bazaar",
             "Browse and install Flatpak apps (Flatpak)",
-            core::is_flatpak_installed("io.github.kolunmi.Bazaar"),
+            core::is_flatpak_installed("io.github.kolunmi.Bazaar", core::FlatpakKind::App),
         ))
         .confirm_label("Install");
 
@@ -120,40 +262,24 @@ fn build_pkg_manager_commands(selected: &[String]) -> CommandSequence {
     let mut commands = CommandSequence::new();
 
     if selected.iter().any(|s| s == "octopi") {
-        commands = commands.then(
-            Command::builder()
-                .aur()
-                .args(&["-S", "--noconfirm", "--needed", "octopi"])
-                .description("Installing Octopi package manager...")
-                .build(),
-        );
+        commands = commands.then(Command::install(&["octopi"], "Octopi package manager"));
     }
 
     if selected.iter().any(|s| s == "pacseek") {
-        commands = commands.then(
-            Command::builder()
-                .aur()
-                .args(&["-S", "--noconfirm", "--needed", "pacseek", "pacfinder"])
-                .description("Installing PacSeek package browser...")
-                .build(),
-        );
+        commands = commands.then(Command::install(
+            &["pacseek", "pacfinder"],
+            "PacSeek package browser",
+        ));
     }
 
     if selected.iter().any(|s| s == "bauh") {
-        commands = commands.then(
-            Command::builder()
-                .aur()
-                .args(&["-S", "--noconfirm", "--needed", "bauh"])
-                .description("Installing Bauh package manager...")
-                .build(),
-        );
+        commands = commands.then(Command::install(&["bauh"], "Bauh package manager"));
     }
 
     if selected.iter().any(|s| s == "warehouse") {
         commands = commands.then(
             Command::builder()
-                .normal()
-                .program("flatpak")
+                .flatpak()
                 .args(&["install", "-y", "io.github.flattool.Warehouse"])
                 .description("Installing Warehouse from Flathub...")
                 .build(),
@@ -163,8 +289,7 @@ fn build_pkg_manager_commands(selected: &[String]) -> CommandSequence {
     if selected.iter().any(|s| s == "flatseal") {
         commands = commands.then(
             Command::builder()
-                .normal()
-                .program("flatpak")
+                .flatpak()
                 .args(&["install", "-y", "com.github.tchx84.Flatseal"])
                 .description("Installing Flatseal from Flathub...")
                 .build(),
@@ -174,8 +299,7 @@ fn build_pkg_manager_commands(selected: &[String]) -> CommandSequence {
     if selected.iter().any(|s| s == "bazaar") {
         commands = commands.then(
             Command::builder()
-                .normal()
-                .program("flatpak")
+                .flatpak()
                 .args(&["install", "-y", "io.github.kolunmi.Bazaar"])
                 .description("Installing Bazaar from Flathub...")
                 .build(),
@@ -185,6 +309,101 @@ fn build_pkg_manager_commands(selected: &[String]) -> CommandSequence {
     commands
 }
 
+/// Flathub remote added for per-user installs.
+const FLATHUB_REPO: &str = "https://dl.flathub.org/repo/flathub.flatpakrepo";
+
+/// Apps offered by the Flatpak apps dialog: Flathub ID, name and description.
+const FLATPAK_APPS: &[(&str, &str, &str)] = &[
+    ("com.spotify.Client", "Spotify", "Music streaming client"),
+    ("com.discordapp.Discord", "Discord", "Voice and text chat"),
+    ("org.telegram.desktop", "Telegram", "Messaging app"),
+    ("org.libreoffice.LibreOffice", "LibreOffice", "Office suite"),
+    ("org.gimp.GIMP", "GIMP", "Image editor"),
+    ("org.kde.krita", "Krita", "Digital painting"),
+    (
+        "md.obsidian.Obsidian",
+        "Obsidian",
+        "Markdown knowledge base",
+    ),
+    ("com.bitwarden.desktop", "Bitwarden", "Password manager"),
+];
+
+/// Setup Flatpak apps button.
+fn setup_flatpak_apps(builder: &Builder, window: &ApplicationWindow) {
+    let button = extract_widget::<Button>(builder, "btn_flatpak_apps");
+    let window = window.clone();
+
+    button.connect_clicked(move |_| {
+        info!("Flatpak Apps button clicked");
+        let window = window.clone();
+
+        // Query installed apps once without blocking the main loop
+        glib::spawn_future_local(async move {
+            let installed = core::package::installed_flatpaks_async(core::FlatpakKind::App).await;
+
+            let config = FLATPAK_APPS.iter().fold(
+                SelectionDialogConfig::new(
+                    "Flatpak Apps",
+                    "Select apps to install from Flathub. They are installed for your user \
+                    only, so no password is needed.",
+                )
+                .selection_type(SelectionType::Multi)
+                .selection_required(true)
+                .searchable(true),
+                |config, (id, name, description)| {
+                    config.add_option(SelectionOption::new(
+                        id,
+                        name,
+                        description,
+                        installed.contains(*id),
+                    ))
+                },
+            );
+
+            let window_for_closure = window.clone();
+            show_selection_dialog(window.upcast_ref(), config, move |selected| {
+                task_runner::run(
+                    window_for_closure.upcast_ref(),
+                    build_flatpak_apps_commands(&selected),
+                    "Flatpak Apps Installation",
+                );
+            });
+        });
+    });
+}
+
+/// Build commands installing Flatpak apps for the current user.
+///
+/// Installs flatpak if needed and adds the Flathub remote to the user
+/// installation, which is separate from the system-wide one.
+fn build_flatpak_apps_commands(app_ids: &[String]) -> CommandSequence {
+    let mut commands = CommandSequence::new();
+
+    if !core::is_package_installed("flatpak") {
+        commands = commands.then(Command::install(&["flatpak"], "Flatpak"));
+    }
+
+    let mut install_args = vec!["install", "--user", "-y", "flathub"];
+    install_args.extend(app_ids.iter().map(String::as_str));
+
+    commands
+        .then(
+            Command::builder()
+                .flatpak()
+                .args(&[
+                    "remote-add",
+                    "--user",
+                    "--if-not-exists",
+                    "flathub",
+                    FLATHUB_REPO,
+                ])
+                .description("Adding Flathub remote...")
+                .build(),
+        )
+        .then(Command::flatpak(&install_args, "Flatpak apps"))
+        .build()
+}
+
 /// Setup download Arch ISO button.
 fn setup_download_arch_iso(builder: &Builder, window: &ApplicationWindow) {
     let button = extract_widget::<Button>(builder, "btn_download_arch_iso");
@@ -267,29 +486,36 @@ fn setup_install_nix(builder: &Builder, window: &ApplicationWindow) {
     });
 }
 
+/// Open an external link, logging a warning if no browser could be started.
+fn open_link(url: &str) {
+    if let Err(e) = core::package::open_url(url) {
+        warn!("Failed to open {}: {}", url, e);
+    }
+}
+
 /// Setup external link buttons.
 fn setup_external_links(builder: &Builder) {
     let btn_discord = extract_widget::<Button>(builder, "link_discord");
     btn_discord.connect_clicked(|_| {
         info!("Discord link clicked");
-        let _ = core::package::open_url(config::links::DISCORD);
+        open_link(config::links::DISCORD);
     });
 
     let btn_youtube = extract_widget::<Button>(builder, "link_youtube");
     btn_youtube.connect_clicked(|_| {
         info!("YouTube link clicked");
-        let _ = core::package::open_url(config::links::YOUTUBE);
+        open_link(config::links::YOUTUBE);
     });
 
     let btn_website = extract_widget::<Button>(builder, "link_website");
     btn_website.connect_clicked(|_| {
         info!("Website link clicked");
-        let _ = core::package::open_url(config::links::WEBSITE);
+        open_link(config::links::WEBSITE);
     });
 
     let btn_donate = extract_widget::<Button>(builder, "link_donate");
     btn_donate.connect_clicked(|_| {
         info!("Donate link clicked");
-        let _ = core::package::open_url(config::links::DONATE);
+        open_link(config::links::DONATE);
     });
 }
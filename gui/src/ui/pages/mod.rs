@@ -7,6 +7,7 @@
 //! - `gamescope`: Gamescope command generator
 //! - `containers_vms`: Docker, Podman, VirtualBox, KVM
 //! - `multimedia_tools`: OBS, Jellyfin
+//! - `kernel_manager_scx`: Installed kernels
 //! - `customization`: ZSH, themes, wallpapers
 //! - `servicing`: System fixes and maintenance
 
@@ -15,6 +16,7 @@ pub mod customization;
 pub mod drivers;
 pub mod gamescope;
 pub mod gaming_tools;
+pub mod kernel_manager_scx;
 pub mod main_page;
 pub mod multimedia_tools;
 pub mod servicing;
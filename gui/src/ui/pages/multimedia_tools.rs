@@ -2,22 +2,25 @@
 //!
 //! Handles:
 //! - OBS-Studio with plugins and V4L2
+//! - Codec bundle with GPU video acceleration and optional players
 //! - Kdenlive video editor
 //! - Jellyfin server installation
 
 use crate::core;
+use crate::core::gpu::GpuVendor;
 use crate::ui::dialogs::selection::{
     show_selection_dialog, SelectionDialogConfig, SelectionOption, SelectionType,
 };
 use crate::ui::task_runner::{self, Command, CommandSequence};
-use crate::ui::utils::extract_widget;
+use crate::ui::utils::{extract_widget, set_command_tooltip};
 use gtk4::prelude::*;
-use gtk4::{ApplicationWindow, Builder};
+use gtk4::{glib, ApplicationWindow, Builder};
 use log::info;
 
 /// Set up all button handlers for the multimedia tools page
 pub fn setup_handlers(page_builder: &Builder, _main_builder: &Builder, window: &ApplicationWindow) {
     setup_obs_studio_aio(page_builder, window);
+    setup_codecs(page_builder, window);
     setup_kdenlive(page_builder, window);
     setup_jellyfin(page_builder, window);
 }
@@ -27,31 +30,42 @@ fn setup_obs_studio_aio(page_builder: &Builder, window: &ApplicationWindow) {
     let window = window.clone();
     btn_obs_studio_aio.connect_clicked(move |_| {
         info!("Multimedia tools: OBS-Studio AiO button clicked");
-        let window_ref = window.upcast_ref();
+        let window = window.clone();
+
+        // Query installed state without blocking the main loop; OBS plugins are flatpak extensions
+        glib::spawn_future_local(async move {
+                let window_ref = window.upcast_ref();
+                let flatpaks = core::package::installed_flatpaks_async(core::FlatpakKind::Runtime).await;
+                let has_all = |ids: &[&str]| ids.iter().all(|id| flatpaks.contains(*id));
 
                 let wayland_hotkeys_installed =
-                    core::is_flatpak_installed("com.obsproject.Studio.Plugin.WaylandHotkeys");
-                let v4l2_installed = core::is_package_installed("v4l2loopback-dkms");
+                    has_all(&["com.obsproject.Studio.Plugin.WaylandHotkeys"]);
+                let v4l2_installed =
+                    core::package::is_package_installed_async("v4l2loopback-dkms").await;
 
-                let graphics_capture_installed =
-                    core::is_flatpak_installed("com.obsproject.Studio.Plugin.OBSVkCapture") &&
-                    core::is_flatpak_installed("com.obsproject.Studio.Plugin.Gstreamer") &&
-                    core::is_flatpak_installed("com.obsproject.Studio.Plugin.GStreamerVaapi");
+                let graphics_capture_installed = has_all(&[
+                    "com.obsproject.Studio.Plugin.OBSVkCapture",
+                    "com.obsproject.Studio.Plugin.Gstreamer",
+                    "com.obsproject.Studio.Plugin.GStreamerVaapi",
+                ]);
 
-                let transitions_effects_installed =
-                    core::is_flatpak_installed("com.obsproject.Studio.Plugin.MoveTransition") &&
-                    core::is_flatpak_installed("com.obsproject.Studio.Plugin.TransitionTable") &&
-                    core::is_flatpak_installed("com.obsproject.Studio.Plugin.ScaleToSound");
+                let transitions_effects_installed = has_all(&[
+                    "com.obsproject.Studio.Plugin.MoveTransition",
+                    "com.obsproject.Studio.Plugin.TransitionTable",
+                    "com.obsproject.Studio.Plugin.ScaleToSound",
+                ]);
 
-                let streaming_tools_installed =
-                    core::is_flatpak_installed("com.obsproject.Studio.Plugin.WebSocket") &&
-                    core::is_flatpak_installed("com.obsproject.Studio.Plugin.SceneSwitcher") &&
-                    core::is_flatpak_installed("com.obsproject.Studio.Plugin.DroidCam");
+                let streaming_tools_installed = has_all(&[
+                    "com.obsproject.Studio.Plugin.WebSocket",
+                    "com.obsproject.Studio.Plugin.SceneSwitcher",
+                    "com.obsproject.Studio.Plugin.DroidCam",
+                ]);
 
-                let audio_video_tools_installed =
-                    core::is_flatpak_installed("com.obsproject.Studio.Plugin.waveform") &&
-                    core::is_flatpak_installed("com.obsproject.Studio.Plugin.VerticalCanvas") &&
-                    core::is_flatpak_installed("com.obsproject.Studio.Plugin.BackgroundRemoval");
+                let audio_video_tools_installed = has_all(&[
+                    "com.obsproject.Studio.Plugin.waveform",
+                    "com.obsproject.Studio.Plugin.VerticalCanvas",
+                    "com.obsproject.Studio.Plugin.BackgroundRemoval",
+                ]);
 
                 let config = SelectionDialogConfig::new(
                     "OBS-Studio & Plugins Installation",
@@ -103,24 +117,21 @@ fn setup_obs_studio_aio(page_builder: &Builder, window: &ApplicationWindow) {
 
                     // Always install OBS-Studio
                     commands = commands.then(Command::builder()
-                        .normal()
-                        .program("flatpak")
+                        .flatpak()
                         .args(&["install", "-y", "com.obsproject.Studio"])
                         .description("Installing OBS-Studio...")
                         .build());
 
                     if selected_ids.iter().any(|s| s == "wayland_hotkeys") {
                         commands = commands.then(Command::builder()
-                            .normal()
-                            .program("flatpak")
+                            .flatpak()
                             .args(&["install", "-y", "com.obsproject.Studio.Plugin.WaylandHotkeys"])
                             .description("Installing Wayland Hotkeys plugin...")
                             .build());
                     }
                     if selected_ids.iter().any(|s| s == "graphics_capture") {
                         commands = commands.then(Command::builder()
-                            .normal()
-                            .program("flatpak")
+                            .flatpak()
                             .args(&[
                                 "install",
                                 "-y",
@@ -134,8 +145,7 @@ fn setup_obs_studio_aio(page_builder: &Builder, window: &ApplicationWindow) {
                     }
                     if selected_ids.iter().any(|s| s == "transitions_effects") {
                         commands = commands.then(Command::builder()
-                            .normal()
-                            .program("flatpak")
+                            .flatpak()
                             .args(&[
                                 "install",
                                 "-y",
@@ -148,8 +158,7 @@ fn setup_obs_studio_aio(page_builder: &Builder, window: &ApplicationWindow) {
                     }
                     if selected_ids.iter().any(|s| s == "streaming_tools") {
                         commands = commands.then(Command::builder()
-                            .normal()
-                            .program("flatpak")
+                            .flatpak()
                             .args(&[
                                 "install",
                                 "-y",
@@ -162,8 +171,7 @@ fn setup_obs_studio_aio(page_builder: &Builder, window: &ApplicationWindow) {
                     }
                     if selected_ids.iter().any(|s| s == "audio_video_tools") {
                         commands = commands.then(Command::builder()
-                            .normal()
-                            .program("flatpak")
+                            .flatpak()
                             .args(&[
                                 "install",
                                 "-y",
@@ -175,11 +183,10 @@ fn setup_obs_studio_aio(page_builder: &Builder, window: &ApplicationWindow) {
                             .build());
                     }
                     if selected_ids.iter().any(|s| s == "v4l2") {
-                        commands = commands.then(Command::builder()
-                            .aur()
-                            .args(&["-S", "--noconfirm", "--needed", "v4l2loopback-dkms", "v4l2loopback-utils"])
-                            .description("Installing V4L2 loopback modules...")
-                            .build());
+                        commands = commands.then(Command::install(
+                            &["v4l2loopback-dkms", "v4l2loopback-utils"],
+                            "V4L2 loopback modules",
+                        ));
                         commands = commands.then(Command::builder()
                             .privileged()
                             .program("sh")
@@ -199,58 +206,153 @@ fn setup_obs_studio_aio(page_builder: &Builder, window: &ApplicationWindow) {
 
                     task_runner::run(window_for_closure.upcast_ref(), commands.build(), "OBS-Studio Setup");
                 });
+        });
+    });
+}
+
+fn setup_codecs(page_builder: &Builder, window: &ApplicationWindow) {
+    let btn_codecs = extract_widget::<gtk4::Button>(page_builder, "btn_codecs");
+    let window = window.clone();
+    btn_codecs.connect_clicked(move |_| {
+        info!("Multimedia tools: Codecs button clicked");
+
+        let gpu_vendors = core::gpu::detect_gpu_vendors();
+        let acceleration = if gpu_vendors.is_empty() {
+            "No GPU was detected, so no hardware decoding drivers will be added."
+        } else {
+            "Hardware video decoding drivers for the detected GPUs are included."
+        };
+
+        let config = SelectionDialogConfig::new(
+            "Multimedia Codecs",
+            &format!(
+                "FFmpeg and the GStreamer plugins will be installed. {} \
+                Select optional players and apps.",
+                acceleration
+            ),
+        )
+        .selection_type(SelectionType::Multi)
+        .selection_required(false)
+        .add_option(SelectionOption::new(
+            "mpv",
+            "mpv",
+            "Minimal, keyboard-driven video player",
+            core::is_package_installed("mpv"),
+        ))
+        .add_option(SelectionOption::new(
+            "vlc",
+            "VLC",
+            "Video player that plays almost any format",
+            core::is_package_installed("vlc"),
+        ))
+        .add_option(SelectionOption::new(
+            "obs-studio",
+            "OBS Studio",
+            "Screen recording and streaming, from the repositories",
+            core::is_package_installed("obs-studio"),
+        ))
+        .confirm_label("Install");
+
+        let window_clone = window.clone();
+        show_selection_dialog(window.upcast_ref(), config, move |selected| {
+            task_runner::run(
+                window_clone.upcast_ref(),
+                build_codecs_commands(&gpu_vendors, &selected),
+                "Multimedia Codecs",
+            );
+        });
     });
 }
 
+/// Build commands installing the codec bundle, GPU decoding drivers and chosen apps.
+fn build_codecs_commands(gpu_vendors: &[GpuVendor], apps: &[String]) -> CommandSequence {
+    let mut packages = vec![
+        "-S",
+        "--noconfirm",
+        "--needed",
+        "ffmpeg",
+        "gstreamer",
+        "gst-plugins-base",
+        "gst-plugins-good",
+        "gst-plugins-bad",
+        "gst-plugins-ugly",
+        "gst-libav",
+    ];
+    if !gpu_vendors.is_empty() {
+        packages.push("libva-utils");
+    }
+    for vendor in gpu_vendors {
+        packages.extend(vendor.video_acceleration_packages());
+    }
+
+    let mut commands = CommandSequence::new().then(
+        Command::builder()
+            .aur()
+            .args(&packages)
+            .description("Installing codecs and hardware decoding drivers...")
+            .build(),
+    );
+
+    if !apps.is_empty() {
+        let mut args = vec!["-S", "--noconfirm", "--needed"];
+        args.extend(apps.iter().map(String::as_str));
+        commands = commands.then(
+            Command::builder()
+                .aur()
+                .args(&args)
+                .description("Installing media apps...")
+                .build(),
+        );
+    }
+
+    commands.build()
+}
+
 fn setup_kdenlive(page_builder: &Builder, window: &ApplicationWindow) {
     let btn_kdenlive = extract_widget::<gtk4::Button>(page_builder, "btn_kdenlive");
+    set_command_tooltip(&btn_kdenlive, build_kdenlive_commands);
     let window = window.clone();
     btn_kdenlive.connect_clicked(move |_| {
         info!("Multimedia tools: Kdenlive button clicked");
-        let commands = CommandSequence::new()
-            .then(
-                Command::builder()
-                    .aur()
-                    .args(&["-S", "--noconfirm", "--needed", "kdenlive"])
-                    .description("Installing Kdenlive...")
-                    .build(),
-            )
-            .build();
+        let commands = build_kdenlive_commands();
 
         task_runner::run(window.upcast_ref(), commands, "Kdenlive Installation");
     });
 }
 
+/// Install Kdenlive.
+fn build_kdenlive_commands() -> CommandSequence {
+    CommandSequence::new()
+        .then(Command::install(&["kdenlive"], "Kdenlive"))
+        .build()
+}
+
 fn setup_jellyfin(page_builder: &Builder, window: &ApplicationWindow) {
     let btn_jellyfin = extract_widget::<gtk4::Button>(page_builder, "btn_jellyfin");
+    set_command_tooltip(&btn_jellyfin, build_jellyfin_commands);
     let window = window.clone();
     btn_jellyfin.connect_clicked(move |_| {
         info!("Multimedia tools: Jellyfin button clicked");
-        let commands = CommandSequence::new()
-            .then(
-                Command::builder()
-                    .aur()
-                    .args(&[
-                        "-S",
-                        "--noconfirm",
-                        "--needed",
-                        "jellyfin-server",
-                        "jellyfin-web",
-                        "jellyfin-ffmpeg",
-                    ])
-                    .description("Installing Jellyfin server and components...")
-                    .build(),
-            )
-            .then(
-                Command::builder()
-                    .privileged()
-                    .program("systemctl")
-                    .args(&["enable", "--now", "jellyfin.service"])
-                    .description("Starting Jellyfin service...")
-                    .build(),
-            )
-            .build();
+        let commands = build_jellyfin_commands();
 
         task_runner::run(window.upcast_ref(), commands, "Jellyfin Server Setup");
     });
 }
+
+/// Install the Jellyfin server and start it.
+fn build_jellyfin_commands() -> CommandSequence {
+    CommandSequence::new()
+        .then(Command::install(
+            &["jellyfin-server", "jellyfin-web", "jellyfin-ffmpeg"],
+            "Jellyfin server and components",
+        ))
+        .then(
+            Command::builder()
+                .privileged()
+                .program("systemctl")
+                .args(&["enable", "--now", "jellyfin.service"])
+                .description("Starting Jellyfin service...")
+                .build(),
+        )
+        .build()
+}
@@ -1,7 +1,7 @@
 //! Servicing and system tweaks page button handlers.
 //!
 //! Handles:
-//! - Clear Pacman cache
+//! - Clear Pacman cache, reporting the freed space
 //! - Unlock Pacman database
 //! - Plasma X11 session installation
 //! - VM guest utilities
@@ -10,17 +10,22 @@
 //! - Fix Arch keyring
 //! - Update mirrorlist
 //! - Parallel downloads adjustment
+//! - ZRAM swap and swappiness tweaks
+//! - Orphaned package cleanup
 
 use crate::core;
-use crate::ui::dialogs::error::show_error;
+use crate::core::download::format_bytes;
+use crate::core::tweaks;
+use crate::ui::dialogs::error::{show_error, show_error_with_details, show_info};
 use crate::ui::dialogs::selection::{
     show_selection_dialog, SelectionDialogConfig, SelectionOption, SelectionType,
 };
 use crate::ui::dialogs::terminal;
-use crate::ui::task_runner::{self, Command, CommandSequence};
-use crate::ui::utils::extract_widget;
+use crate::ui::dialogs::warning::show_warning_confirmation;
+use crate::ui::task_runner::{self, Command, CommandSequence, RunOptions};
+use crate::ui::utils::{extract_widget, set_command_tooltip};
 use gtk4::prelude::*;
-use gtk4::{ApplicationWindow, Builder};
+use gtk4::{glib, ApplicationWindow, Builder};
 use log::info;
 
 /// Set up all button handlers for the servicing/system tweaks page
@@ -34,6 +39,9 @@ pub fn setup_handlers(page_builder: &Builder, _main_builder: &Builder, window: &
     setup_fix_arch_keyring(page_builder, window);
     setup_update_mirrorlist(page_builder, window);
     setup_parallel_downloads(page_builder, window);
+    setup_zram(page_builder, window);
+    setup_swappiness(page_builder, window);
+    setup_remove_orphans(page_builder, window);
 }
 
 fn setup_clr_pacman(page_builder: &Builder, window: &ApplicationWindow) {
@@ -41,53 +49,181 @@ fn setup_clr_pacman(page_builder: &Builder, window: &ApplicationWindow) {
     let window = window.clone();
     btn_clr_pacman.connect_clicked(move |_| {
         info!("Servicing: Clear Pacman Cache button clicked");
-        // Use terminal dialog for interactive pacman cache clearing
-        terminal::show_terminal_dialog(
-            window.upcast_ref(),
-            "Clear Pacman Cache",
-            "pkexec",
-            &["pacman", "-Scc"],
-        );
+        let window = window.clone();
+
+        // Measure the cache off the main loop, it can be large
+        glib::spawn_future_local(async move {
+            let size_before = core::package::pacman_cache_size_async().await;
+            let paccache_installed = core::is_package_installed("pacman-contrib");
+            let paccache_note = if paccache_installed {
+                ""
+            } else {
+                " Installs pacman-contrib first."
+            };
+
+            let config = SelectionDialogConfig::new(
+                "Clear Pacman Cache",
+                &format!(
+                    "The package cache uses {}. Choose how much of it to remove.",
+                    format_bytes(size_before)
+                ),
+            )
+            .selection_type(SelectionType::Single)
+            .selection_required(true)
+            .add_option(SelectionOption::new(
+                "keep3",
+                "Keep last 3 versions",
+                &format!(
+                    "Remove older versions of each package with paccache.{}",
+                    paccache_note
+                ),
+                false,
+            ))
+            .add_option(SelectionOption::new(
+                "keep1",
+                "Keep last version",
+                &format!(
+                    "Keep only the newest cached version of each package.{}",
+                    paccache_note
+                ),
+                false,
+            ))
+            .add_option(SelectionOption::new(
+                "all",
+                "Remove everything",
+                "Clear the whole cache with pacman -Scc. Downgrading will need a download.",
+                false,
+            ))
+            .confirm_label("Clean");
+
+            let window_clone = window.clone();
+            show_selection_dialog(window.upcast_ref(), config, move |selected| {
+                let Some(choice) = selected.first() else {
+                    return;
+                };
+
+                let window_for_report = window_clone.clone();
+                let options = RunOptions::new().on_complete(move |report| {
+                    if report.success {
+                        let window = window_for_report.clone();
+                        glib::spawn_future_local(async move {
+                            let size_after = core::package::pacman_cache_size_async().await;
+                            show_info(
+                                &window,
+                                "Pacman Cache Cleaned",
+                                &format!(
+                                    "Freed {}, the cache now uses {}.",
+                                    format_bytes(size_before.saturating_sub(size_after)),
+                                    format_bytes(size_after)
+                                ),
+                            );
+                        });
+                    }
+                });
+
+                task_runner::run_with_options(
+                    window_clone.upcast_ref(),
+                    build_cache_cleanup_commands(choice, paccache_installed),
+                    "Clear Pacman Cache",
+                    options,
+                );
+            });
+        });
     });
 }
 
+/// Build commands for the chosen cache cleanup.
+///
+/// `keep3` and `keep1` use paccache, installing pacman-contrib if needed;
+/// `all` clears the whole cache.
+fn build_cache_cleanup_commands(choice: &str, paccache_installed: bool) -> CommandSequence {
+    let mut commands = CommandSequence::new();
+
+    let keep = match choice {
+        "keep3" => "3",
+        "keep1" => "1",
+        _ => {
+            return commands
+                .then(
+                    Command::builder()
+                        .privileged()
+                        .program("sh")
+                        .args(&["-c", "yes | pacman -Scc"])
+                        .description("Removing all cached packages...")
+                        .build(),
+                )
+                .build();
+        }
+    };
+
+    if !paccache_installed {
+        commands = commands.then(Command::install_native(
+            &["pacman-contrib"],
+            "pacman-contrib for paccache",
+        ));
+    }
+
+    commands
+        .then(
+            Command::builder()
+                .privileged()
+                .program("paccache")
+                .args(&["-r", "-k", keep])
+                .description("Removing old cached package versions...")
+                .build(),
+        )
+        .build()
+}
+
 fn setup_unlock_pacman(page_builder: &Builder, window: &ApplicationWindow) {
     let btn_unlock_pacman = extract_widget::<gtk4::Button>(page_builder, "btn_unlock_pacman");
+    set_command_tooltip(&btn_unlock_pacman, build_unlock_pacman_commands);
     let window = window.clone();
     btn_unlock_pacman.connect_clicked(move |_| {
         info!("Servicing: Unlock Pacman DB button clicked");
-        let commands = CommandSequence::new()
-            .then(
-                Command::builder()
-                    .privileged()
-                    .program("rm")
-                    .args(&["-f", "/var/lib/pacman/db.lck"])
-                    .description("Removing Pacman lock file...")
-                    .build(),
-            )
-            .build();
+        let commands = build_unlock_pacman_commands();
         task_runner::run(window.upcast_ref(), commands, "Unlock Pacman Database");
     });
 }
 
+/// Remove the pacman database lock.
+fn build_unlock_pacman_commands() -> CommandSequence {
+    CommandSequence::new()
+        .then(
+            Command::builder()
+                .privileged()
+                .program("rm")
+                .args(&["-f", "/var/lib/pacman/db.lck"])
+                .description("Removing Pacman lock file...")
+                .build(),
+        )
+        .build()
+}
+
 fn setup_plasma_x11(page_builder: &Builder, window: &ApplicationWindow) {
     let btn_plasma_x11 = extract_widget::<gtk4::Button>(page_builder, "btn_plasma_x11");
+    set_command_tooltip(&btn_plasma_x11, build_plasma_x11_commands);
     let window = window.clone();
     btn_plasma_x11.connect_clicked(move |_| {
         info!("Servicing: Plasma X11 Session button clicked");
-        let commands = CommandSequence::new()
-            .then(
-                Command::builder()
-                    .aur()
-                    .args(&["-S", "--noconfirm", "kwin-x11", "plasma-x11-session"])
-                    .description("Installing KDE Plasma X11 session components...")
-                    .build(),
-            )
-            .build();
+        let commands = build_plasma_x11_commands();
         task_runner::run(window.upcast_ref(), commands, "Install KDE X11 Session");
     });
 }
 
+/// Install the Plasma X11 session.
+fn build_plasma_x11_commands() -> CommandSequence {
+    CommandSequence::new()
+        .then(
+            Command::builder()
+                .aur()
+                .args(&["-S", "--noconfirm", "kwin-x11", "plasma-x11-session"])
+                .description("Installing KDE Plasma X11 session components...")
+                .build(),
+        )
+        .build()
+}
+
 fn setup_vm_guest_utils(page_builder: &Builder, window: &ApplicationWindow) {
     let btn_vm_guest_utils = extract_widget::<gtk4::Button>(page_builder, "btn_vm_guest_utils");
     let window = window.clone();
@@ -100,28 +236,16 @@ fn setup_vm_guest_utils(page_builder: &Builder, window: &ApplicationWindow) {
                 let virt = String::from_utf8_lossy(&result.stdout).trim().to_string();
                 match virt.as_str() {
                     "oracle" => {
-                        commands = commands.then(
-                            Command::builder()
-                                .aur()
-                                .args(&["-S", "--needed", "--noconfirm", "virtualbox-guest-utils"])
-                                .description("Installing VirtualBox guest utilities...")
-                                .build(),
-                        )
+                        commands = commands.then(Command::install(
+                            &["virtualbox-guest-utils"],
+                            "VirtualBox guest utilities",
+                        ))
                     }
                     "kvm" => {
-                        commands = commands.then(
-                            Command::builder()
-                                .aur()
-                                .args(&[
-                                    "-S",
-                                    "--needed",
-                                    "--noconfirm",
-                                    "qemu-guest-agent",
-                                    "spice-vdagent",
-                                ])
-                                .description("Installing KVM/QEMU guest agents...")
-                                .build(),
-                        )
+                        commands = commands.then(Command::install(
+                            &["qemu-guest-agent", "spice-vdagent"],
+                            "KVM/QEMU guest agents",
+                        ))
                     }
                     _ => {
                         show_error(&window, "Unsupported or no virtualization detected.");
@@ -129,8 +253,25 @@ fn setup_vm_guest_utils(page_builder: &Builder, window: &ApplicationWindow) {
                     }
                 }
             }
-            _ => {
-                show_error(&window, "Failed to detect virtualization environment.");
+            Ok(result) => {
+                show_error_with_details(
+                    &window,
+                    "Failed to detect virtualization environment.",
+                    &format!(
+                        "systemd-detect-virt exited with {}\n{}{}",
+                        result.status,
+                        String::from_utf8_lossy(&result.stdout),
+                        String::from_utf8_lossy(&result.stderr)
+                    ),
+                );
+                return;
+            }
+            Err(e) => {
+                show_error_with_details(
+                    &window,
+                    "Failed to detect virtualization environment.",
+                    &format!("Could not run systemd-detect-virt: {}", e),
+                );
                 return;
             }
         }
@@ -172,45 +313,51 @@ fn setup_fix_gpgme(page_builder: &Builder, window: &ApplicationWindow) {
 
 fn setup_fix_arch_keyring(page_builder: &Builder, window: &ApplicationWindow) {
     let btn_fix_arch_keyring = extract_widget::<gtk4::Button>(page_builder, "btn_fix_arch_keyring");
+    set_command_tooltip(&btn_fix_arch_keyring, build_fix_arch_keyring_commands);
     let window = window.clone();
     btn_fix_arch_keyring.connect_clicked(move |_| {
         info!("Servicing: Fix Arch Keyring button clicked");
-        let commands = CommandSequence::new()
-            .then(Command::builder()
-                .privileged()
-                .program("rm")
-                .args(&["-rf", "/etc/pacman.d/gnupg"])
-                .description("Removing existing GnuPG keyring...")
-                .build())
-            .then(Command::builder()
-                .privileged()
-                .program("pacman-key")
-                .args(&["--init"])
-                .description("Initializing new keyring...")
-                .build())
-            .then(Command::builder()
-                .privileged()
-                .program("pacman-key")
-                .args(&["--populate"])
-                .description("Populating keyring...")
-                .build())
-            .then(Command::builder()
-                .privileged()
-                .program("sh")
-                .args(&["-c", "echo 'keyserver hkp://keyserver.ubuntu.com:80' >> /etc/pacman.d/gnupg/gpg.conf"])
-                .description("Setting keyserver...")
-                .build())
-            .then(Command::builder()
-                .privileged()
-                .program("pacman")
-                .args(&["-Syy", "--noconfirm", "archlinux-keyring"])
-                .description("Reinstalling Arch Linux keyring...")
-                .build())
-            .build();
+        let commands = build_fix_arch_keyring_commands();
         task_runner::run(window.upcast_ref(), commands, "Fix GnuPG Keyring");
     });
 }
 
+/// Recreate the pacman keyring and reinstall the Arch keyring.
+fn build_fix_arch_keyring_commands() -> CommandSequence {
+    CommandSequence::new()
+        .then(Command::builder()
+            .privileged()
+            .program("rm")
+            .args(&["-rf", "/etc/pacman.d/gnupg"])
+            .description("Removing existing GnuPG keyring...")
+            .build())
+        .then(Command::builder()
+            .privileged()
+            .program("pacman-key")
+            .args(&["--init"])
+            .description("Initializing new keyring...")
+            .build())
+        .then(Command::builder()
+            .privileged()
+            .program("pacman-key")
+            .args(&["--populate"])
+            .description("Populating keyring...")
+            .build())
+        .then(Command::builder()
+            .privileged()
+            .program("sh")
+            .args(&["-c", "echo 'keyserver hkp://keyserver.ubuntu.com:80' >> /etc/pacman.d/gnupg/gpg.conf"])
+            .description("Setting keyserver...")
+            .build())
+        .then(Command::builder()
+            .privileged()
+            .program("pacman")
+            .args(&["-Syy", "--noconfirm", "archlinux-keyring"])
+            .description("Reinstalling Arch Linux keyring...")
+            .build())
+        .build()
+}
+
 fn setup_update_mirrorlist(page_builder: &Builder, window: &ApplicationWindow) {
     let btn_update_mirrorlist =
         extract_widget::<gtk4::Button>(page_builder, "btn_update_mirrorlist");
@@ -243,6 +390,7 @@ fn setup_update_mirrorlist(page_builder: &Builder, window: &ApplicationWindow) {
                              .aur()
                              .args(&["-S", "--needed", "--noconfirm", "rate-mirrors"])
                              .description("Installing rate-mirrors utility...")
+                             .retries(2)
                              .build());
                     }
 
@@ -284,3 +432,264 @@ fn setup_parallel_downloads(page_builder: &Builder, window: &ApplicationWindow)
         );
     });
 }
+
+fn setup_zram(page_builder: &Builder, window: &ApplicationWindow) {
+    let btn_zram = extract_widget::<gtk4::Button>(page_builder, "btn_zram");
+    let window = window.clone();
+    btn_zram.connect_clicked(move |_| {
+        info!("Servicing: ZRAM Swap button clicked");
+
+        if tweaks::is_zram_enabled() {
+            let window_clone = window.clone();
+            show_warning_confirmation(
+                window.upcast_ref(),
+                "ZRAM Swap",
+                "ZRAM swap is <b>already enabled</b>.\nDo you want to <span foreground=\"red\" weight=\"bold\">remove</span> it?",
+                move || {
+                    task_runner::run(
+                        window_clone.upcast_ref(),
+                        build_zram_removal_commands(),
+                        "Remove ZRAM Swap",
+                    );
+                },
+            );
+            return;
+        }
+
+        task_runner::run(window.upcast_ref(), build_zram_commands(), "Enable ZRAM Swap");
+    });
+}
+
+/// Install zram-generator, write the default config and start the swap device.
+fn build_zram_commands() -> CommandSequence {
+    CommandSequence::new()
+        .then(Command::install(&[tweaks::ZRAM_PACKAGE], "zram-generator"))
+        .then(
+            Command::builder()
+                .privileged()
+                .program("sh")
+                .args(&[
+                    "-c",
+                    &format!(
+                        "printf '%s' '{}' > {}",
+                        tweaks::DEFAULT_ZRAM_CONFIG,
+                        tweaks::ZRAM_CONFIG
+                    ),
+                ])
+                .description("Writing ZRAM configuration...")
+                .build(),
+        )
+        .then(
+            Command::builder()
+                .privileged()
+                .program("systemctl")
+                .args(&["daemon-reload"])
+                .description("Reloading systemd units...")
+                .build(),
+        )
+        .then(
+            Command::builder()
+                .privileged()
+                .program("systemctl")
+                .args(&["start", tweaks::ZRAM_SERVICE])
+                .description("Starting ZRAM swap...")
+                .build(),
+        )
+        .build()
+}
+
+/// Stop the ZRAM swap device and remove its config and package.
+fn build_zram_removal_commands() -> CommandSequence {
+    CommandSequence::new()
+        .then(
+            Command::builder()
+                .privileged()
+                .program("systemctl")
+                .args(&["stop", tweaks::ZRAM_SERVICE])
+                .description("Stopping ZRAM swap...")
+                .allow_failure()
+                .build(),
+        )
+        .then(
+            Command::builder()
+                .privileged()
+                .program("rm")
+                .args(&["-f", tweaks::ZRAM_CONFIG])
+                .description("Removing ZRAM configuration...")
+                .build(),
+        )
+        .then(
+            Command::builder()
+                .privileged()
+                .program("systemctl")
+                .args(&["daemon-reload"])
+                .description("Reloading systemd units...")
+                .build(),
+        )
+        .then(Command::aur_remove(
+            &[tweaks::ZRAM_PACKAGE],
+            "zram-generator",
+        ))
+        .build()
+}
+
+/// Swappiness presets offered in the dialog, with label and description.
+const SWAPPINESS_PRESETS: &[(u32, &str, &str)] = &[
+    (
+        10,
+        "10 - Prefer RAM",
+        "Swap only under memory pressure, for desktops with plenty of RAM",
+    ),
+    (
+        tweaks::DEFAULT_SWAPPINESS,
+        "60 - Kernel default",
+        "Remove the toolkit's setting and restore the default",
+    ),
+    (
+        180,
+        "180 - ZRAM",
+        "Swap early to compressed RAM, recommended with ZRAM swap",
+    ),
+];
+
+fn setup_swappiness(page_builder: &Builder, window: &ApplicationWindow) {
+    let btn_swappiness = extract_widget::<gtk4::Button>(page_builder, "btn_swappiness");
+    let window = window.clone();
+    btn_swappiness.connect_clicked(move |_| {
+        info!("Servicing: Swappiness button clicked");
+
+        let current = tweaks::current_swappiness();
+        let configured = tweaks::configured_swappiness();
+        let state = match (current, configured) {
+            (Some(current), Some(_)) => {
+                format!("Current swappiness is {}, set by the toolkit.", current)
+            }
+            (Some(current), None) => format!("Current swappiness is {}.", current),
+            (None, _) => "Current swappiness could not be read.".to_string(),
+        };
+
+        let config = SWAPPINESS_PRESETS.iter().fold(
+            SelectionDialogConfig::new(
+                "Swappiness",
+                &format!(
+                    "{} The value already in effect is marked. Lower values keep more in RAM, \
+                    higher values swap earlier.",
+                    state
+                ),
+            )
+            .selection_type(SelectionType::Single)
+            .selection_required(true)
+            .confirm_label("Apply"),
+            |config, (value, label, description)| {
+                config.add_option(SelectionOption::new(
+                    &value.to_string(),
+                    label,
+                    description,
+                    current == Some(*value),
+                ))
+            },
+        );
+
+        let window_clone = window.clone();
+        show_selection_dialog(window.upcast_ref(), config, move |selected| {
+            let Some(value) = selected.first().and_then(|value| value.parse().ok()) else {
+                return;
+            };
+            task_runner::run(
+                window_clone.upcast_ref(),
+                build_swappiness_commands(value),
+                "Set Swappiness",
+            );
+        });
+    });
+}
+
+/// Persist `value` in the sysctl drop-in and apply it, or reset to the default.
+fn build_swappiness_commands(value: u32) -> CommandSequence {
+    let persist = if value == tweaks::DEFAULT_SWAPPINESS {
+        Command::builder()
+            .privileged()
+            .program("rm")
+            .args(&["-f", tweaks::SWAPPINESS_DROPIN])
+            .description("Removing swappiness setting...")
+            .build()
+    } else {
+        Command::builder()
+            .privileged()
+            .program("sh")
+            .args(&[
+                "-c",
+                &format!(
+                    "echo 'vm.swappiness = {}' > {}",
+                    value,
+                    tweaks::SWAPPINESS_DROPIN
+                ),
+            ])
+            .description("Saving swappiness setting...")
+            .build()
+    };
+
+    CommandSequence::new()
+        .then(persist)
+        .then(
+            Command::builder()
+                .privileged()
+                .program("sysctl")
+                .args(&["-w", &format!("vm.swappiness={}", value)])
+                .description(&format!("Applying swappiness {}...", value))
+                .build(),
+        )
+        .build()
+}
+
+fn setup_remove_orphans(page_builder: &Builder, window: &ApplicationWindow) {
+    let btn_remove_orphans = extract_widget::<gtk4::Button>(page_builder, "btn_remove_orphans");
+    let window = window.clone();
+    btn_remove_orphans.connect_clicked(move |_| {
+        info!("Servicing: Remove Orphans button clicked");
+        let window = window.clone();
+
+        glib::spawn_future_local(async move {
+            let Some(orphans) = core::package::list_orphans_async().await else {
+                show_error(&window, "Failed to list orphaned packages.");
+                return;
+            };
+
+            if orphans.is_empty() {
+                show_info(
+                    &window,
+                    "No Orphans",
+                    "There are no orphaned packages to remove.",
+                );
+                return;
+            }
+
+            let config = orphans.iter().fold(
+                SelectionDialogConfig::new(
+                    "Remove Orphans",
+                    "These packages were installed as dependencies and are no longer needed. \
+                    Deselect any you want to keep.",
+                )
+                .selection_type(SelectionType::Multi)
+                .selection_required(true)
+                .searchable(true)
+                .confirm_label("Remove"),
+                |config, orphan| {
+                    config.add_option(
+                        SelectionOption::new(orphan, orphan, "Orphaned dependency", false)
+                            .selected(),
+                    )
+                },
+            );
+
+            let window_clone = window.clone();
+            show_selection_dialog(window.upcast_ref(), config, move |selected| {
+                let packages: Vec<&str> = selected.iter().map(String::as_str).collect();
+                let commands = CommandSequence::new()
+                    .then(Command::aur_remove(&packages, "orphaned packages"))
+                    .build();
+                task_runner::run(window_clone.upcast_ref(), commands, "Remove Orphans");
+            });
+        });
+    });
+}
@@ -1,36 +1,86 @@
 //! Common utilities for seasonal effects.
 
 use adw::prelude::*;
+use gtk4::glib;
 use gtk4::{ApplicationWindow, DrawingArea, EventControllerMotion, Widget};
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+/// Redraw rate of the effects unless changed with [`set_target_fps`].
+const DEFAULT_FPS: u32 = 30;
+
+/// Highest redraw rate that can be set.
+const MAX_FPS: u32 = 120;
+
+static TARGET_FPS: AtomicU32 = AtomicU32::new(DEFAULT_FPS);
 
 /// Mouse position context for seasonal effects.
 /// Provides mouse coordinates that effects can use.
+#[derive(Clone, Default)]
 pub struct MouseContext {
     position: Rc<RefCell<(f64, f64)>>,
+    inside: Rc<Cell<bool>>,
 }
 
 impl MouseContext {
-    /// Get a clone of the internal Rc<RefCell<(f64, f64)>> for sharing.
-    pub fn position_internal(&self) -> Rc<RefCell<(f64, f64)>> {
-        self.position.clone()
+    /// Current mouse position, or `None` while the pointer is outside the window.
+    pub fn position(&self) -> Option<(f64, f64)> {
+        self.inside.get().then(|| *self.position.borrow())
     }
 }
 
 /// Set up mouse tracking for the window and return a MouseContext.
 pub fn setup_mouse_tracking(window: &ApplicationWindow) -> MouseContext {
-    let mouse_pos = Rc::new(RefCell::new((0.0f64, 0.0f64)));
+    let context = MouseContext::default();
 
     let motion = EventControllerMotion::new();
-    let mouse_pos_clone = mouse_pos.clone();
+    let ctx = context.clone();
+    motion.connect_enter(move |_, x, y| {
+        *ctx.position.borrow_mut() = (x, y);
+        ctx.inside.set(true);
+    });
+    let ctx = context.clone();
     motion.connect_motion(move |_, x, y| {
-        *mouse_pos_clone.borrow_mut() = (x, y);
+        *ctx.position.borrow_mut() = (x, y);
+        ctx.inside.set(true);
+    });
+    let ctx = context.clone();
+    motion.connect_leave(move |_| {
+        ctx.inside.set(false);
     });
     window.add_controller(motion);
 
-    MouseContext {
-        position: mouse_pos,
+    context
+}
+
+/// Pushes particles away from the mouse pointer.
+#[derive(Clone, Copy, Debug)]
+pub struct RepelField {
+    /// Distance from the pointer within which particles are pushed, in pixels
+    pub radius: f64,
+    /// Strength of the push; the nudge grows with the inverse distance
+    pub strength: f64,
+}
+
+impl RepelField {
+    /// Velocity change per second for a particle at `(x, y)`.
+    ///
+    /// Returns `None` outside the radius or while the mouse is outside the window.
+    pub fn force(&self, x: f64, y: f64, mouse: Option<(f64, f64)>) -> Option<(f64, f64)> {
+        let (mx, my) = mouse?;
+        let (dx, dy) = (x - mx, y - my);
+        let dist = (dx * dx + dy * dy).sqrt();
+        if dist >= self.radius {
+            return None;
+        }
+
+        // Avoid blowing up for particles right under the pointer
+        let dist = dist.max(1.0);
+        // Zero at the edge of the radius, growing as 1/dist towards the pointer
+        let magnitude = self.strength * (1.0 / dist - 1.0 / self.radius);
+        Some((dx / dist * magnitude, dy / dist * magnitude))
     }
 }
 
@@ -60,52 +110,164 @@ where
     });
 }
 
-/// Helper function to add a drawing area as an overlay to the window.
-pub fn add_overlay_to_window(window: &ApplicationWindow, drawing_area: &DrawingArea) -> bool {
-    use log::info;
+/// Set the redraw rate of all effects, clamped to 1-120 FPS.
+///
+/// Takes effect from the next frame on.
+pub fn set_target_fps(fps: u32) {
+    TARGET_FPS.store(fps.clamp(1, MAX_FPS), Ordering::Relaxed);
+}
+
+/// Time between two frames at the target FPS.
+fn frame_interval() -> Duration {
+    Duration::from_secs(1) / TARGET_FPS.load(Ordering::Relaxed)
+}
+
+/// Redraw the drawing area at the target FPS while the window is active.
+///
+/// Redraws stop while the window is unfocused or minimized, and resume when
+/// it becomes active again.
+pub fn start_redraw_loop(window: &ApplicationWindow, drawing_area: &Rc<DrawingArea>) {
+    let running = Rc::new(Cell::new(true));
+    schedule_frame(window.clone(), drawing_area.clone(), running.clone());
+
+    let drawing_area = drawing_area.clone();
+    window.connect_is_active_notify(move |window| {
+        if window.is_active() && !running.get() {
+            running.set(true);
+            schedule_frame(window.clone(), drawing_area.clone(), running.clone());
+        }
+    });
+}
+
+/// Queue a redraw after one frame interval, then schedule the next frame.
+fn schedule_frame(
+    window: ApplicationWindow,
+    drawing_area: Rc<DrawingArea>,
+    running: Rc<Cell<bool>>,
+) {
+    glib::timeout_add_local_once(frame_interval(), move || {
+        if !window.is_active() {
+            // Paused until the window is active again
+            running.set(false);
+            return;
+        }
 
-    let adw_window = match window.downcast_ref::<adw::ApplicationWindow>() {
-        Some(w) => w,
-        None => {
-            info!("Window is not an AdwApplicationWindow, cannot add overlay");
-            return false;
+        if drawing_area.is_visible() {
+            drawing_area.queue_draw();
         }
-    };
+        schedule_frame(window, drawing_area, running);
+    });
+}
+
+/// Reason a drawing area could not be added as a window overlay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverlayError {
+    /// The window is not an `AdwApplicationWindow`
+    NotAdwWindow,
+    /// The window has no content to draw over
+    NoContent,
+}
 
-    let content_widget = match adw_window.content() {
-        Some(w) => w,
-        None => {
-            info!("Window has no content");
-            return false;
+impl std::fmt::Display for OverlayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotAdwWindow => write!(f, "window is not an AdwApplicationWindow"),
+            Self::NoContent => write!(f, "window has no content"),
         }
-    };
+    }
+}
+
+impl std::error::Error for OverlayError {}
+
+/// Add a drawing area as an overlay covering the whole window.
+///
+/// The window content is wrapped in a `gtk4::Overlay`, or the drawing area is
+/// added to the existing one. Fails with [`OverlayError::NotAdwWindow`] for
+/// plain GTK windows, which can use [`add_overlay_to_child`] instead.
+pub fn add_overlay_to_window(
+    window: &ApplicationWindow,
+    drawing_area: &DrawingArea,
+) -> Result<(), OverlayError> {
+    use log::info;
+
+    let adw_window = window
+        .downcast_ref::<adw::ApplicationWindow>()
+        .ok_or(OverlayError::NotAdwWindow)?;
+    let content_widget = adw_window.content().ok_or(OverlayError::NoContent)?;
 
     // Verify it's a ToolbarView
     if content_widget.downcast_ref::<adw::ToolbarView>().is_none() {
         info!("Window content is not a ToolbarView, overlay may not work correctly");
     }
 
+    wrap_in_overlay(&content_widget, drawing_area, |widget| {
+        adw_window.set_content(widget)
+    });
+    Ok(())
+}
+
+/// Add a drawing area as an overlay on the child of a plain GTK window.
+///
+/// Fallback for windows that are not an `AdwApplicationWindow`.
+pub fn add_overlay_to_child(
+    window: &ApplicationWindow,
+    drawing_area: &DrawingArea,
+) -> Result<(), OverlayError> {
+    let child = window.child().ok_or(OverlayError::NoContent)?;
+    wrap_in_overlay(&child, drawing_area, |widget| window.set_child(widget));
+    Ok(())
+}
+
+/// Add a drawing area over `content`, wrapping it in an overlay if needed.
+///
+/// `set_content` replaces the content in its parent.
+fn wrap_in_overlay(
+    content: &Widget,
+    drawing_area: &DrawingArea,
+    set_content: impl Fn(Option<&Widget>),
+) {
+    use log::info;
+
     // Check if the content is already wrapped in an overlay
-    if let Some(existing_overlay) = content_widget.downcast_ref::<gtk4::Overlay>() {
+    if let Some(existing_overlay) = content.downcast_ref::<gtk4::Overlay>() {
         info!("Found existing overlay at window level, adding drawing area");
         existing_overlay.add_overlay(drawing_area);
-        true
     } else {
         info!("Wrapping window content in overlay to cover entire window including navbar");
         // Create overlay that will wrap the entire content
         let overlay = gtk4::Overlay::new();
 
         // Remove the content from the window
-        adw_window.set_content(Option::<&Widget>::None);
+        set_content(None);
 
         // Add content as the main child of the overlay
-        overlay.set_child(Some(&content_widget));
+        overlay.set_child(Some(content));
 
         // Add the drawing area as an overlay
         overlay.add_overlay(drawing_area);
 
         // Set the overlay as the window content
-        adw_window.set_content(Some(&overlay));
-        true
+        set_content(Some(overlay.upcast_ref()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repel_field_force() {
+        let field = RepelField {
+            radius: 100.0,
+            strength: 1000.0,
+        };
+
+        assert!(field.force(10.0, 0.0, None).is_none());
+        assert!(field.force(150.0, 0.0, Some((0.0, 0.0))).is_none());
+
+        let (near_x, near_y) = field.force(10.0, 0.0, Some((0.0, 0.0))).unwrap();
+        let (far_x, _) = field.force(50.0, 0.0, Some((0.0, 0.0))).unwrap();
+        assert!(near_x > far_x && far_x > 0.0);
+        assert_eq!(near_y, 0.0);
     }
 }
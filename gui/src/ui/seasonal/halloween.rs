@@ -8,7 +8,8 @@
 
 use crate::config::seasonal_debug;
 use crate::ui::seasonal::common::{
-    add_overlay_to_window, setup_resize_handler, MouseContext, ResizableEffectState,
+    add_overlay_to_child, add_overlay_to_window, setup_resize_handler, start_redraw_loop,
+    MouseContext, OverlayError, RepelField, ResizableEffectState,
 };
 use crate::ui::seasonal::SeasonalEffect;
 use gtk4::cairo;
@@ -23,11 +24,23 @@ use std::rc::Rc;
 
 const BAT_COUNT: usize = 15;
 const BASE_SPEED: f64 = 100.0;
-const MOUSE_AVOID_RADIUS: f64 = 250.0;
-const MOUSE_AVOID_FORCE: f64 = 800.0;
 
 /// Halloween bat effect.
-pub struct HalloweenEffect;
+pub struct HalloweenEffect {
+    /// How bats avoid the mouse pointer
+    pub repel: RepelField,
+}
+
+impl Default for HalloweenEffect {
+    fn default() -> Self {
+        Self {
+            repel: RepelField {
+                radius: 250.0,
+                strength: 40_000.0,
+            },
+        }
+    }
+}
 
 impl SeasonalEffect for HalloweenEffect {
     fn is_active(&self) -> bool {
@@ -64,21 +77,13 @@ impl SeasonalEffect for HalloweenEffect {
         drawing_area.set_valign(gtk4::Align::Fill);
         drawing_area.set_visible(crate::ui::seasonal::are_effects_enabled());
 
-        let mouse_pos = if let Some(ctx) = mouse_context {
-            ctx.position_internal()
-        } else {
-            Rc::new(RefCell::new((0.0f64, 0.0f64)))
-        };
+        let mouse = mouse_context.cloned().unwrap_or_default();
+        let repel = self.repel;
 
         let state = Rc::new(RefCell::new(None::<BatState>));
         let setup_state = Rc::clone(&state);
-        let draw_mouse_pos = mouse_pos.clone();
 
-        let drawing_area_clone = drawing_area.clone();
-        glib::timeout_add_local(std::time::Duration::from_millis(16), move || {
-            drawing_area_clone.queue_draw();
-            glib::ControlFlow::Continue
-        });
+        start_redraw_loop(window, &drawing_area);
 
         drawing_area.set_draw_func(move |_da, cr, width, height| {
             let mut state_ref = setup_state.borrow_mut();
@@ -89,9 +94,7 @@ impl SeasonalEffect for HalloweenEffect {
 
             if let Some(bat_state) = state_ref.as_mut() {
                 let now = std::time::Instant::now();
-                let (mx, my) = *draw_mouse_pos.borrow();
-
-                bat_state.update(width as f64, height as f64, now, mx, my);
+                bat_state.update(width as f64, height as f64, now, mouse.position(), &repel);
 
                 let _ = cr.save();
                 cr.set_operator(cairo::Operator::Clear);
@@ -107,12 +110,19 @@ impl SeasonalEffect for HalloweenEffect {
         // Set up resize handler
         setup_resize_handler(&drawing_area, state);
 
-        if add_overlay_to_window(window, &drawing_area) {
-            info!("Halloween effect overlay added successfully");
-            Some(drawing_area)
-        } else {
-            info!("Failed to add Halloween effect overlay");
-            None
+        let result = match add_overlay_to_window(window, &drawing_area) {
+            Err(OverlayError::NotAdwWindow) => add_overlay_to_child(window, &drawing_area),
+            result => result,
+        };
+        match result {
+            Ok(()) => {
+                info!("Halloween effect overlay added successfully");
+                Some(drawing_area)
+            }
+            Err(e) => {
+                info!("Failed to add Halloween effect overlay: {}", e);
+                None
+            }
         }
     }
 }
@@ -149,7 +159,15 @@ impl Bat {
         }
     }
 
-    fn update(&mut self, width: f64, height: f64, dt: f64, rng: &mut StdRng, mx: f64, my: f64) {
+    fn update(
+        &mut self,
+        width: f64,
+        height: f64,
+        dt: f64,
+        rng: &mut StdRng,
+        mouse: Option<(f64, f64)>,
+        repel: &RepelField,
+    ) {
         self.flap_phase += self.flap_speed * dt;
 
         if rng.random::<f64>() > 0.92 {
@@ -160,18 +178,9 @@ impl Bat {
             self.velocity_y = angle.sin() * current_speed;
         }
 
-        let dx = self.x - mx;
-        let dy = self.y - my;
-        let dist_sq = dx * dx + dy * dy;
-
-        if dist_sq < (MOUSE_AVOID_RADIUS * MOUSE_AVOID_RADIUS) {
-            let dist = dist_sq.sqrt();
-            let repulsion_strength = (MOUSE_AVOID_RADIUS - dist) / MOUSE_AVOID_RADIUS;
-            let norm_x = dx / dist;
-            let norm_y = dy / dist;
-
-            self.velocity_x += norm_x * repulsion_strength * MOUSE_AVOID_FORCE * dt;
-            self.velocity_y += norm_y * repulsion_strength * MOUSE_AVOID_FORCE * dt;
+        if let Some((force_x, force_y)) = repel.force(self.x, self.y, mouse) {
+            self.velocity_x += force_x * dt;
+            self.velocity_y += force_y * dt;
         }
 
         let max_speed = BASE_SPEED * 3.0;
@@ -311,7 +320,14 @@ impl BatState {
         }
     }
 
-    fn update(&mut self, width: f64, height: f64, now: std::time::Instant, mx: f64, my: f64) {
+    fn update(
+        &mut self,
+        width: f64,
+        height: f64,
+        now: std::time::Instant,
+        mouse: Option<(f64, f64)>,
+        repel: &RepelField,
+    ) {
         // Sync dimensions
         self.current_width = width;
         self.current_height = height;
@@ -324,7 +340,7 @@ impl BatState {
         self.last_frame_time = now;
 
         for bat in &mut self.bats {
-            bat.update(width, height, dt, &mut self.rng, mx, my);
+            bat.update(width, height, dt, &mut self.rng, mouse, repel);
         }
     }
 
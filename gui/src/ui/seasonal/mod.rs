@@ -7,6 +7,7 @@ mod common;
 mod halloween;
 mod snow;
 
+use crate::config;
 use crate::ui::seasonal::common::MouseContext;
 use gtk4::prelude::*;
 use gtk4::{ApplicationWindow, DrawingArea};
@@ -15,6 +16,8 @@ use std::cell::RefCell;
 use std::rc::Rc;
 use std::sync::atomic::{AtomicBool, Ordering};
 
+pub use crate::config::user::SeasonalMode;
+pub use common::set_target_fps;
 pub use halloween::HalloweenEffect;
 pub use snow::SnowEffect;
 
@@ -53,10 +56,23 @@ pub fn set_effects_enabled(enabled: bool) {
     }
 }
 
+/// Decide whether seasonal effects should be shown at all.
+///
+/// Follows the `seasonal_effects` option of the user config. `Auto` becomes
+/// `Off` when no effect is in season, so callers only need to check for `Off`.
+pub fn should_enable_seasonal() -> SeasonalMode {
+    match config::user::get().seasonal_effects {
+        SeasonalMode::Auto if !has_active_effect() => SeasonalMode::Off,
+        mode => mode,
+    }
+}
+
 /// Check if any seasonal effect is currently active.
 pub fn has_active_effect() -> bool {
-    let effects: Vec<Box<dyn SeasonalEffect>> =
-        vec![Box::new(SnowEffect), Box::new(HalloweenEffect)];
+    let effects: Vec<Box<dyn SeasonalEffect>> = vec![
+        Box::new(SnowEffect::default()),
+        Box::new(HalloweenEffect::default()),
+    ];
 
     effects.iter().any(|e| e.is_active())
 }
@@ -86,7 +102,15 @@ pub trait SeasonalEffect {
 }
 
 /// Apply any active seasonal effects to the window.
+///
+/// Nothing is created when effects are off, not even the mouse tracking.
 pub fn apply_seasonal_effects(window: &ApplicationWindow) {
+    let mode = should_enable_seasonal();
+    if mode == SeasonalMode::Off {
+        info!("Seasonal effects are off");
+        return;
+    }
+
     if !are_effects_enabled() {
         info!("Seasonal effects are disabled");
         return;
@@ -94,20 +118,31 @@ pub fn apply_seasonal_effects(window: &ApplicationWindow) {
 
     info!("Checking for active seasonal effects...");
 
+    let effects: Vec<Box<dyn SeasonalEffect>> = vec![
+        Box::new(SnowEffect::default()),
+        Box::new(HalloweenEffect::default()),
+    ];
+    let mut active: Vec<Box<dyn SeasonalEffect>> =
+        effects.into_iter().filter(|e| e.is_active()).collect();
+
+    // Out of season, `always` falls back to snow
+    if active.is_empty() && mode == SeasonalMode::Always {
+        active.push(Box::new(SnowEffect::default()));
+    }
+
+    if let Some(fps) = config::user::get().seasonal_fps {
+        set_target_fps(fps);
+    }
+
     let mouse_context = common::setup_mouse_tracking(window);
 
-    let effects: Vec<Box<dyn SeasonalEffect>> =
-        vec![Box::new(SnowEffect), Box::new(HalloweenEffect)];
-
-    for effect in effects {
-        if effect.is_active() {
-            info!("Active seasonal effect detected: {}", effect.name());
-            if let Some(drawing_area) = effect.apply(window, Some(&mouse_context)) {
-                register_drawing_area(drawing_area);
-                info!("Successfully applied {} effect", effect.name());
-            } else {
-                info!("Failed to apply {} effect", effect.name());
-            }
+    for effect in active {
+        info!("Active seasonal effect detected: {}", effect.name());
+        if let Some(drawing_area) = effect.apply(window, Some(&mouse_context)) {
+            register_drawing_area(drawing_area);
+            info!("Successfully applied {} effect", effect.name());
+        } else {
+            info!("Failed to apply {} effect", effect.name());
         }
     }
 }
@@ -4,13 +4,15 @@
 
 use crate::config::seasonal_debug;
 use crate::ui::seasonal::common::{
-    add_overlay_to_window, setup_resize_handler, ResizableEffectState,
+    add_overlay_to_child, add_overlay_to_window, setup_resize_handler, start_redraw_loop,
+    MouseContext, OverlayError, RepelField, ResizableEffectState,
 };
 use crate::ui::seasonal::SeasonalEffect;
 use gtk4::cairo;
 use gtk4::glib;
 use gtk4::prelude::*;
 use gtk4::{ApplicationWindow, DrawingArea};
+use log::info;
 use rand::rngs::StdRng;
 use rand::{Rng, SeedableRng};
 use std::cell::RefCell;
@@ -19,9 +21,25 @@ use std::rc::Rc;
 
 const SNOW_COUNT: usize = 80;
 const WIND_STRENGTH: f64 = 0.5;
+/// Fraction of the mouse push a flake loses per second
+const PUSH_DAMPING: f64 = 3.0;
 
 /// Christmas snow effect.
-pub struct SnowEffect;
+pub struct SnowEffect {
+    /// How flakes are blown away from the mouse pointer
+    pub repel: RepelField,
+}
+
+impl Default for SnowEffect {
+    fn default() -> Self {
+        Self {
+            repel: RepelField {
+                radius: 120.0,
+                strength: 20_000.0,
+            },
+        }
+    }
+}
 
 impl SeasonalEffect for SnowEffect {
     fn is_active(&self) -> bool {
@@ -45,7 +63,7 @@ impl SeasonalEffect for SnowEffect {
     fn apply(
         &self,
         window: &ApplicationWindow,
-        _mouse_context: Option<&crate::ui::seasonal::common::MouseContext>,
+        mouse_context: Option<&MouseContext>,
     ) -> Option<Rc<DrawingArea>> {
         let drawing_area = Rc::new(DrawingArea::new());
         drawing_area.set_hexpand(true);
@@ -56,14 +74,13 @@ impl SeasonalEffect for SnowEffect {
         drawing_area.set_valign(gtk4::Align::Fill);
         drawing_area.set_visible(crate::ui::seasonal::are_effects_enabled());
 
+        let mouse = mouse_context.cloned().unwrap_or_default();
+        let repel = self.repel;
+
         let state = Rc::new(RefCell::new(None::<SnowState>));
         let setup_state = Rc::clone(&state);
 
-        let drawing_area_clone = drawing_area.clone();
-        glib::timeout_add_local(std::time::Duration::from_millis(16), move || {
-            drawing_area_clone.queue_draw();
-            glib::ControlFlow::Continue
-        });
+        start_redraw_loop(window, &drawing_area);
 
         drawing_area.set_draw_func(move |_da, cr, width, height| {
             let mut state_ref = setup_state.borrow_mut();
@@ -74,7 +91,7 @@ impl SeasonalEffect for SnowEffect {
 
             if let Some(snow_state) = state_ref.as_mut() {
                 let now = std::time::Instant::now();
-                snow_state.update(width as f64, height as f64, now);
+                snow_state.update(width as f64, height as f64, now, mouse.position(), &repel);
 
                 let _ = cr.save();
                 cr.set_operator(cairo::Operator::Clear);
@@ -89,10 +106,16 @@ impl SeasonalEffect for SnowEffect {
         // Set up resize handler
         setup_resize_handler(&drawing_area, state);
 
-        if add_overlay_to_window(window, &drawing_area) {
-            Some(drawing_area)
-        } else {
-            None
+        let result = match add_overlay_to_window(window, &drawing_area) {
+            Err(OverlayError::NotAdwWindow) => add_overlay_to_child(window, &drawing_area),
+            result => result,
+        };
+        match result {
+            Ok(()) => Some(drawing_area),
+            Err(e) => {
+                info!("Failed to add snow effect overlay: {}", e);
+                None
+            }
         }
     }
 }
@@ -106,6 +129,9 @@ struct Snowflake {
     sway_offset: f64,
     sway_speed: f64,
     size: f64,
+    /// Velocity from the mouse push, decaying over time
+    push_x: f64,
+    push_y: f64,
 }
 
 impl Snowflake {
@@ -119,14 +145,34 @@ impl Snowflake {
             sway_offset: rng.random_range(0.0..2.0 * PI),
             sway_speed: rng.random_range(0.5..2.0),
             size: rng.random_range(2.0..5.0) * z,
+            push_x: 0.0,
+            push_y: 0.0,
         }
     }
 
-    fn update(&mut self, width: f64, height: f64, dt: f64, wind: f64, rng: &mut StdRng) {
-        self.y += self.speed_y * dt;
+    #[allow(clippy::too_many_arguments)]
+    fn update(
+        &mut self,
+        width: f64,
+        height: f64,
+        dt: f64,
+        wind: f64,
+        rng: &mut StdRng,
+        mouse: Option<(f64, f64)>,
+        repel: &RepelField,
+    ) {
+        if let Some((force_x, force_y)) = repel.force(self.x, self.y, mouse) {
+            self.push_x += force_x * dt;
+            self.push_y += force_y * dt;
+        }
+        let damping = (1.0 - PUSH_DAMPING * dt).max(0.0);
+        self.push_x *= damping;
+        self.push_y *= damping;
+
+        self.y += (self.speed_y + self.push_y) * dt;
         self.sway_offset += self.sway_speed * dt;
         let horizontal_move = (self.sway_offset.sin() * 20.0 * self.z) + (wind * 50.0);
-        self.x += horizontal_move * dt;
+        self.x += (horizontal_move + self.push_x) * dt;
 
         // When wrapping around, respawn at random position in current window dimensions
         if self.y > height + 10.0 {
@@ -191,7 +237,14 @@ impl SnowState {
         }
     }
 
-    fn update(&mut self, width: f64, height: f64, now: std::time::Instant) {
+    fn update(
+        &mut self,
+        width: f64,
+        height: f64,
+        now: std::time::Instant,
+        mouse: Option<(f64, f64)>,
+        repel: &RepelField,
+    ) {
         // Update stored dimensions during normal loop just in case,
         // though handle_resize does the heavy lifting.
         self.current_width = width;
@@ -206,7 +259,7 @@ impl SnowState {
         self.wind += (self.wind_target - self.wind) * dt;
 
         for flake in &mut self.snowflakes {
-            flake.update(width, height, dt, self.wind, &mut self.rng);
+            flake.update(width, height, dt, self.wind, &mut self.rng, mouse, repel);
         }
     }
 
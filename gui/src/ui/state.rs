@@ -0,0 +1,67 @@
+//! UI state persisted across restarts.
+//!
+//! Stored as TOML in `~/.config/xero-toolkit/state.toml`. A missing or
+//! unreadable file falls back to the defaults of a fresh install.
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+/// State remembered between application launches.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiState {
+    /// ID of the page that was shown last
+    pub last_page: Option<String>,
+}
+
+impl UiState {
+    /// Load the saved state, or the defaults if there is none.
+    pub fn load() -> Self {
+        let path = crate::config::paths::state_file();
+        let Ok(contents) = fs::read_to_string(&path) else {
+            return Self::default();
+        };
+
+        toml::from_str(&contents).unwrap_or_else(|e| {
+            warn!("Ignoring invalid state file {}: {}", path.display(), e);
+            Self::default()
+        })
+    }
+
+    /// Write the state to disk, creating the config directory if needed.
+    pub fn save(&self) -> anyhow::Result<()> {
+        let path = crate::config::paths::state_file();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        fs::write(&path, toml::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Update the saved state in place, logging failures.
+    pub fn update(f: impl FnOnce(&mut Self)) {
+        let mut state = Self::load();
+        f(&mut state);
+        if let Err(e) = state.save() {
+            warn!("Failed to save UI state: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_round_trip() {
+        let state = UiState {
+            last_page: Some("gaming_tools".to_string()),
+        };
+        let contents = toml::to_string(&state).unwrap();
+        assert_eq!(contents.trim(), "last_page = \"gaming_tools\"");
+        assert_eq!(toml::from_str::<UiState>(&contents).unwrap(), state);
+        assert_eq!(toml::from_str::<UiState>("").unwrap(), UiState::default());
+    }
+}
@@ -0,0 +1,145 @@
+//! ANSI escape sequence handling for command output.
+//!
+//! AUR helpers and pacman emit colored output. This module splits a chunk of
+//! output into plain text segments, mapping SGR foreground colors to the
+//! `ansi-*` text tags of the output view. All other escape sequences are dropped.
+
+/// Text tags for the 8 basic foreground colors, indexed by SGR color offset.
+pub const COLOR_TAGS: [&str; 8] = [
+    "ansi-black",
+    "ansi-red",
+    "ansi-green",
+    "ansi-yellow",
+    "ansi-blue",
+    "ansi-magenta",
+    "ansi-cyan",
+    "ansi-white",
+];
+
+/// A run of text with the color tag active for it, if any.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Segment {
+    pub text: String,
+    pub color: Option<&'static str>,
+}
+
+/// Split text into colored segments, removing all escape sequences.
+///
+/// Color state does not carry over between calls; the output is streamed
+/// line by line and tools reset their colors at the end of each line.
+pub fn parse(text: &str) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    let mut color: Option<&'static str> = None;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            current.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            // CSI: ESC [ params final-byte
+            Some('[') => {
+                let mut params = String::new();
+                let mut final_byte = None;
+                for c in chars.by_ref() {
+                    if ('\x40'..='\x7e').contains(&c) {
+                        final_byte = Some(c);
+                        break;
+                    }
+                    params.push(c);
+                }
+
+                if final_byte == Some('m') {
+                    let new_color = apply_sgr(&params, color);
+                    if new_color != color {
+                        flush(&mut segments, &mut current, color);
+                        color = new_color;
+                    }
+                }
+            }
+            // OSC: ESC ] ... terminated by BEL or ESC \
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' {
+                        break;
+                    }
+                    if c == '\x1b' && chars.peek() == Some(&'\\') {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            // Other two-character escapes
+            _ => {}
+        }
+    }
+
+    flush(&mut segments, &mut current, color);
+    segments
+}
+
+/// Compute the foreground color after applying SGR parameters.
+fn apply_sgr(params: &str, mut color: Option<&'static str>) -> Option<&'static str> {
+    let mut codes = params.split(';').map(|p| p.parse::<u32>().unwrap_or(0));
+
+    while let Some(code) = codes.next() {
+        match code {
+            0 | 39 => color = None,
+            30..=37 => color = Some(COLOR_TAGS[(code - 30) as usize]),
+            90..=97 => color = Some(COLOR_TAGS[(code - 90) as usize]),
+            // Extended colors: skip "5;n" or "2;r;g;b"
+            38 | 48 => match codes.next() {
+                Some(5) => {
+                    codes.next();
+                }
+                Some(2) => {
+                    codes.nth(2);
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    color
+}
+
+/// Push the accumulated text as a segment, if non-empty.
+fn flush(segments: &mut Vec<Segment>, current: &mut String, color: Option<&'static str>) {
+    if !current.is_empty() {
+        segments.push(Segment {
+            text: std::mem::take(current),
+            color,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_colors_and_strip_other_sequences() {
+        let segments = parse("\x1b[1;32m==>\x1b[0m Making \x1b]0;title\x07package\x1b[K\n");
+        assert_eq!(
+            segments,
+            vec![
+                Segment {
+                    text: "==>".to_string(),
+                    color: Some("ansi-green"),
+                },
+                Segment {
+                    text: " Making package\n".to_string(),
+                    color: None,
+                },
+            ]
+        );
+
+        let segments = parse("\x1b[38;5;196mred?\x1b[39m ünïcode");
+        assert_eq!(segments[0].color, None);
+        assert_eq!(segments[0].text, "red? ünïcode");
+    }
+}
@@ -3,6 +3,21 @@
 //! This module provides the core data structures for representing commands
 //! and their execution results in the task runner system.
 
+use super::stderr::StderrPatterns;
+use std::os::unix::process::ExitStatusExt;
+use std::path::PathBuf;
+use std::process::ExitStatus;
+use std::time::Duration;
+
+/// Default base delay between retry attempts.
+const DEFAULT_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Arguments shown in a [`Command::summary`] before the rest is cut.
+const SUMMARY_ARGS: usize = 8;
+
+/// Characters of a single argument shown in a [`Command::summary`].
+const SUMMARY_ARG_CHARS: usize = 60;
+
 /// Type of command to execute.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum CommandType {
@@ -10,12 +25,14 @@ pub enum CommandType {
     Normal,
     /// Command that needs privilege escalation (pkexec)
     Privileged,
-    /// AUR helper command (paru/yay)
+    /// AUR helper command (paru/yay/pikaur/aura)
     Aur,
+    /// Flatpak command, run as the user without privilege escalation
+    Flatpak,
 }
 
 /// Status of a task in the UI.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum TaskStatus {
     /// Task is pending (not started yet)
     Pending,
@@ -25,8 +42,12 @@ pub enum TaskStatus {
     Success,
     /// Task failed with error
     Failed,
+    /// Task failed, but was allowed to fail
+    Warning,
     /// Task was canceled by user
     Cancelled,
+    /// Task was not run because it was already satisfied
+    Skipped,
 }
 
 /// Result of command execution.
@@ -36,11 +57,103 @@ pub enum CommandResult {
     Success,
     /// Command failed with optional exit code
     Failure {
-        /// Exit code of the command, if available
+        /// Exit code of the command, if it exited
         exit_code: Option<i32>,
+        /// Signal that terminated the command, if it was killed
+        signal: Option<i32>,
     },
 }
 
+impl CommandResult {
+    /// Result of a process that has exited or been killed.
+    pub fn from_status(status: ExitStatus) -> Self {
+        if status.success() {
+            Self::Success
+        } else {
+            Self::Failure {
+                exit_code: status.code(),
+                signal: status.signal(),
+            }
+        }
+    }
+
+    /// How a failed command ended, such as `exit code: 1` or
+    /// `terminated by signal SIGSEGV`.
+    ///
+    /// None on success or when neither is known.
+    pub fn termination(&self) -> Option<String> {
+        match self {
+            Self::Success => None,
+            Self::Failure {
+                signal: Some(signal),
+                ..
+            } => Some(format!("terminated by signal {}", signal_name(*signal))),
+            Self::Failure {
+                exit_code: Some(code),
+                ..
+            } => Some(format!("exit code: {}", code)),
+            Self::Failure { .. } => None,
+        }
+    }
+}
+
+/// Name of a signal, such as `SIGSEGV`, or its number if it has no common name.
+pub fn signal_name(signal: i32) -> String {
+    let name = match signal {
+        libc::SIGHUP => "SIGHUP",
+        libc::SIGINT => "SIGINT",
+        libc::SIGQUIT => "SIGQUIT",
+        libc::SIGILL => "SIGILL",
+        libc::SIGTRAP => "SIGTRAP",
+        libc::SIGABRT => "SIGABRT",
+        libc::SIGBUS => "SIGBUS",
+        libc::SIGFPE => "SIGFPE",
+        libc::SIGKILL => "SIGKILL",
+        libc::SIGUSR1 => "SIGUSR1",
+        libc::SIGSEGV => "SIGSEGV",
+        libc::SIGUSR2 => "SIGUSR2",
+        libc::SIGPIPE => "SIGPIPE",
+        libc::SIGALRM => "SIGALRM",
+        libc::SIGTERM => "SIGTERM",
+        _ => return signal.to_string(),
+    };
+    name.to_string()
+}
+
+/// Outcome of a single step of a run.
+#[allow(dead_code)]
+// Output fields are read by `on_complete` callbacks and headless callers
+#[derive(Debug, Clone)]
+pub struct StepReport {
+    /// Description of the step
+    pub description: String,
+    /// Result of the last attempt
+    pub result: CommandResult,
+    /// Captured standard output of the last attempt
+    pub stdout: String,
+    /// Captured standard error of the last attempt
+    pub stderr: String,
+}
+
+impl StepReport {
+    /// Whether the step exited successfully.
+    pub fn success(&self) -> bool {
+        matches!(self.result, CommandResult::Success)
+    }
+}
+
+/// Report of a run, with one entry per executed step.
+///
+/// Steps after a failing step (that is not allowed to fail) are not run and
+/// have no entry.
+#[derive(Debug, Clone, Default)]
+pub struct CommandRunReport {
+    /// Outcome of each executed step, in order
+    pub steps: Vec<StepReport>,
+    /// Whether the whole sequence ran to completion
+    pub success: bool,
+}
+
 /// A command step to be executed by the task runner.
 ///
 /// Commands can be of different types (normal, privileged, AUR) and include
@@ -55,6 +168,32 @@ pub struct Command {
     pub args: Vec<String>,
     /// Human-readable description shown in the UI
     pub description: String,
+    /// Number of times to re-run the command after a failure
+    pub retries: u32,
+    /// Delay before the first retry, doubled after every further failed attempt
+    pub retry_delay: Duration,
+    /// Continue with the next step if this one fails
+    pub allow_failure: bool,
+    /// Extra environment variables for the process
+    pub env: Vec<(String, String)>,
+    /// Working directory, defaults to the application's current directory
+    pub cwd: Option<PathBuf>,
+    /// Message to confirm with the user before the command runs
+    pub confirm_before: Option<String>,
+    /// Let the user type input for the process while it runs
+    pub interactive: bool,
+    /// Recommend a reboot once the command has succeeded
+    pub requires_reboot: bool,
+    /// Share of the overall progress bar, relative to the other steps
+    pub weight: f64,
+    /// User to run the command as, dropping root privileges
+    pub run_as: Option<String>,
+    /// Patterns deciding which stderr lines are shown as errors or warnings
+    pub stderr_patterns: StderrPatterns,
+    /// Body of a script step, written to a private temp file and run with `program`
+    pub script: Option<String>,
+    /// Packages that make the step unnecessary once all of them are installed
+    pub skip_if_installed: Vec<String>,
 }
 
 /// Builder for constructing `Command` objects with a fluent API.
@@ -79,10 +218,9 @@ pub struct Command {
 ///     .description("Installing package")
 ///     .build();
 ///
-/// // Normal command
+/// // Flatpak command (program is automatically set)
 /// let cmd = Command::builder()
-///     .normal()
-///     .program("flatpak")
+///     .flatpak()
 ///     .args(&["install", "-y", "app.id"])
 ///     .description("Installing Flatpak app")
 ///     .build();
@@ -93,12 +231,46 @@ pub struct CommandBuilder {
     program: Option<String>,
     args: Vec<String>,
     description: Option<String>,
+    retries: u32,
+    retry_delay: Duration,
+    allow_failure: bool,
+    env: Vec<(String, String)>,
+    cwd: Option<PathBuf>,
+    confirm_before: Option<String>,
+    interactive: bool,
+    requires_reboot: bool,
+    weight: f64,
+    run_as: Option<String>,
+    stderr_patterns: StderrPatterns,
+    skip_if_installed: Vec<String>,
 }
 
 impl CommandBuilder {
+    /// Create an empty builder for the given command type.
+    fn new(command_type: CommandType) -> Self {
+        Self {
+            command_type,
+            program: None,
+            args: Vec::new(),
+            description: None,
+            retries: 0,
+            retry_delay: DEFAULT_RETRY_DELAY,
+            allow_failure: false,
+            env: Vec::new(),
+            cwd: None,
+            confirm_before: None,
+            interactive: false,
+            requires_reboot: false,
+            weight: 1.0,
+            run_as: None,
+            stderr_patterns: StderrPatterns::default(),
+            skip_if_installed: Vec::new(),
+        }
+    }
+
     /// Set the program/executable to run.
     ///
-    /// For AUR commands, the program is automatically set and this is ignored.
+    /// For AUR and Flatpak commands, the program is automatically set and this is ignored.
     pub fn program(mut self, program: &str) -> Self {
         self.program = Some(program.to_string());
         self
@@ -116,6 +288,117 @@ impl CommandBuilder {
         self
     }
 
+    /// Re-run the command up to `retries` times if it fails.
+    ///
+    /// Useful for steps that can fail transiently, such as downloads from mirrors.
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Set the delay before the first retry.
+    ///
+    /// The delay doubles after every further failed attempt.
+    #[allow(dead_code)]
+    pub fn retry_delay(mut self, delay: Duration) -> Self {
+        self.retry_delay = delay;
+        self
+    }
+
+    /// Let the sequence continue if this command fails.
+    ///
+    /// The step is marked with a warning instead of aborting the run. Useful for
+    /// cleanup steps, such as removing a package that may not be installed.
+    pub fn allow_failure(mut self) -> Self {
+        self.allow_failure = true;
+        self
+    }
+
+    /// Set an environment variable for the command.
+    ///
+    /// Privileged commands get the variable through the selected privilege
+    /// backend (`--env` for xero-auth, `env` for sudo, `--setenv` for run0), so it
+    /// reaches the command itself rather than only the escalation helper.
+    pub fn with_env(mut self, key: &str, value: &str) -> Self {
+        self.env.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Run the command from the given working directory.
+    ///
+    /// The step fails before spawning if the directory does not exist.
+    pub fn in_dir(mut self, path: impl Into<PathBuf>) -> Self {
+        self.cwd = Some(path.into());
+        self
+    }
+
+    /// Ask the user to confirm `message` before running this command.
+    ///
+    /// Declining cancels the rest of the sequence. Use for risky steps such
+    /// as driver swaps or disabling services.
+    pub fn confirm_before(mut self, message: &str) -> Self {
+        self.confirm_before = Some(message.to_string());
+        self
+    }
+
+    /// Let the user answer prompts of the command.
+    ///
+    /// The process gets a stdin pipe fed from an entry in the task dialog.
+    #[allow(dead_code)]
+    // AUR steps also become interactive through the `interactive` config option
+    pub fn interactive(mut self) -> Self {
+        self.interactive = true;
+        self
+    }
+
+    /// Recommend a reboot after this command succeeds.
+    ///
+    /// Use for steps that only take effect after a restart, such as kernel,
+    /// driver or initramfs changes.
+    pub fn requires_reboot(mut self) -> Self {
+        self.requires_reboot = true;
+        self
+    }
+
+    /// Set how much of the progress bar this step takes up.
+    ///
+    /// Steps default to a weight of 1.0. Give long steps, such as builds or
+    /// large downloads, a higher weight so the bar moves at an honest pace.
+    pub fn weight(mut self, weight: f64) -> Self {
+        self.weight = weight.max(0.0);
+        self
+    }
+
+    /// Run the command as the given user through `runuser`.
+    ///
+    /// Use for steps that must not run as root, such as AUR builds, when the
+    /// toolkit itself may be running elevated. The step fails before spawning
+    /// if the user does not exist.
+    pub fn run_as(mut self, user: &str) -> Self {
+        self.run_as = Some(user.to_string());
+        self
+    }
+
+    /// Set which stderr lines are highlighted as errors or warnings.
+    ///
+    /// The defaults match pacman and AUR helper messages. Patterns are
+    /// case-insensitive substrings; other stderr lines are shown muted.
+    #[allow(dead_code)]
+    // Every step currently runs pacman-style tools
+    pub fn stderr_patterns(mut self, errors: &[&str], warnings: &[&str]) -> Self {
+        self.stderr_patterns = StderrPatterns::new(errors, warnings);
+        self
+    }
+
+    /// Skip the step when all of `packages` are already installed.
+    ///
+    /// Checked right before the step would run, from the package cache if it
+    /// is loaded. Skipped steps count as successful.
+    pub fn skip_if_installed(mut self, packages: &[&str]) -> Self {
+        self.skip_if_installed = packages.iter().map(|p| p.to_string()).collect();
+        self
+    }
+
     /// Build the final `Command` object.
     ///
     /// # Panics
@@ -124,6 +407,7 @@ impl CommandBuilder {
     pub fn build(self) -> Command {
         let program = match self.command_type {
             CommandType::Aur => "aur".to_string(),
+            CommandType::Flatpak => "flatpak".to_string(),
             _ => self
                 .program
                 .expect("program is required for normal and privileged commands"),
@@ -136,11 +420,83 @@ impl CommandBuilder {
             program,
             args: self.args,
             description,
+            retries: self.retries,
+            retry_delay: self.retry_delay,
+            allow_failure: self.allow_failure,
+            env: self.env,
+            cwd: self.cwd,
+            confirm_before: self.confirm_before,
+            interactive: self.interactive,
+            requires_reboot: self.requires_reboot,
+            weight: self.weight,
+            run_as: self.run_as,
+            stderr_patterns: self.stderr_patterns,
+            script: None,
+            skip_if_installed: self.skip_if_installed,
         }
     }
 }
 
 impl Command {
+    /// Whether the step has nothing to do, see [`CommandBuilder::skip_if_installed`].
+    pub fn is_satisfied(&self) -> bool {
+        self.is_satisfied_with(crate::core::are_packages_installed)
+    }
+
+    /// [`Command::is_satisfied`] with the check for installed packages given.
+    pub(super) fn is_satisfied_with(&self, are_installed: impl Fn(&[&str]) -> bool) -> bool {
+        if self.skip_if_installed.is_empty() {
+            return false;
+        }
+        let packages: Vec<&str> = self.skip_if_installed.iter().map(String::as_str).collect();
+        are_installed(&packages)
+    }
+
+    /// Whether the user can send input to this command while it runs.
+    ///
+    /// AUR steps are interactive when the `interactive` config option is on.
+    pub fn is_interactive(&self) -> bool {
+        self.interactive
+            || (self.command_type == CommandType::Aur && crate::config::user::get().interactive)
+    }
+
+    /// Human-readable summary of the step: its description and what it runs.
+    ///
+    /// AUR steps show the detected helper and privileged ones are marked as
+    /// running as root. Long argument lists and arguments are shortened.
+    pub fn summary(&self) -> String {
+        let program = match self.command_type {
+            CommandType::Aur => crate::core::aur_helper().unwrap_or("aur"),
+            _ => self.program.as_str(),
+        };
+
+        let mut words = vec![program.to_string()];
+        if self.script.is_some() {
+            words.push("<script>".to_string());
+        }
+        words.extend(self.args.iter().take(SUMMARY_ARGS).map(|arg| {
+            if arg.chars().count() > SUMMARY_ARG_CHARS {
+                let short: String = arg.chars().take(SUMMARY_ARG_CHARS).collect();
+                format!("{}…", short)
+            } else {
+                arg.clone()
+            }
+        }));
+        let hidden = self.args.len().saturating_sub(SUMMARY_ARGS);
+        if hidden > 0 {
+            words.push(format!("(+{} more)", hidden));
+        }
+        if self.command_type == CommandType::Privileged {
+            words.push("(as root)".to_string());
+        }
+
+        format!(
+            "{}\n    {}",
+            self.description.trim_end_matches("..."),
+            words.join(" ")
+        )
+    }
+
     /// Create a new command builder.
     ///
     /// This is the recommended way to construct commands with a fluent API.
@@ -160,6 +516,134 @@ impl Command {
     pub fn builder() -> CommandBuilderType {
         CommandBuilderType
     }
+
+    /// Create a command that installs packages through the AUR helper.
+    ///
+    /// Runs `-S --needed --noconfirm`, so packages that are already up to
+    /// date are skipped, and the whole step once all are installed. Use
+    /// [`Command::builder`] with `.aur()` for other flags.
+    pub fn install(packages: &[&str], friendly_name: &str) -> Command {
+        Command::builder()
+            .aur()
+            .args(&install_args(packages))
+            .description(&format!("Installing {}...", friendly_name))
+            .skip_if_installed(packages)
+            .build()
+    }
+
+    /// Create a privileged command that installs repo packages with pacman.
+    ///
+    /// Same flags as [`Command::install`], for packages that must not come
+    /// from the AUR.
+    pub fn install_native(packages: &[&str], friendly_name: &str) -> Command {
+        Command::builder()
+            .privileged()
+            .program("pacman")
+            .args(&install_args(packages))
+            .description(&format!("Installing {}...", friendly_name))
+            .skip_if_installed(packages)
+            .build()
+    }
+
+    /// Create a command that removes packages through the AUR helper.
+    ///
+    /// Runs `-Rns --noconfirm`, removing unneeded dependencies and config
+    /// backups as well. Escalation goes through the selected privilege backend.
+    pub fn aur_remove(packages: &[&str], friendly_name: &str) -> Command {
+        Command::builder()
+            .aur()
+            .args(&remove_args(packages))
+            .description(&format!("Removing {}...", friendly_name))
+            .build()
+    }
+
+    /// Create a flatpak command for the given app.
+    ///
+    /// The description follows the flatpak subcommand in `args`, e.g.
+    /// `install` reads as "Installing {friendly_name}...".
+    pub fn flatpak(args: &[&str], friendly_name: &str) -> Command {
+        let action = match args.first().copied() {
+            Some("install") => "Installing",
+            Some("uninstall") => "Removing",
+            Some("update") => "Updating",
+            _ => "Running Flatpak for",
+        };
+        Command::builder()
+            .flatpak()
+            .args(args)
+            .description(&format!("{} {}...", action, friendly_name))
+            .build()
+    }
+
+    /// Create a privileged command that removes packages with pacman.
+    #[allow(dead_code)]
+    // Counterpart of `aur_remove` for repo-only packages
+    pub fn pacman_remove(packages: &[&str], friendly_name: &str) -> Command {
+        Command::builder()
+            .privileged()
+            .program("pacman")
+            .args(&remove_args(packages))
+            .description(&format!("Removing {}...", friendly_name))
+            .build()
+    }
+
+    /// Create a privileged command that runs a shell script.
+    ///
+    /// The body is written to a temp file with a random name and `0700`
+    /// permissions when the step starts, run with bash through the privilege
+    /// backend, and removed once the step has ended, even if it failed.
+    /// Nothing is interpolated into a `bash -c` string, so multi-line scripts
+    /// can be kept as they are.
+    pub fn script(script_body: &str, friendly_name: &str) -> Command {
+        let mut command = Command::builder()
+            .privileged()
+            .program("bash")
+            .description(&format!("Running {}...", friendly_name))
+            .build();
+        command.script = Some(script_body.to_string());
+        command
+    }
+
+    /// Create a privileged command that enables the `[multilib]` repository.
+    ///
+    /// Uncomments only the exact `#[multilib]` header and the `Include` line
+    /// right after it, leaving `[multilib-testing]` and other comments alone,
+    /// then syncs and upgrades the system. Syncing the databases without
+    /// upgrading would leave a partial upgrade behind. Asks the user before
+    /// running.
+    pub fn enable_multilib() -> Command {
+        let script = format!(
+            "sed -i '/^#\\[multilib\\]$/{{s/^#//;n;s/^#Include/Include/}}' {} && pacman -Syu --noconfirm",
+            crate::core::package::PACMAN_CONF
+        );
+
+        Command::builder()
+            .privileged()
+            .program("bash")
+            .args(&["-c", &script])
+            .description("Enabling multilib repository and updating the system...")
+            .confirm_before(
+                "The packages being installed need the multilib repository, which is not \
+                enabled. Enable it in pacman.conf, update the system and continue?",
+            )
+            .build()
+    }
+}
+
+/// Arguments for a pacman-style package install.
+fn install_args<'a>(packages: &[&'a str]) -> Vec<&'a str> {
+    ["-S", "--needed", "--noconfirm"]
+        .into_iter()
+        .chain(packages.iter().copied())
+        .collect()
+}
+
+/// Arguments for a pacman-style package removal.
+fn remove_args<'a>(packages: &[&'a str]) -> Vec<&'a str> {
+    ["-Rns", "--noconfirm"]
+        .into_iter()
+        .chain(packages.iter().copied())
+        .collect()
 }
 
 /// Entry point for the command builder API.
@@ -186,10 +670,9 @@ impl Command {
 ///     .description("Installing package")
 ///     .build();
 ///
-/// // Normal command
+/// // Flatpak command (program is automatically set)
 /// let cmd = Command::builder()
-///     .normal()
-///     .program("flatpak")
+///     .flatpak()
 ///     .args(&["install", "-y", "app.id"])
 ///     .description("Installing Flatpak app")
 ///     .build();
@@ -200,31 +683,21 @@ pub struct CommandBuilderType;
 impl CommandBuilderType {
     /// Create a builder for a normal command (no special handling).
     pub fn normal(self) -> CommandBuilder {
-        CommandBuilder {
-            command_type: CommandType::Normal,
-            program: None,
-            args: Vec::new(),
-            description: None,
-        }
+        CommandBuilder::new(CommandType::Normal)
     }
 
     /// Create a builder for a privileged command (runs through pkexec).
     pub fn privileged(self) -> CommandBuilder {
-        CommandBuilder {
-            command_type: CommandType::Privileged,
-            program: None,
-            args: Vec::new(),
-            description: None,
-        }
+        CommandBuilder::new(CommandType::Privileged)
     }
 
-    /// Create a builder for an AUR helper command (paru/yay).
+    /// Create a builder for an AUR helper command (paru/yay/pikaur/aura).
     pub fn aur(self) -> CommandBuilder {
-        CommandBuilder {
-            command_type: CommandType::Aur,
-            program: None,
-            args: Vec::new(),
-            description: None,
-        }
+        CommandBuilder::new(CommandType::Aur)
+    }
+
+    /// Create a builder for a flatpak command (never escalated).
+    pub fn flatpak(self) -> CommandBuilder {
+        CommandBuilder::new(CommandType::Flatpak)
     }
 }
@@ -5,49 +5,436 @@
 //! - Output capture (stdout/stderr)
 //! - Error handling and result processing
 //! - Command resolution (privilege escalation, AUR helpers)
+//! - Persisting output to a log file on disk
+//! - Notifying the desktop when a run ends in the background
 
-use super::command::{Command, CommandResult, CommandType, TaskStatus};
+use super::command::{
+    signal_name, Command, CommandResult, CommandRunReport, CommandType, StepReport, TaskStatus,
+};
+use super::progress::parse_progress;
+use super::remote::{remote_script_urls, remote_script_warning};
+use super::script::ScriptFile;
+use super::sequence::{Next, Sequence, StepOutcome};
+use super::spawner::{CommandSpawner, ProcessWatch, SpawnRequest};
 use super::widgets::TaskRunnerWidgets;
+use super::{RunOptions, StepEvent};
 use crate::core;
 use crate::core::daemon::get_xero_auth_path;
-use gtk4::gio;
-use gtk4::glib;
+use adw::prelude::*;
+use gtk4::{gio, glib, Window};
 use log::{error, info, warn};
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
-use xero_auth::utils::read_buffer_with_line_processing;
+use std::time::{Duration, Instant};
 
-/// Context for a running command execution.
-pub struct RunningContext {
+/// Output of steps skipped because they were already satisfied.
+pub(super) const SKIPPED_MESSAGE: &str = "Already satisfied, skipping";
+
+/// Shared state for a command sequence that is being executed.
+pub struct ExecutionState {
     pub widgets: Rc<TaskRunnerWidgets>,
-    pub commands: Rc<Vec<Command>>,
-    pub index: usize,
+    pub commands: Vec<Command>,
+    pub options: RunOptions,
     pub cancelled: Rc<RefCell<bool>>,
-    pub current_process: Rc<RefCell<Option<gio::Subprocess>>>,
-    exit_result: RefCell<Option<CommandResult>>,
+    /// Context of the command that is currently running, if any
+    pub current_process: RefCell<Option<Rc<RunningContext>>>,
+    /// Confirmation dialog currently waiting for an answer
+    pub pending_confirmation: RefCell<Option<adw::AlertDialog>>,
+    /// Whether the running command is stopped with SIGSTOP
+    paused: Cell<bool>,
+    /// Marks the action as running until the run is finished or closed
+    action: RefCell<Option<super::ActionGuard>>,
+    /// Result and output of each step, for the `on_complete` callback
+    step_reports: RefCell<Vec<Option<StepReport>>>,
+    /// Step the run failed at, kept so it can be retried from there
+    failed_step: Cell<Option<usize>>,
+    /// Starts the process of each step
+    spawner: Rc<dyn CommandSpawner>,
+    /// Step the run is at and how often it failed
+    sequence: RefCell<Sequence>,
 }
 
-impl RunningContext {
-    /// Create a new running command context.
+impl ExecutionState {
+    /// Create the shared state for a new run.
     pub fn new(
         widgets: Rc<TaskRunnerWidgets>,
-        commands: Rc<Vec<Command>>,
-        index: usize,
+        commands: Vec<Command>,
+        options: RunOptions,
         cancelled: Rc<RefCell<bool>>,
-        current_process: Rc<RefCell<Option<gio::Subprocess>>>,
+        action: super::ActionGuard,
+        spawner: Rc<dyn CommandSpawner>,
     ) -> Rc<Self> {
+        let step_reports = vec![None; commands.len()];
+
         Rc::new(Self {
             widgets,
             commands,
-            index,
+            options,
             cancelled,
-            current_process,
+            current_process: RefCell::new(None),
+            pending_confirmation: RefCell::new(None),
+            paused: Cell::new(false),
+            action: RefCell::new(Some(action)),
+            step_reports: RefCell::new(step_reports),
+            failed_step: Cell::new(None),
+            spawner,
+            sequence: RefCell::new(Sequence::new(0)),
+        })
+    }
+
+    /// Mark the action as running again, for a retry of this run.
+    pub fn reacquire_action(&self, action: super::ActionGuard) {
+        *self.action.borrow_mut() = Some(action);
+    }
+
+    /// Step the run failed at, clearing it.
+    pub fn take_failed_step(&self) -> Option<usize> {
+        self.failed_step.take()
+    }
+
+    /// Record the result and output of a step's latest attempt.
+    fn record_step(&self, index: usize, result: CommandResult, stdout: String, stderr: String) {
+        if let Some(report) = self.step_reports.borrow_mut().get_mut(index) {
+            *report = Some(StepReport {
+                description: self.commands[index].description.clone(),
+                result,
+                stdout,
+                stderr,
+            });
+        }
+    }
+
+    /// Let other actions start, even while this window stays open.
+    pub fn release_action(&self) {
+        self.action.borrow_mut().take();
+    }
+
+    /// Send a line of user input to the running command.
+    ///
+    /// The line is echoed in the output view. Nothing is sent if no command is
+    /// running or its input was closed.
+    pub fn send_input(&self, line: &str) {
+        let running = self.current_process.borrow().clone();
+        let Some(context) = running else {
+            return;
+        };
+
+        match context.write_stdin(line) {
+            Ok(()) => self
+                .widgets
+                .append_colored(&format!("{}\n", line), "timestamp"),
+            Err(e) => {
+                warn!("Failed to send input to process: {}", e);
+                self.widgets
+                    .append_colored("Input could not be sent, the process closed it\n", "error");
+            }
+        }
+    }
+
+    /// Request cancellation, closing any open confirmation dialog.
+    ///
+    /// The running command is asked to terminate; the sequence stops once it
+    /// has exited.
+    pub fn cancel(&self) {
+        *self.cancelled.borrow_mut() = true;
+
+        // Closing triggers the "no" response, which finalizes the run
+        let dialog = self.pending_confirmation.borrow_mut().take();
+        if let Some(dialog) = dialog {
+            dialog.close();
+        }
+
+        // A stopped process would not act on SIGTERM
+        self.unpause();
+
+        let running = self.current_process.borrow().clone();
+        if let Some(context) = running {
+            context.terminate();
+        }
+    }
+
+    /// Whether the running command is a pacman or AUR helper transaction.
+    ///
+    /// Killing one of these midway can leave the package database
+    /// inconsistent.
+    pub fn is_package_transaction_running(&self) -> bool {
+        let Some(context) = self.current_process.borrow().clone() else {
+            return false;
+        };
+        self.commands.get(context.index).is_some_and(|cmd| {
+            cmd.command_type == CommandType::Aur
+                || (cmd.command_type == CommandType::Privileged && cmd.program == "pacman")
+        })
+    }
+
+    /// Resume the running command if it is paused.
+    pub fn unpause(&self) {
+        if !self.paused.replace(false) {
+            return;
+        }
+
+        let running = self.current_process.borrow().clone();
+        if let Some(context) = running {
+            context.resume();
+        }
+        self.widgets.set_paused(false);
+    }
+
+    /// Pause the running command, or resume it if it is paused.
+    ///
+    /// The process keeps its state while paused, so resuming continues it
+    /// where it stopped.
+    pub fn toggle_pause(&self) {
+        let Some(context) = self.current_process.borrow().clone() else {
+            return;
+        };
+
+        let paused = !self.paused.get();
+        if paused {
+            context.pause();
+        } else {
+            context.resume();
+        }
+        self.paused.set(paused);
+        self.widgets.set_paused(paused);
+    }
+
+    /// Update a task's status in the UI and notify the `on_step` callback.
+    pub fn set_task_status(&self, index: usize, status: TaskStatus) {
+        self.widgets.update_task_status(index, status);
+
+        if status == TaskStatus::Success
+            && self.commands[index].requires_reboot
+            && !self.options.dry_run
+        {
+            self.widgets.recommend_reboot();
+        }
+
+        if let Some(on_step) = &self.options.on_step {
+            on_step(StepEvent {
+                index,
+                total: self.commands.len(),
+                description: self.commands[index].description.clone(),
+                status,
+            });
+        }
+    }
+}
+
+/// Writes task runner output to a log file, flushing after every write.
+pub struct LogWriter {
+    path: PathBuf,
+    file: RefCell<File>,
+}
+
+impl LogWriter {
+    /// Create a new timestamped log file in the logs directory.
+    pub fn create() -> anyhow::Result<Self> {
+        let dir = crate::config::paths::logs();
+        std::fs::create_dir_all(&dir)?;
+
+        let timestamp = glib::DateTime::now_local()?.format("%Y-%m-%d_%H-%M-%S")?;
+        let path = dir.join(format!("{}.log", timestamp));
+        let file = File::create(&path)?;
+
+        info!("Writing task log to {}", path.display());
+        Ok(Self {
+            path,
+            file: RefCell::new(file),
+        })
+    }
+
+    /// Path of the log file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append text to the log and flush it so a crash still leaves a usable log.
+    pub fn write(&self, text: &str) {
+        let mut file = self.file.borrow_mut();
+        if let Err(e) = file.write_all(text.as_bytes()).and_then(|_| file.flush()) {
+            warn!("Failed to write to log file {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+/// Time a cancelled command gets to exit after SIGTERM before it is killed.
+const TERMINATE_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+/// How often output of the running command is added to the output view.
+const OUTPUT_FLUSH_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Send `signal` to the process `pid`, logging failures.
+fn send_signal(pid: u32, signal: libc::c_int) {
+    if let Ok(pid) = libc::pid_t::try_from(pid) {
+        kill(pid, signal);
+    }
+}
+
+/// Send `signal` to every process in the process group led by `pid`.
+///
+/// Commands are spawned as group leaders, so this reaches child processes
+/// such as the compiler in an AUR build too.
+fn send_group_signal(pid: u32, signal: libc::c_int) {
+    if let Ok(pid) = libc::pid_t::try_from(pid) {
+        kill(-pid, signal);
+    }
+}
+
+/// Call `kill(2)`, logging failures.
+fn kill(target: libc::pid_t, signal: libc::c_int) {
+    // SAFETY: kill has no memory safety requirements
+    if unsafe { libc::kill(target, signal) } != 0 {
+        warn!(
+            "Failed to send signal {} to {}: {}",
+            signal,
+            target,
+            std::io::Error::last_os_error()
+        );
+    }
+}
+
+/// How often a step waiting to be retried checks whether the run was cancelled.
+const RETRY_CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Context for a running command execution.
+pub struct RunningContext {
+    pub state: Rc<ExecutionState>,
+    pub index: usize,
+    pub attempt: u32,
+    /// Process ID of the spawned command, cleared once it has exited
+    pid: Cell<Option<u32>>,
+    /// Input pipe of interactive commands, closed once the process has exited
+    stdin: RefCell<Option<Box<dyn Write>>>,
+    exit_result: RefCell<Option<CommandResult>>,
+    /// Standard output captured so far
+    stdout: RefCell<String>,
+    /// Standard error captured so far
+    stderr: RefCell<String>,
+    /// Temp file of a script step, removed once the command has exited
+    script: RefCell<Option<ScriptFile>>,
+}
+
+impl RunningContext {
+    /// Create a new running command context.
+    pub fn new(state: Rc<ExecutionState>, index: usize, attempt: u32) -> Rc<Self> {
+        Rc::new(Self {
+            state,
+            index,
+            attempt,
+            pid: Cell::new(None),
+            stdin: RefCell::new(None),
             exit_result: RefCell::new(None),
+            stdout: RefCell::new(String::new()),
+            stderr: RefCell::new(String::new()),
+            script: RefCell::new(None),
         })
     }
 
+    /// Process ID of the running command, if it has been spawned and not exited.
+    pub fn pid(&self) -> Option<u32> {
+        self.pid.get()
+    }
+
+    /// Stop the running command gracefully.
+    ///
+    /// Sends SIGTERM so tools like pacman can remove their lock files, and
+    /// escalates to SIGKILL if the process is still alive after
+    /// `TERMINATE_GRACE_PERIOD`.
+    pub fn terminate(self: &Rc<Self>) {
+        let Some(pid) = self.pid() else {
+            return;
+        };
+
+        info!("Sending SIGTERM to process {}", pid);
+        send_signal(pid, libc::SIGTERM);
+
+        let context = self.clone();
+        glib::timeout_add_local_once(TERMINATE_GRACE_PERIOD, move || {
+            // The PID is cleared on exit, so it can't have been reused
+            if let Some(pid) = context.pid() {
+                warn!(
+                    "Process {} still running after {:?}, sending SIGKILL",
+                    pid, TERMINATE_GRACE_PERIOD
+                );
+                send_signal(pid, libc::SIGKILL);
+            }
+        });
+    }
+
+    /// Stop the running command and its children with SIGSTOP.
+    ///
+    /// Processes running as root, such as the command behind a privilege
+    /// helper, can't be stopped this way and keep running.
+    pub fn pause(&self) {
+        if let Some(pid) = self.pid() {
+            info!("Pausing process group {}", pid);
+            send_group_signal(pid, libc::SIGSTOP);
+        }
+    }
+
+    /// Continue a command stopped with [`RunningContext::pause`].
+    pub fn resume(&self) {
+        if let Some(pid) = self.pid() {
+            info!("Resuming process group {}", pid);
+            send_group_signal(pid, libc::SIGCONT);
+        }
+    }
+
+    /// Write a line to the command's stdin.
+    ///
+    /// Fails if the command is not interactive or has closed its input, in
+    /// which case the pipe is dropped.
+    pub fn write_stdin(&self, line: &str) -> std::io::Result<()> {
+        let mut stdin = self.stdin.borrow_mut();
+        let Some(pipe) = stdin.as_mut() else {
+            return Err(std::io::ErrorKind::NotConnected.into());
+        };
+
+        let result = pipe
+            .write_all(format!("{}\n", line).as_bytes())
+            .and_then(|_| pipe.flush());
+        if result.is_err() {
+            stdin.take();
+        }
+        result
+    }
+
+    /// Append the output the reader threads sent since the last flush.
+    ///
+    /// Everything received is inserted in one go per stream, so chatty
+    /// commands don't redraw the output view for every line.
+    fn flush_output(&self, chunks: Vec<String>, stderr: &str) {
+        let widgets = &self.state.widgets;
+
+        if !chunks.is_empty() {
+            if let Some(fraction) = chunks.iter().rev().find_map(|chunk| parse_progress(chunk)) {
+                widgets.update_substep_progress(fraction);
+            }
+            // Chunks already include their line endings
+            let text = chunks.concat();
+            widgets.append_output(&text, "stdout");
+            self.stdout.borrow_mut().push_str(&text);
+        }
+
+        if !stderr.is_empty() {
+            let patterns = &self.state.commands[self.index].stderr_patterns;
+            widgets.append_stderr(stderr, patterns);
+            self.stderr.borrow_mut().push_str(stderr);
+        }
+    }
+
     /// Set the exit result for the current command.
+    ///
+    /// Only called after the command's last output has been flushed, so the
+    /// output and step report are complete when the step is finalized.
     pub fn set_exit_result(self: &Rc<Self>, result: CommandResult) {
+        self.pid.set(None);
+        self.stdin.borrow_mut().take();
+        self.script.borrow_mut().take();
         *self.exit_result.borrow_mut() = Some(result);
         self.try_finalize();
     }
@@ -63,99 +450,279 @@ impl RunningContext {
             return;
         };
 
-        // Clear current process
-        self.current_process.borrow_mut().take();
-
-        // Check if canceled
-        if *self.cancelled.borrow() {
-            // Mark the current task as canceled
-            self.widgets
-                .update_task_status(self.index, TaskStatus::Cancelled);
-            finalize_execution(&self.widgets, false, super::CANCELLED_MESSAGE);
-            return;
-        }
+        let exit_code = match result {
+            CommandResult::Success => Some(0),
+            CommandResult::Failure { exit_code, .. } => exit_code,
+        };
+        core::audit::log_command_result(&self.state.commands[self.index].description, exit_code);
+        self.state.record_step(
+            self.index,
+            result.clone(),
+            self.stdout.take(),
+            self.stderr.take(),
+        );
+        let widgets = &self.state.widgets;
 
-        // Handle result and print exit code to terminal
-        match result {
-            CommandResult::Success => {
-                // Print exit code for successful command
-                self.widgets.append_colored("\n[Exit code: 0]\n", "stdout");
+        // Clear current process
+        self.state.current_process.borrow_mut().take();
+        widgets.set_pause_available(false);
+        widgets.set_input_available(false);
 
-                self.widgets
-                    .update_task_status(self.index, TaskStatus::Success);
-                execute_commands(
-                    self.widgets.clone(),
-                    self.commands.clone(),
-                    self.index + 1,
-                    self.cancelled.clone(),
-                    self.current_process.clone(),
-                );
+        let cmd = &self.state.commands[self.index];
+        let outcome = self.state.sequence.borrow_mut().finished(
+            &self.state.commands,
+            &result,
+            *self.state.cancelled.borrow(),
+        );
+        match outcome {
+            StepOutcome::Cancelled => {
+                self.state
+                    .set_task_status(self.index, TaskStatus::Cancelled);
+                finalize_execution(&self.state, false, super::CANCELLED_MESSAGE);
             }
-            CommandResult::Failure { exit_code } => {
-                // Print exit code for failed command
-                let exit_msg = match exit_code {
-                    Some(code) => format!("\n[Exit code: {}]\n", code),
-                    None => "\n[Exit code: unknown]\n".to_string(),
-                };
-                self.widgets.append_colored(&exit_msg, "stderr");
-
-                self.widgets
-                    .update_task_status(self.index, TaskStatus::Failed);
+            StepOutcome::Retry(delay) => {
+                self.append_exit_message(&result);
+                self.schedule_retry(delay);
+            }
+            StepOutcome::Continue(status) => {
+                self.append_exit_message(&result);
+                if status == TaskStatus::Warning {
+                    warn!(
+                        "Command '{}' failed but is allowed to fail, continuing",
+                        cmd.description
+                    );
+                    widgets
+                        .append_colored("Step failed, continuing with the next step\n", "stderr");
+                }
+                self.state.set_task_status(self.index, status);
+                run_next(self.state.clone());
+            }
+            StepOutcome::Failed => {
+                self.append_exit_message(&result);
+                self.state.set_task_status(self.index, TaskStatus::Failed);
 
-                // Include exit code in error message if available
-                let exit_msg = exit_code
-                    .map(|code| format!(" (exit code: {})", code))
+                // Include the exit code or signal in the error message if available
+                let exit_msg = result
+                    .termination()
+                    .map(|termination| format!(" ({})", termination))
                     .unwrap_or_default();
                 let final_message = format!(
                     "Operation failed at step {} of {}{}",
                     self.index + 1,
-                    self.commands.len(),
+                    self.state.commands.len(),
                     exit_msg
                 );
 
-                finalize_execution(&self.widgets, false, &final_message);
+                finalize_execution(&self.state, false, &final_message);
             }
         }
     }
+
+    /// Print how the command ended to the output view.
+    fn append_exit_message(&self, result: &CommandResult) {
+        match *result {
+            CommandResult::Success => self
+                .state
+                .widgets
+                .append_colored("\n[Exit code: 0]\n", "stdout"),
+            CommandResult::Failure { exit_code, signal } => {
+                let exit_msg = match (signal, exit_code) {
+                    (Some(signal), _) => {
+                        format!("\n[Terminated by signal {}]\n", signal_name(signal))
+                    }
+                    (None, Some(code)) => format!("\n[Exit code: {}]\n", code),
+                    (None, None) => "\n[Exit code: unknown]\n".to_string(),
+                };
+                self.state.widgets.append_colored(&exit_msg, "stderr");
+            }
+        }
+    }
+
+    /// Re-run the current command after `delay`.
+    ///
+    /// Cancelling during the delay stops the run without waiting for the
+    /// delay to end.
+    fn schedule_retry(self: &Rc<Self>, delay: Duration) {
+        let cmd = &self.state.commands[self.index];
+        let next_attempt = self.attempt + 1;
+
+        warn!(
+            "Command '{}' failed, retrying ({}/{}) in {:?}",
+            cmd.description, next_attempt, cmd.retries, delay
+        );
+        self.state.widgets.append_colored(
+            &format!(
+                "\nRetry {}/{} after failure (waiting {}s)...\n",
+                next_attempt,
+                cmd.retries,
+                delay.as_secs()
+            ),
+            "stderr",
+        );
+
+        let state = self.state.clone();
+        let retry_at = Instant::now() + delay;
+        glib::timeout_add_local(RETRY_CANCEL_POLL_INTERVAL, move || {
+            if !*state.cancelled.borrow() && Instant::now() < retry_at {
+                return glib::ControlFlow::Continue;
+            }
+            // A cancelled run is finalized right away instead of retried
+            run_next(state.clone());
+            glib::ControlFlow::Break
+        });
+    }
 }
 
-/// Execute a sequence of commands.
-pub fn execute_commands(
-    widgets: Rc<TaskRunnerWidgets>,
-    commands: Rc<Vec<Command>>,
-    index: usize,
-    cancelled: Rc<RefCell<bool>>,
-    current_process: Rc<RefCell<Option<gio::Subprocess>>>,
-) {
-    if *cancelled.borrow() {
-        // If there's a current task being processed, mark it as canceled
-        if index < commands.len() {
-            widgets.update_task_status(index, TaskStatus::Cancelled);
-        }
-        finalize_execution(&widgets, false, super::CANCELLED_MESSAGE);
-        return;
+/// Execute a sequence of commands, starting at `index`.
+pub fn execute_commands(state: Rc<ExecutionState>, index: usize) {
+    *state.sequence.borrow_mut() = Sequence::new(index);
+    run_next(state);
+}
+
+/// Go on with the step the sequence is at, see [`Sequence::next`].
+fn run_next(state: Rc<ExecutionState>) {
+    let next = state.sequence.borrow_mut().next(
+        &state.commands,
+        *state.cancelled.borrow(),
+        Command::is_satisfied,
+    );
+
+    match next {
+        Next::Cancelled(index) => {
+            // If there's a current task being processed, mark it as canceled
+            if let Some(index) = index {
+                state.set_task_status(index, TaskStatus::Cancelled);
+            }
+            finalize_execution(&state, false, super::CANCELLED_MESSAGE);
+        }
+        Next::Done => finalize_execution(&state, true, super::SUCCESS_MESSAGE),
+        Next::Skip(index) => skip_step(state, index),
+        Next::Run { index, attempt } => {
+            // Ask before risky steps; retries of a confirmed step don't ask again
+            if attempt == 0 && !state.options.dry_run {
+                if let Some(message) = confirmation_message(&state.commands[index]) {
+                    confirm_step(state, index, &message);
+                    return;
+                }
+            }
+            start_command(state, index, attempt);
+        }
     }
+}
 
-    if index >= commands.len() {
-        finalize_execution(&widgets, true, super::SUCCESS_MESSAGE);
-        return;
+/// Mark the already satisfied step at `index` as skipped and go on.
+fn skip_step(state: Rc<ExecutionState>, index: usize) {
+    let cmd = &state.commands[index];
+    info!("Skipping satisfied step: {}", cmd.description);
+
+    state.set_task_status(index, TaskStatus::Skipped);
+    state.widgets.append_command_header(&cmd.description);
+    state
+        .widgets
+        .append_colored(&format!("{}\n", SKIPPED_MESSAGE), "stdout");
+    state.record_step(
+        index,
+        CommandResult::Success,
+        SKIPPED_MESSAGE.to_string(),
+        String::new(),
+    );
+
+    glib::idle_add_local_once(move || run_next(state));
+}
+
+/// Message to confirm before running `cmd`, if it needs confirmation.
+///
+/// AUR steps are confirmed too when `noconfirm` is off in the user config,
+/// and steps running a downloaded script always are, naming its URLs.
+fn confirmation_message(cmd: &Command) -> Option<String> {
+    let urls = remote_script_urls(cmd);
+    if !urls.is_empty() {
+        let warning = remote_script_warning(cmd, &urls);
+        return Some(match &cmd.confirm_before {
+            Some(message) => format!("{}\n\n{}", message, warning),
+            None => warning,
+        });
+    }
+
+    if let Some(message) = &cmd.confirm_before {
+        return Some(message.clone());
     }
 
-    let cmd = &commands[index];
+    (cmd.command_type == CommandType::Aur && !crate::config::user::get().noconfirm).then(|| {
+        format!(
+            "{}\n\n{} {}",
+            cmd.description,
+            core::aur_helper().unwrap_or("aur"),
+            cmd.args.join(" ")
+        )
+    })
+}
+
+/// Show a confirmation dialog before running the step at `index`.
+///
+/// "Yes" starts the step, "No" cancels the remaining sequence. The sequence is
+/// resumed from the response callback so the main loop is never blocked.
+fn confirm_step(state: Rc<ExecutionState>, index: usize, message: &str) {
+    info!("Waiting for confirmation before step {}", index + 1);
+    state.widgets.set_title("Waiting for confirmation...");
+
+    let dialog = adw::AlertDialog::builder()
+        .heading("Confirm Step")
+        .body(message)
+        .build();
+    dialog.add_responses(&[("no", "No"), ("yes", "Yes")]);
+    dialog.set_response_appearance("yes", adw::ResponseAppearance::Destructive);
+    dialog.set_default_response(Some("no"));
+    dialog.set_close_response("no");
+
+    let state_clone = state.clone();
+    dialog.connect_response(None, move |_, response| {
+        state_clone.pending_confirmation.borrow_mut().take();
+
+        if response != "yes" {
+            info!("Step {} declined, cancelling sequence", index + 1);
+            *state_clone.cancelled.borrow_mut() = true;
+        }
+        if *state_clone.cancelled.borrow() {
+            // Ends the run at this step
+            run_next(state_clone.clone());
+            return;
+        }
+
+        start_command(state_clone.clone(), index, 0);
+    });
+
+    *state.pending_confirmation.borrow_mut() = Some(dialog.clone());
+    dialog.present(Some(&state.widgets.window));
+}
+
+/// Spawn the command at `index` and stream its output.
+fn start_command(state: Rc<ExecutionState>, index: usize, attempt: u32) {
+    let widgets = &state.widgets;
+    let cmd = &state.commands[index];
 
     // Mark current task as running
-    widgets.update_task_status(index, TaskStatus::Running);
+    state.set_task_status(index, TaskStatus::Running);
     widgets.set_title(&cmd.description);
 
-    let (program, args) = match resolve_command(cmd) {
+    let (program, args, script) = match prepare_command(cmd) {
         Ok(result) => result,
         Err(err) => {
             error!("Failed to prepare command: {}", err);
             let error_msg = format!("Failed to prepare command: {}\n", err);
             widgets.append_colored(&error_msg, "error");
-            widgets.update_task_status(index, TaskStatus::Failed);
+            state.record_step(
+                index,
+                CommandResult::Failure {
+                    exit_code: None,
+                    signal: None,
+                },
+                String::new(),
+                error_msg,
+            );
+            state.set_task_status(index, TaskStatus::Failed);
             finalize_execution(
-                &widgets,
+                &state,
                 false,
                 &format!("Failed to prepare command: {}", err),
             );
@@ -163,49 +730,71 @@ pub fn execute_commands(
         }
     };
 
-    info!("Executing: {} {:?}", program, args);
+    // Dry run: show what would be executed and move on without spawning anything
+    if state.options.dry_run {
+        info!("Dry run: {} {:?}", program, args);
+        widgets.append_command_header(&cmd.description);
+        widgets.append_colored(
+            &format!("$ {}\n", format_command_line(&program, &args)),
+            "stdout",
+        );
+        if let Some(body) = &cmd.script {
+            widgets.append_colored(&format!("{}\n", body.trim_end()), "stdout");
+        }
+        state.record_step(index, CommandResult::Success, String::new(), String::new());
+        state.set_task_status(index, TaskStatus::Success);
+        state.sequence.borrow_mut().advance();
 
-    // Use std::process for real-time output streaming
-    use std::process::{Command, Stdio};
-    use std::sync::Arc;
-    use std::thread;
+        let state = state.clone();
+        glib::idle_add_local_once(move || run_next(state));
+        return;
+    }
+
+    info!("Executing: {} {:?}", program, args);
+    core::audit::log_command_start(&cmd.description, &format_command_line(&program, &args));
 
     // Create context for this command
-    let context = RunningContext::new(
-        widgets.clone(),
-        commands.clone(),
-        index,
-        cancelled.clone(),
-        current_process.clone(),
-    );
+    let context = RunningContext::new(state.clone(), index, attempt);
+    *context.script.borrow_mut() = script;
 
     // Display command header
     widgets.append_command_header(&cmd.description);
+    // Record the resolved command in the log only
+    widgets.write_log(&format!("$ {}\n", format_command_line(&program, &args)));
 
-    let mut process = Command::new(&program);
-    process.args(&args);
-
-    // Inject sudo shim to intercept sudo calls in scripts
-    let scripts_dir = crate::config::paths::scripts();
-    if scripts_dir.exists() {
-        if let Ok(path) = std::env::var("PATH") {
-            let new_path = format!("{}:{}", scripts_dir.display(), path);
-            process.env("PATH", new_path);
-        }
-    }
-
-    process.stdout(Stdio::piped());
-    process.stderr(Stdio::piped());
+    let interactive = cmd.is_interactive();
+    let request = SpawnRequest {
+        program: &program,
+        args: &args,
+        // Extra environment for normal and AUR commands; privileged ones get it via the backend
+        env: &cmd.env,
+        // Inject sudo shim to intercept sudo calls in scripts
+        path: shim_path(),
+        cwd: cmd.cwd.as_deref(),
+        stdin: interactive,
+        // Lead a new process group so pausing reaches the command's children
+        process_group: true,
+    };
 
-    let child = match process.spawn() {
-        Ok(child) => child,
+    let mut process = match state.spawner.spawn(&request) {
+        Ok(process) => process,
         Err(err) => {
             error!("Failed to start command: {}", err);
             let error_msg = format!("Failed to start operation: {}\n", err);
             widgets.append_colored(&error_msg, "error");
-            widgets.update_task_status(index, TaskStatus::Failed);
+            core::audit::log_command_result(&cmd.description, None);
+            state.record_step(
+                index,
+                CommandResult::Failure {
+                    exit_code: None,
+                    signal: None,
+                },
+                String::new(),
+                error_msg,
+            );
+            state.set_task_status(index, TaskStatus::Failed);
             finalize_execution(
-                &widgets,
+                &state,
                 false,
                 &format!("Failed to start operation: {}", err),
             );
@@ -213,145 +802,57 @@ pub fn execute_commands(
         }
     };
 
-    // Store child process for cancellation
-    use std::sync::Mutex;
-    let child_arc = Arc::new(Mutex::new(Some(child)));
-    *current_process.borrow_mut() = None; // Clear gio subprocess reference
-
-    // Set up result storage
-    let result_arc: Arc<Mutex<Option<CommandResult>>> = Arc::new(Mutex::new(None));
-
-    // Set up real-time output streaming using channels
-    use std::sync::mpsc;
-    let (stdout_tx, stdout_rx) = mpsc::channel();
-    let (stderr_tx, stderr_rx) = mpsc::channel();
-
-    // Spawn thread to read stdout
-    let stdout_handle = child_arc
-        .lock()
-        .unwrap()
-        .as_mut()
-        .and_then(|c| c.stdout.take())
-        .map(|stdout| {
-            thread::spawn(move || {
-                read_buffer_with_line_processing(
-                    stdout,
-                    |text| match stdout_tx.send(text) {
-                        Ok(()) => true,
-                        Err(e) => {
-                            warn!("Failed to send stdout chunk to channel: {}", e);
-                            false
-                        }
-                    },
-                    |e| {
-                        warn!("Error reading stdout: {}", e);
-                    },
-                );
-            })
-        });
-
-    // Spawn thread to read stderr
-    let stderr_handle = child_arc
-        .lock()
-        .unwrap()
-        .as_mut()
-        .and_then(|c| c.stderr.take())
-        .map(|stderr| {
-            thread::spawn(move || {
-                read_buffer_with_line_processing(
-                    stderr,
-                    |text| match stderr_tx.send(text) {
-                        Ok(()) => true,
-                        Err(e) => {
-                            warn!("Failed to send stderr chunk to channel: {}", e);
-                            false
-                        }
-                    },
-                    |e| {
-                        warn!("Error reading stderr: {}", e);
-                    },
-                );
-            })
-        });
-
-    // Process output in main thread
-    let widgets_stdout = widgets.clone();
-    let widgets_stderr = widgets.clone();
-    let result_arc_for_output = result_arc.clone();
-    glib::timeout_add_local(std::time::Duration::from_millis(50), move || {
-        // Process stdout
-        while let Ok(text) = stdout_rx.try_recv() {
-            let cleaned_text = strip_ansi_escapes::strip_str(&text);
-            // Text already includes newline from buffer processing
-            widgets_stdout.append_colored(&cleaned_text, "stdout");
-        }
-        // Process stderr
-        while let Ok(text) = stderr_rx.try_recv() {
-            let cleaned_text = strip_ansi_escapes::strip_str(&text);
-            // Text already includes newline from buffer processing
-            widgets_stderr.append_colored(&cleaned_text, "stderr");
-        }
-        // Stop if result is ready
-        if result_arc_for_output.lock().unwrap().is_some() {
-            glib::ControlFlow::Break
-        } else {
-            glib::ControlFlow::Continue
-        }
-    });
+    // Store the process ID for cancellation
+    info!("Started process {}", process.id());
+    context.pid.set(Some(process.id()));
+    *context.stdin.borrow_mut() = process.take_stdin();
+    *state.current_process.borrow_mut() = Some(context.clone());
+    widgets.set_pause_available(true);
+    widgets.set_input_available(interactive);
+    widgets.start_timer();
 
-    // Wait for process to complete in a separate thread
-    let result_arc_clone = result_arc.clone();
+    // Flush output and check for the result in the main thread
+    let watch = ProcessWatch::start(process);
+    let context_clone = context.clone();
+    glib::timeout_add_local(OUTPUT_FLUSH_INTERVAL, move || {
+        // A result only comes with the last of the output, see `ProcessWatch::poll`
+        let polled = watch.poll();
+        context_clone.flush_output(polled.stdout, &polled.stderr);
 
-    thread::spawn(move || {
-        // Wait for output threads to finish
-        if let Some(handle) = stdout_handle {
-            if let Err(e) = handle.join() {
-                warn!("Error joining stdout reader thread: {:?}", e);
+        match polled.result {
+            Some(result) => {
+                context_clone.set_exit_result(result);
+                glib::ControlFlow::Break
             }
-        }
-        if let Some(handle) = stderr_handle {
-            if let Err(e) = handle.join() {
-                warn!("Error joining stderr reader thread: {:?}", e);
-            }
-        }
-
-        // Wait for process
-        let mut child_guard = child_arc.lock().unwrap();
-        if let Some(mut child) = child_guard.take() {
-            let result = match child.wait() {
-                Ok(status) => {
-                    if status.success() {
-                        CommandResult::Success
-                    } else {
-                        CommandResult::Failure {
-                            exit_code: status.code(),
-                        }
-                    }
-                }
-                Err(e) => {
-                    error!("Error waiting for process: {}", e);
-                    CommandResult::Failure { exit_code: None }
-                }
-            };
-            *result_arc_clone.lock().unwrap() = Some(result);
+            None => glib::ControlFlow::Continue,
         }
     });
+}
 
-    // Check for result in main thread
-    let context_clone = context.clone();
-    glib::timeout_add_local(std::time::Duration::from_millis(100), move || {
-        let mut result_guard = result_arc.lock().unwrap();
-        if let Some(result) = result_guard.take() {
-            context_clone.set_exit_result(result);
-            glib::ControlFlow::Break
-        } else {
-            glib::ControlFlow::Continue
-        }
-    });
+/// Resolve a command, writing the body of a script step to disk first.
+///
+/// The script path becomes the first argument of `program`. The returned
+/// [`ScriptFile`] deletes the script when dropped, so it must be kept until
+/// the command has exited.
+pub(super) fn prepare_command(
+    command: &Command,
+) -> Result<(String, Vec<String>, Option<ScriptFile>), String> {
+    let Some(body) = &command.script else {
+        let (program, args) = resolve_command(command)?;
+        return Ok((program, args, None));
+    };
+
+    let script = ScriptFile::create(body).map_err(|e| format!("Failed to write script: {}", e))?;
+    let mut command = command.clone();
+    command
+        .args
+        .insert(0, script.path().to_string_lossy().into_owned());
+    let (program, args) = resolve_command(&command)?;
+    Ok((program, args, Some(script)))
 }
 
 /// Resolve command to executable program and arguments,
-/// handling privilege escalation (pkexec) and AUR helper detection.
+/// handling privilege escalation and AUR helper detection.
 ///
 /// # Returns
 ///
@@ -360,45 +861,172 @@ pub fn execute_commands(
 ///
 /// # Errors
 ///
-/// Returns an error if the AUR helper is required but not available.
-fn resolve_command(command: &Command) -> Result<(String, Vec<String>), String> {
-    // Prepare PATH with scripts directory for sudo shim
-    let scripts_dir = crate::config::paths::scripts();
-    let shim_path_env = if scripts_dir.exists() {
-        if let Ok(path) = std::env::var("PATH") {
-            Some(format!("PATH={}:{}", scripts_dir.display(), path))
-        } else {
-            None
+/// Returns an error if the AUR helper is required but not available, if
+/// the selected privilege backend is not installed, if the working
+/// directory does not exist, or if the `run_as` user does not exist or
+/// can't be switched to.
+pub(super) fn resolve_command(command: &Command) -> Result<(String, Vec<String>), String> {
+    if let Some(cwd) = &command.cwd {
+        if !cwd.is_dir() {
+            return Err(format!(
+                "Working directory {} does not exist",
+                cwd.display()
+            ));
         }
-    } else {
-        None
-    };
+    }
 
-    match command.command_type {
-        CommandType::Normal => Ok((command.program.clone(), command.args.clone())),
+    if let Some(user) = &command.run_as {
+        if !core::privilege::user_exists(user) {
+            return Err(format!("User '{}' does not exist", user));
+        }
+    }
+
+    let backend = core::privilege::get();
+    if matches!(
+        command.command_type,
+        CommandType::Privileged | CommandType::Aur
+    ) {
+        backend.validate()?;
+    }
+
+    resolve_with(
+        command,
+        backend,
+        core::aur_helper(),
+        crate::config::user::get().interactive,
+        core::privilege::current_user().as_deref(),
+    )
+}
+
+/// Resolve a command for the given privilege backend, AUR helper and user.
+///
+/// Does not check that the backend is installed or that the `run_as` user
+/// exists; see [`resolve_command`].
+fn resolve_with(
+    command: &Command,
+    backend: core::privilege::PrivilegeBackend,
+    aur_helper: Option<&str>,
+    interactive: bool,
+    current_user: Option<&str>,
+) -> Result<(String, Vec<String>), String> {
+    let resolved = match command.command_type {
+        CommandType::Normal | CommandType::Flatpak => {
+            (command.program.clone(), command.args.clone())
+        }
         CommandType::Privileged => {
+            // Escalate first, then drop to the requested user
+            let (program, command_args) = match &command.run_as {
+                Some(user) => run_as_user(user, command.program.clone(), command.args.clone()),
+                None => (command.program.clone(), command.args.clone()),
+            };
+
+            if !backend.uses_daemon() {
+                let mut args: Vec<String> = backend.flags().iter().map(|s| s.to_string()).collect();
+                args.extend(backend.cwd_args(command.cwd.as_deref()));
+                args.extend(backend.env_args(&command.env));
+                args.push(program);
+                args.extend(command_args);
+                return Ok((backend.binary().to_string(), args));
+            }
+
             // Use xero-auth client instead of pkexec for better session reuse
             let mut args = Vec::new();
 
-            // Pass PATH via --env if available
-            if let Some(env) = &shim_path_env {
+            // Pass PATH with the scripts directory for the sudo shim via --env if available
+            if let Some(path) = shim_path() {
                 args.push("--env".to_string());
-                args.push(env.clone());
+                args.push(format!("PATH={}", path));
             }
+            args.extend(backend.cwd_args(command.cwd.as_deref()));
+            args.extend(backend.env_args(&command.env));
 
-            args.push(command.program.clone());
-            args.extend(command.args.clone());
-            Ok((get_xero_auth_path().to_string_lossy().to_string(), args))
+            args.push(program);
+            args.extend(command_args);
+            return Ok((get_xero_auth_path().to_string_lossy().to_string(), args));
         }
         CommandType::Aur => {
-            let helper = core::aur_helper()
-                .ok_or_else(|| "AUR helper not available (paru or yay required)".to_string())?;
-            let mut args = Vec::with_capacity(command.args.len() + 2);
-            args.push("--sudo".to_string());
-            args.push(get_xero_auth_path().to_string_lossy().to_string());
-            args.extend(command.args.clone());
-            Ok((helper.to_string(), args))
+            let helper = aur_helper.ok_or_else(|| {
+                "No AUR helper installed (paru, yay, pikaur or aura required). \
+                Install one with the Install AUR Helper button in the sidebar."
+                    .to_string()
+            })?;
+
+            let sudo = if backend.uses_daemon() {
+                get_xero_auth_path().to_string_lossy().to_string()
+            } else {
+                backend.binary().to_string()
+            };
+            let mut args = core::aur::sudo_args(helper, &sudo, backend.flags());
+            // In interactive mode the user answers the helper's prompts
+            let command_args = if interactive {
+                core::aur::strip_noconfirm(&command.args)
+            } else {
+                command.args.clone()
+            };
+            args.extend(core::aur::translate_args(helper, &command_args));
+            (helper.to_string(), args)
         }
+    };
+
+    match command.run_as.as_deref() {
+        None => Ok(resolved),
+        Some(user) if current_user == Some(user) => Ok(resolved),
+        Some(user) if current_user == Some("root") => Ok(run_as_user(user, resolved.0, resolved.1)),
+        Some(user) => Err(format!(
+            "Running as user '{}' requires the toolkit to run as root",
+            user
+        )),
+    }
+}
+
+/// Wrap a command line so that `runuser` runs it as `user`.
+fn run_as_user(user: &str, program: String, args: Vec<String>) -> (String, Vec<String>) {
+    let mut wrapped = vec![
+        "-u".to_string(),
+        user.to_string(),
+        "--".to_string(),
+        program,
+    ];
+    wrapped.extend(args);
+    ("runuser".to_string(), wrapped)
+}
+
+/// PATH with the scripts directory prepended, so scripts calling `sudo` hit the shim.
+///
+/// Returns None if the scripts directory does not exist or the shim is not
+/// used by the selected privilege backend.
+pub(super) fn shim_path() -> Option<String> {
+    let scripts_dir = crate::config::paths::scripts();
+    if !scripts_dir.exists() || !core::privilege::get().uses_daemon() {
+        return None;
+    }
+    std::env::var("PATH")
+        .ok()
+        .map(|path| format!("{}:{}", scripts_dir.display(), path))
+}
+
+/// Format a program and its arguments as a copy-pasteable shell command line.
+///
+/// Arguments containing whitespace or shell metacharacters are single-quoted.
+pub fn format_command_line(program: &str, args: &[String]) -> String {
+    std::iter::once(program)
+        .chain(args.iter().map(String::as_str))
+        .map(quote_argument)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Quote a single argument for display if the shell would otherwise split or expand it.
+fn quote_argument(arg: &str) -> String {
+    let needs_quoting = arg.is_empty()
+        || arg
+            .chars()
+            .any(|c| c.is_whitespace() || "'\"\\$`!*?&|;<>(){}[]#~".contains(c));
+
+    if needs_quoting {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    } else {
+        arg.to_string()
     }
 }
 
@@ -411,12 +1039,21 @@ fn stop_daemon_if_needed() {
 }
 
 /// Finalize dialog with success or failure message.
-pub fn finalize_execution(widgets: &TaskRunnerWidgets, success: bool, message: &str) {
-    use std::sync::atomic::Ordering;
+pub fn finalize_execution(state: &ExecutionState, success: bool, message: &str) {
+    let widgets = &state.widgets;
 
     // Stop daemon before finalizing
     stop_daemon_if_needed();
 
+    // Installs and removals change what is installed
+    core::package::refresh_cache();
+
+    let verification = if success && !state.options.dry_run {
+        state.options.verify.as_ref().map(|verify| verify())
+    } else {
+        None
+    };
+
     // Print final message to terminal
     if success {
         let success_msg = format!("\n{}\n", message);
@@ -425,7 +1062,330 @@ pub fn finalize_execution(widgets: &TaskRunnerWidgets, success: bool, message: &
         let error_msg = format!("\n{}\n", message);
         widgets.append_colored(&error_msg, "error");
     }
+    match &verification {
+        Some(Ok(())) => widgets.append_colored("Verification passed\n", "stdout"),
+        Some(Err(reason)) => {
+            warn!("Verification failed: {}", reason);
+            widgets.append_colored(&format!("Verification failed: {}\n", reason), "warning");
+        }
+        None => {}
+    }
 
-    super::ACTION_RUNNING.store(false, Ordering::SeqCst);
+    state.release_action();
+    widgets.stop_timer();
     widgets.show_completion(success, message);
+    if let Some(Err(reason)) = &verification {
+        widgets.show_verification_failure(reason);
+    }
+    notify_completion(&widgets.window, success, message);
+
+    // Cancelled runs stopped on purpose, failed ones can resume at the failed step
+    let failed_step = if success || *state.cancelled.borrow() {
+        None
+    } else {
+        state
+            .step_reports
+            .borrow()
+            .iter()
+            .position(|step| step.as_ref().is_some_and(|step| !step.success()))
+    };
+    state.failed_step.set(failed_step);
+    if failed_step.is_some() {
+        widgets.offer_retry();
+    }
+
+    let report = CommandRunReport {
+        steps: state
+            .step_reports
+            .borrow()
+            .iter()
+            .flatten()
+            .cloned()
+            .collect(),
+        success,
+    };
+    super::set_last_run(super::LastRun {
+        title: widgets.window.title().unwrap_or_default().to_string(),
+        output: widgets.output_text(),
+        report: report.clone(),
+    });
+    if let Some(on_complete) = &state.options.on_complete {
+        on_complete(&report);
+    }
+    super::action_finished(success);
+}
+
+/// Send a desktop notification about a finished run.
+///
+/// Skipped while the task window is focused or when disabled in the user
+/// config. Activating the notification brings the task window back, and the
+/// notification is withdrawn once the window is focused or closed.
+fn notify_completion(window: &Window, success: bool, message: &str) {
+    if !crate::config::user::get().notify_on_completion || window.is_active() {
+        return;
+    }
+    let Some(app) = gio::Application::default() else {
+        return;
+    };
+
+    let id = format!("task-finished-{:016x}", rand::random::<u64>());
+    let action = gio::SimpleAction::new(&id, None);
+    let window_weak = window.downgrade();
+    action.connect_activate(move |_, _| {
+        if let Some(window) = window_weak.upgrade() {
+            window.present();
+        }
+    });
+    app.add_action(&action);
+
+    let title = window.title().unwrap_or_default();
+    let outcome = if success { "finished" } else { "failed" };
+    let notification = gio::Notification::new(&format!("{} {}", title, outcome));
+    notification.set_body(Some(message));
+    notification.set_default_action(&format!("app.{}", id));
+    notification.add_button("Show Output", &format!("app.{}", id));
+    app.send_notification(Some(&id), &notification);
+
+    let app_clone = app.clone();
+    let id_clone = id.clone();
+    window.connect_is_active_notify(move |window| {
+        if window.is_active() {
+            app_clone.withdraw_notification(&id_clone);
+        }
+    });
+    window.connect_destroy(move |_| {
+        app.withdraw_notification(&id);
+        app.remove_action(&id);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::privilege::PrivilegeBackend;
+
+    /// Resolve with the sudo backend and paru, running as alice.
+    fn resolve(command: &Command, interactive: bool) -> (String, Vec<String>) {
+        resolve_with(
+            command,
+            PrivilegeBackend::Sudo,
+            Some("paru"),
+            interactive,
+            Some("alice"),
+        )
+        .unwrap()
+    }
+
+    fn privileged_command() -> Command {
+        Command::builder()
+            .privileged()
+            .program("systemctl")
+            .args(&["enable", "cups.socket"])
+            .description("Enabling")
+            .build()
+    }
+
+    fn aur_command() -> Command {
+        Command::builder()
+            .aur()
+            .args(&["-S", "--noconfirm", "steam"])
+            .description("Installing")
+            .build()
+    }
+
+    #[test]
+    fn test_resolve_unescalated_commands() {
+        let normal = Command::builder()
+            .normal()
+            .program("ls")
+            .args(&["-la"])
+            .description("Listing")
+            .build();
+        assert_eq!(
+            resolve(&normal, false),
+            ("ls".to_string(), vec!["-la".to_string()])
+        );
+
+        let flatpak = Command::flatpak(&["install", "--user", "-y", "flathub", "app.id"], "App");
+        let (program, args) = resolve(&flatpak, false);
+        assert_eq!(program, "flatpak");
+        assert_eq!(args, ["install", "--user", "-y", "flathub", "app.id"]);
+    }
+
+    #[test]
+    fn test_resolve_privileged_command() {
+        let (program, args) = resolve(&privileged_command(), false);
+        assert_eq!(program, "sudo");
+        assert_eq!(args, ["-A", "systemctl", "enable", "cups.socket"]);
+
+        let command = Command::builder()
+            .privileged()
+            .program("make")
+            .args(&["install"])
+            .in_dir("/tmp")
+            .with_env("DESTDIR", "/")
+            .description("Installing")
+            .build();
+        let (program, args) =
+            resolve_with(&command, PrivilegeBackend::Run0, None, false, Some("alice")).unwrap();
+        assert_eq!(program, "run0");
+        assert_eq!(
+            args,
+            ["--chdir=/tmp", "--setenv=DESTDIR=/", "make", "install"]
+        );
+
+        // The daemon client gets the command after its own options
+        let (program, args) = resolve_with(
+            &command,
+            PrivilegeBackend::Pkexec,
+            None,
+            false,
+            Some("alice"),
+        )
+        .unwrap();
+        assert_eq!(PathBuf::from(program), get_xero_auth_path());
+        assert!(args.ends_with(&[
+            "--cwd".to_string(),
+            "/tmp".to_string(),
+            "--env".to_string(),
+            "DESTDIR=/".to_string(),
+            "make".to_string(),
+            "install".to_string(),
+        ]));
+    }
+
+    #[test]
+    fn test_resolve_aur_command() {
+        let aur = aur_command();
+        let (program, args) = resolve(&aur, false);
+        assert_eq!(program, "paru");
+        assert_eq!(
+            args,
+            [
+                "--sudo",
+                "sudo",
+                "--sudoflags",
+                "-A",
+                "-S",
+                "--noconfirm",
+                "steam"
+            ]
+        );
+        assert_eq!(
+            resolve(&aur, true).1,
+            ["--sudo", "sudo", "--sudoflags", "-A", "-S", "steam"]
+        );
+
+        // The daemon client stands in for sudo
+        let (_, args) = resolve_with(
+            &aur,
+            PrivilegeBackend::Pkexec,
+            Some("yay"),
+            false,
+            Some("alice"),
+        )
+        .unwrap();
+        assert_eq!(args[0], "--sudo");
+        assert_eq!(PathBuf::from(&args[1]), get_xero_auth_path());
+
+        let (program, args) = resolve_with(
+            &aur,
+            PrivilegeBackend::Sudo,
+            Some("aura"),
+            false,
+            Some("alice"),
+        )
+        .unwrap();
+        assert_eq!(program, "aura");
+        assert_eq!(args, ["-A", "--noconfirm", "steam"]);
+    }
+
+    #[test]
+    fn test_resolve_run_as_command() {
+        let command = Command::builder()
+            .normal()
+            .program("makepkg")
+            .args(&["-si"])
+            .description("Building")
+            .run_as("alice")
+            .build();
+
+        // Already running as that user
+        assert_eq!(resolve(&command, false).0, "makepkg");
+
+        let (program, args) =
+            resolve_with(&command, PrivilegeBackend::Sudo, None, false, Some("root")).unwrap();
+        assert_eq!(program, "runuser");
+        assert_eq!(args, ["-u", "alice", "--", "makepkg", "-si"]);
+
+        assert!(resolve_with(&command, PrivilegeBackend::Sudo, None, false, Some("bob")).is_err());
+
+        // Privileged commands drop to the user after escalating
+        let command = Command::builder()
+            .privileged()
+            .program("makepkg")
+            .args(&["-si"])
+            .description("Building")
+            .run_as("alice")
+            .build();
+        let (program, args) =
+            resolve_with(&command, PrivilegeBackend::Sudo, None, false, Some("bob")).unwrap();
+        assert_eq!(program, "sudo");
+        assert_eq!(
+            args,
+            ["-A", "runuser", "-u", "alice", "--", "makepkg", "-si"]
+        );
+    }
+
+    #[test]
+    fn test_resolve_aur_command_without_helper() {
+        assert!(resolve_with(
+            &aur_command(),
+            PrivilegeBackend::Sudo,
+            None,
+            false,
+            Some("alice")
+        )
+        .is_err());
+        // Other command types don't need a helper
+        assert!(resolve_with(
+            &privileged_command(),
+            PrivilegeBackend::Sudo,
+            None,
+            false,
+            Some("alice")
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_install_steps_skip_when_satisfied() {
+        fn installed(packages: &[&str]) -> bool {
+            packages
+                .iter()
+                .all(|package| ["steam", "lib32-mesa"].contains(package))
+        }
+
+        let install = Command::install(&["steam", "lib32-mesa"], "Installing Steam");
+        assert_eq!(install.skip_if_installed, vec!["steam", "lib32-mesa"]);
+        assert!(install.is_satisfied_with(installed));
+        // Partly installed steps still run
+        let partial = Command::install(&["steam", "gamescope"], "Installing Gamescope");
+        assert!(!partial.is_satisfied_with(installed));
+        // Steps without packages to check always run
+        assert!(!privileged_command().is_satisfied_with(|_| true));
+    }
+
+    #[test]
+    fn test_format_command_line_quotes_only_when_needed() {
+        let args = vec![
+            "-S".to_string(),
+            "--needed".to_string(),
+            "echo 'hi' | bash".to_string(),
+        ];
+        assert_eq!(
+            format_command_line("paru", &args),
+            "paru -S --needed 'echo '\\''hi'\\'' | bash'"
+        );
+    }
 }
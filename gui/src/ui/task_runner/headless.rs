@@ -0,0 +1,351 @@
+//! Headless command execution without any GTK dependency.
+//!
+//! Runs a command sequence to completion on the calling thread and returns a
+//! report with the result and captured output of every step. Useful for tests
+//! and command-line tooling. Command resolution is shared with the GTK runner.
+
+use super::command::{Command, CommandResult, CommandRunReport, CommandType, StepReport};
+use super::executor::{format_command_line, prepare_command, shim_path, SKIPPED_MESSAGE};
+use super::sequence::{Next, Sequence, StepOutcome};
+use super::spawner::{run_process, CommandSpawner, SpawnRequest, SystemSpawner};
+use crate::core;
+use log::{error, info, warn};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+
+/// Run commands sequentially without a UI and collect their results.
+///
+/// Retries, allowed failures, environment variables and working directories
+/// behave as in the GTK runner. The xero-auth daemon is started if any step
+/// needs it and stopped again afterwards.
+pub fn run_commands_headless(commands: Vec<Command>) -> CommandRunReport {
+    run_commands_headless_with(commands, &SystemSpawner, &AtomicBool::new(false))
+}
+
+/// Run commands like [`run_commands_headless`], starting processes with
+/// `spawner`.
+///
+/// Setting `cancelled` stops the run before the next step or retry; the
+/// step running at that moment is finished first and the run counts as
+/// failed.
+pub fn run_commands_headless_with(
+    commands: Vec<Command>,
+    spawner: &dyn CommandSpawner,
+    cancelled: &AtomicBool,
+) -> CommandRunReport {
+    let needs_daemon = core::privilege::get().uses_daemon()
+        && commands
+            .iter()
+            .any(|cmd| matches!(cmd.command_type, CommandType::Privileged | CommandType::Aur));
+
+    if needs_daemon {
+        if let Err(e) = core::daemon::start_daemon() {
+            error!("Failed to start daemon: {}", e);
+            return CommandRunReport::default();
+        }
+    }
+
+    let mut report = CommandRunReport {
+        steps: Vec::with_capacity(commands.len()),
+        success: true,
+    };
+
+    let mut sequence = Sequence::new(0);
+    // Failed attempt waiting to be retried, reported if the run is cancelled first
+    let mut retried = None;
+    loop {
+        let next = sequence.next(
+            &commands,
+            cancelled.load(Ordering::SeqCst),
+            Command::is_satisfied,
+        );
+        match next {
+            Next::Skip(index) => {
+                let cmd = &commands[index];
+                info!("Skipping satisfied step: {}", cmd.description);
+                report.steps.push(StepReport {
+                    description: cmd.description.clone(),
+                    result: CommandResult::Success,
+                    stdout: SKIPPED_MESSAGE.to_string(),
+                    stderr: String::new(),
+                });
+            }
+            Next::Run { index, attempt } => {
+                let cmd = &commands[index];
+                let step = run_attempt(cmd, spawner);
+                retried = None;
+
+                match sequence.finished(&commands, &step.result, cancelled.load(Ordering::SeqCst)) {
+                    StepOutcome::Retry(delay) => {
+                        warn!(
+                            "Command '{}' failed, retrying ({}/{}) in {:?}",
+                            cmd.description,
+                            attempt + 1,
+                            cmd.retries,
+                            delay
+                        );
+                        retried = Some(step);
+                        std::thread::sleep(delay);
+                    }
+                    StepOutcome::Continue(_) => report.steps.push(step),
+                    StepOutcome::Cancelled | StepOutcome::Failed => {
+                        report.steps.push(step);
+                        report.success = false;
+                        break;
+                    }
+                }
+            }
+            Next::Done => break,
+            Next::Cancelled(index) => {
+                if let Some(index) = index {
+                    info!("Run cancelled before step: {}", commands[index].description);
+                }
+                report.steps.extend(retried.take());
+                report.success = false;
+                break;
+            }
+        }
+    }
+
+    if needs_daemon {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        if let Err(e) = rt.block_on(core::daemon::stop_daemon()) {
+            error!("Failed to stop daemon: {}", e);
+        }
+    }
+
+    report
+}
+
+/// Spawn the command once and wait for it, capturing its output.
+fn run_attempt(cmd: &Command, spawner: &dyn CommandSpawner) -> StepReport {
+    let failed = |stderr: String| StepReport {
+        description: cmd.description.clone(),
+        result: CommandResult::Failure {
+            exit_code: None,
+            signal: None,
+        },
+        stdout: String::new(),
+        stderr,
+    };
+
+    // Keeps the file of a script step until the process has exited
+    let (program, args, _script) = match prepare_command(cmd) {
+        Ok(resolved) => resolved,
+        Err(err) => return failed(format!("Failed to prepare command: {}", err)),
+    };
+
+    info!("Executing (headless): {} {:?}", program, args);
+    core::audit::log_command_start(&cmd.description, &format_command_line(&program, &args));
+
+    let request = SpawnRequest {
+        program: &program,
+        args: &args,
+        env: &cmd.env,
+        path: shim_path(),
+        cwd: cmd.cwd.as_deref(),
+        stdin: false,
+        process_group: false,
+    };
+    let process = match spawner.spawn(&request) {
+        Ok(process) => process,
+        Err(err) => {
+            core::audit::log_command_result(&cmd.description, None);
+            return failed(format!("Failed to start operation: {}", err));
+        }
+    };
+
+    let (stdout_tx, stdout_rx) = mpsc::channel();
+    let (stderr_tx, stderr_rx) = mpsc::channel();
+    let result = run_process(
+        process,
+        move |text| stdout_tx.send(text).is_ok(),
+        move |text| stderr_tx.send(text).is_ok(),
+    );
+    let exit_code = match &result {
+        CommandResult::Success => Some(0),
+        CommandResult::Failure { exit_code, .. } => *exit_code,
+    };
+    core::audit::log_command_result(&cmd.description, exit_code);
+
+    StepReport {
+        description: cmd.description.clone(),
+        result,
+        stdout: stdout_rx.try_iter().collect(),
+        stderr: stderr_rx.try_iter().collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::spawner::fake::{FakeScript, FakeSpawner};
+    use super::*;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    fn step(program: &str) -> Command {
+        Command::builder()
+            .normal()
+            .program(program)
+            .description(program)
+            .build()
+    }
+
+    #[test]
+    fn test_fake_steps_success_and_failure() {
+        let spawner = FakeSpawner::new([
+            FakeScript::exit(0).stdout(&["one"]),
+            FakeScript::exit(0).stdout(&["two"]),
+        ]);
+        let report = run_commands_headless_with(
+            vec![step("first"), step("second")],
+            &spawner,
+            &AtomicBool::new(false),
+        );
+        assert!(report.success);
+        assert_eq!(report.steps[1].stdout, "two\n");
+        assert_eq!(*spawner.spawned.borrow(), ["first", "second"]);
+
+        // A failing step aborts the sequence
+        let spawner = FakeSpawner::new([
+            FakeScript::exit(0),
+            FakeScript::exit(2).stderr("error: target not found\n"),
+            FakeScript::exit(0),
+        ]);
+        let report = run_commands_headless_with(
+            vec![step("first"), step("second"), step("third")],
+            &spawner,
+            &AtomicBool::new(false),
+        );
+        assert!(!report.success);
+        assert_eq!(report.steps.len(), 2);
+        assert_eq!(report.steps[1].stderr, "error: target not found\n");
+        assert_eq!(*spawner.spawned.borrow(), ["first", "second"]);
+    }
+
+    #[test]
+    fn test_fake_steps_retry_and_allowed_failure() {
+        let flaky = Command::builder()
+            .normal()
+            .program("flaky")
+            .description("flaky")
+            .retries(2)
+            .retry_delay(Duration::ZERO)
+            .build();
+        let optional = Command::builder()
+            .normal()
+            .program("optional")
+            .description("optional")
+            .allow_failure()
+            .build();
+        let spawner = FakeSpawner::new([
+            FakeScript::exit(1),
+            FakeScript::exit(0).stdout(&["done"]),
+            FakeScript::exit(1),
+            FakeScript::exit(0),
+        ]);
+
+        let report = run_commands_headless_with(
+            vec![flaky, optional, step("last")],
+            &spawner,
+            &AtomicBool::new(false),
+        );
+        assert!(report.success);
+        // Only the last attempt of a retried step is reported
+        assert_eq!(report.steps.len(), 3);
+        assert_eq!(report.steps[0].stdout, "done\n");
+        assert!(!report.steps[1].success());
+        assert_eq!(
+            *spawner.spawned.borrow(),
+            ["flaky", "flaky", "optional", "last"]
+        );
+    }
+
+    #[test]
+    fn test_fake_steps_cancelled_mid_run() {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let cancelled_clone = cancelled.clone();
+        let spawner = FakeSpawner::new([
+            FakeScript::exit(0),
+            FakeScript::exit(0).on_spawn(move || cancelled_clone.store(true, Ordering::SeqCst)),
+            FakeScript::exit(0),
+        ]);
+
+        let report = run_commands_headless_with(
+            vec![step("first"), step("second"), step("third")],
+            &spawner,
+            &cancelled,
+        );
+        assert!(!report.success);
+        // The running step finishes, the next one never starts
+        assert_eq!(report.steps.len(), 2);
+        assert!(report.steps[1].success());
+        assert_eq!(*spawner.spawned.borrow(), ["first", "second"]);
+    }
+
+    #[test]
+    fn test_run_commands_headless_stops_at_failure() {
+        let commands = vec![
+            Command::builder()
+                .normal()
+                .program("echo")
+                .args(&["hello"])
+                .description("Echo")
+                .build(),
+            Command::builder()
+                .normal()
+                .program("false")
+                .description("Optional failure")
+                .allow_failure()
+                .build(),
+            Command::builder()
+                .normal()
+                .program("false")
+                .description("Failure")
+                .build(),
+            Command::builder()
+                .normal()
+                .program("true")
+                .description("Never run")
+                .build(),
+        ];
+
+        let report = run_commands_headless(commands);
+        assert!(!report.success);
+        assert_eq!(report.steps.len(), 3);
+        assert_eq!(report.steps[0].stdout, "hello\n");
+        assert!(!report.steps[1].success());
+        assert!(matches!(
+            report.steps[2].result,
+            CommandResult::Failure {
+                exit_code: Some(1),
+                signal: None
+            }
+        ));
+    }
+
+    #[test]
+    fn test_run_commands_headless_reports_signal() {
+        let commands = vec![Command::builder()
+            .normal()
+            .program("sh")
+            .args(&["-c", "kill -SEGV $$"])
+            .description("Crash")
+            .build()];
+
+        let report = run_commands_headless(commands);
+        assert!(!report.success);
+        let result = &report.steps[0].result;
+        assert!(matches!(
+            result,
+            CommandResult::Failure {
+                exit_code: None,
+                signal: Some(libc::SIGSEGV)
+            }
+        ));
+        assert_eq!(
+            result.termination().as_deref(),
+            Some("terminated by signal SIGSEGV")
+        );
+    }
+}
@@ -2,10 +2,30 @@
 //!
 //! This module provides a command execution system with:
 //! - Step-by-step execution status with visual progress tracking
+//! - Progress within a step parsed from pacman output
 //! - Output capture (stdout/stderr) for better error reporting
-//! - Cancellation support (waits for current command to finish)
-//! - Automatic privilege escalation via pkexec
-//! - AUR helper integration (paru/yay)
+//! - ANSI color rendering in the output view, or stripping if disabled
+//! - stderr lines highlighted as errors or warnings only when they look like one
+//! - Output folded per step once it succeeds, with failures left expanded
+//! - Cancellation support (SIGTERM to the current command, SIGKILL after a grace period)
+//! - Escape cancels too, pressed twice while pacman is mid-transaction
+//! - Pausing and resuming the current command (SIGSTOP/SIGCONT to its process group)
+//! - Typing input for interactive commands, such as AUR helper prompts
+//! - A reboot prompt after steps marked with `requires_reboot`
+//! - Automatic retry of failed steps with backoff
+//! - Removal of stale pacman database locks after confirmation
+//! - Queueing of actions requested while another one is running
+//! - Optional review of every step and its resolved command before a run
+//! - A warning naming the URL before steps that pipe a download into a shell
+//! - Script steps run from a private temp file instead of `bash -c`
+//! - Optional steps that may fail without aborting the sequence
+//! - Install steps skipped when all their packages are already installed
+//! - Output logged to `~/.cache/xero-toolkit/logs/` for bug reports
+//! - The output of the last run kept in memory, see [`last_run`]
+//! - Automatic privilege escalation via pkexec, sudo or run0
+//! - AUR helper integration (paru/yay/pikaur/aura)
+//! - A headless runner without GTK, see [`headless`]
+//! - Processes started through a spawner that tests replace with scripted ones
 //!
 //! ## Usage
 //!
@@ -51,20 +71,36 @@
 //! 3. Capture command output for error reporting
 //! 4. Show completion status with appropriate success/failure messages
 
+mod ansi;
 mod command;
 mod executor;
+#[allow(dead_code)]
+// Headless API for tests and command-line tooling; the GUI uses the GTK runner
+pub mod headless;
+mod progress;
+mod remote;
+mod script;
+mod sequence;
+mod spawner;
+mod stderr;
 mod widgets;
 
-use crate::ui::utils::extract_widget;
-use gtk4::glib;
-use gtk4::prelude::*;
+use crate::ui::utils::{load_builder, try_extract_widget};
+use adw::prelude::*;
+use gtk4::{gio, glib};
 use gtk4::{Button, Label, Separator, ToggleButton, Window};
 use log::{error, info, warn};
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 
 // Re-export public API
+use command::CommandRunReport;
 pub use command::{Command, TaskStatus};
 
 use widgets::{TaskItem, TaskRunnerWidgets};
@@ -109,6 +145,17 @@ impl CommandSequence {
         self
     }
 
+    /// Add a step enabling the multilib repository, unless it is already enabled.
+    ///
+    /// Call before steps that install 32-bit (`lib32-*`) packages.
+    pub fn ensure_multilib(self) -> Self {
+        if crate::core::is_multilib_enabled() {
+            self
+        } else {
+            self.then(Command::enable_multilib())
+        }
+    }
+
     /// Build the final command sequence.
     pub fn build(self) -> Self {
         self
@@ -118,23 +165,285 @@ impl CommandSequence {
     pub fn is_empty(&self) -> bool {
         self.commands.is_empty()
     }
+
+    /// Summary of every step, one [`Command::summary`] per step.
+    pub fn summary(&self) -> String {
+        self.commands
+            .iter()
+            .map(Command::summary)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Check if any command modifies the pacman database.
+    fn uses_pacman(&self) -> bool {
+        self.commands.iter().any(|cmd| match cmd.command_type {
+            command::CommandType::Aur => true,
+            command::CommandType::Privileged => cmd.program == "pacman",
+            command::CommandType::Normal | command::CommandType::Flatpak => false,
+        })
+    }
+}
+
+/// Callback getting the report of a finished run, see [`RunOptions::on_complete`].
+pub type CompletionCallback = Rc<dyn Fn(&CommandRunReport)>;
+
+/// Options controlling how a command sequence is run.
+///
+/// # Example
+///
+/// ```no_run
+/// use crate::ui::task_runner::{run_with_options, RunOptions};
+///
+/// // Print the resolved commands without executing anything
+/// run_with_options(&window, commands, "System Setup", RunOptions::new().dry_run(true));
+/// ```
+#[derive(Clone)]
+pub struct RunOptions {
+    /// Print resolved commands instead of executing them
+    pub dry_run: bool,
+    /// Mirror all output to a timestamped log file
+    pub log_to_file: bool,
+    /// Called whenever a step changes status
+    pub on_step: Option<Rc<dyn Fn(StepEvent)>>,
+    /// Called once the run has finished, failed or been cancelled
+    pub on_complete: Option<CompletionCallback>,
+    /// Check run after all steps succeeded, returning why the result looks wrong
+    pub verify: Option<Rc<dyn Fn() -> Result<(), String>>>,
+    /// List every step and its resolved command, and ask before running
+    pub confirm: bool,
+}
+
+/// Status change of a single step, reported to [`RunOptions::on_step`].
+#[allow(dead_code)]
+// Fields are read by `on_step` callbacks
+#[derive(Debug, Clone)]
+pub struct StepEvent {
+    /// Zero-based index of the step
+    pub index: usize,
+    /// Total number of steps in the sequence
+    pub total: usize,
+    /// Description of the step, as shown in the task list
+    pub description: String,
+    /// New status of the step
+    pub status: TaskStatus,
+}
+
+impl Default for RunOptions {
+    fn default() -> Self {
+        Self {
+            dry_run: crate::config::debug::is_dry_run(),
+            log_to_file: true,
+            on_step: None,
+            on_complete: None,
+            verify: None,
+            confirm: false,
+        }
+    }
+}
+
+#[allow(dead_code)]
+// Builder API for callers that need per-run options; `run` uses the defaults
+impl RunOptions {
+    /// Create options with default values.
+    ///
+    /// Dry-run is enabled by default when `XERO_TOOLKIT_DRY_RUN` is set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enable or disable dry-run mode.
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Enable or disable writing output to a log file.
+    pub fn log_to_file(mut self, log_to_file: bool) -> Self {
+        self.log_to_file = log_to_file;
+        self
+    }
+
+    /// Set a callback that fires whenever a step starts, succeeds or fails.
+    ///
+    /// It is called on the main thread, once per status change.
+    pub fn on_step(mut self, on_step: impl Fn(StepEvent) + 'static) -> Self {
+        self.on_step = Some(Rc::new(on_step));
+        self
+    }
+
+    /// Set a callback that fires once the run has ended.
+    ///
+    /// It gets a [`CommandRunReport`] with the result and captured output of
+    /// every executed step, whether the run succeeded, failed or was cancelled.
+    pub fn on_complete(mut self, on_complete: impl Fn(&CommandRunReport) + 'static) -> Self {
+        self.on_complete = Some(Rc::new(on_complete));
+        self
+    }
+
+    /// Set a check that confirms the result once every step has succeeded.
+    ///
+    /// It runs on the main thread, so it should only look at packages, files
+    /// or services. A failure doesn't undo anything, the run still counts as
+    /// successful, but the dialog shows a warning with the returned reason.
+    /// Skipped in dry-run mode.
+    pub fn verify(mut self, verify: impl Fn() -> Result<(), String> + 'static) -> Self {
+        self.verify = Some(Rc::new(verify));
+        self
+    }
+
+    /// Enable or disable reviewing the steps before the run starts.
+    ///
+    /// The user sees each step with the command it resolves to and whether
+    /// it runs as root, through the AUR helper or as the user, and can
+    /// cancel before anything runs.
+    pub fn confirm(mut self, confirm: bool) -> Self {
+        self.confirm = confirm;
+        self
+    }
 }
 
 /// Message displayed when waiting for current command to finish after cancellation.
 pub(super) const CANCEL_WAITING_MESSAGE: &str = "Waiting for current command to finish...";
 
+/// How long a first Escape during a package transaction waits for the second.
+const ESCAPE_CONFIRM_TIMEOUT: Duration = Duration::from_secs(3);
+
 /// Message displayed when operation is canceled.
 pub(super) const CANCELLED_MESSAGE: &str = "Operation cancelled by user";
 
 /// Message displayed when all operations complete successfully.
 pub(super) const SUCCESS_MESSAGE: &str = "All operations completed successfully!";
 
+/// Banner printed at the top of the output in dry-run mode.
+const DRY_RUN_BANNER: &str =
+    "*** DRY RUN: commands below are shown as they would run, nothing is executed ***\n\n";
+
 /// Global flag to track if an action is currently running.
 static ACTION_RUNNING: AtomicBool = AtomicBool::new(false);
 
-/// Check if an action is currently running.
-pub fn is_running() -> bool {
-    ACTION_RUNNING.load(Ordering::SeqCst)
+/// Marks an action as running for as long as it is alive.
+///
+/// Dropping the guard clears the flag, so a run that ends early or panics
+/// can't leave the toolkit believing an action is still running.
+pub struct ActionGuard(());
+
+impl ActionGuard {
+    /// Mark an action as running, or return None if one already is.
+    fn acquire() -> Option<Self> {
+        ACTION_RUNNING
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .ok()
+            .map(|_| Self(()))
+    }
+}
+
+impl Drop for ActionGuard {
+    fn drop(&mut self) {
+        ACTION_RUNNING.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Title, output and report of the last finished run.
+#[derive(Clone)]
+pub struct LastRun {
+    pub title: String,
+    /// Text of the output view, as shown when the run ended
+    pub output: String,
+    pub report: CommandRunReport,
+}
+
+thread_local! {
+    /// Last finished run, kept until the next one starts.
+    static LAST_RUN: RefCell<Option<LastRun>> = const { RefCell::new(None) };
+}
+
+/// The last finished run, if one has finished since the last run started.
+///
+/// Kept in memory only, so it is available without log files.
+pub fn last_run() -> Option<LastRun> {
+    LAST_RUN.with_borrow(Clone::clone)
+}
+
+/// Remember `run` as the last finished run.
+pub(super) fn set_last_run(run: LastRun) {
+    LAST_RUN.with_borrow_mut(|last_run| *last_run = Some(run));
+}
+
+/// Run requested while another action was running.
+struct QueuedRun {
+    parent: Window,
+    commands: CommandSequence,
+    title: String,
+    options: RunOptions,
+}
+
+thread_local! {
+    /// Runs waiting for the current action to finish, oldest first.
+    static QUEUE: RefCell<VecDeque<QueuedRun>> = const { RefCell::new(VecDeque::new()) };
+}
+
+/// Titles of the runs waiting for the current action, oldest first.
+pub fn queued_titles() -> Vec<String> {
+    QUEUE.with_borrow(|queue| queue.iter().map(|run| run.title.clone()).collect())
+}
+
+/// Drop all runs waiting for the current action.
+pub fn clear_queue() {
+    let cleared = QUEUE.with_borrow_mut(std::mem::take).len();
+    if cleared > 0 {
+        info!("Cleared {} queued action(s)", cleared);
+    }
+}
+
+/// Start the next queued run now that the current action has finished.
+///
+/// After a failed or cancelled action the user is asked first, so queued
+/// runs never start without them noticing.
+pub(super) fn action_finished(success: bool) {
+    let Some(parent) = QUEUE.with_borrow(|queue| queue.front().map(|run| run.parent.clone()))
+    else {
+        return;
+    };
+
+    if success {
+        glib::idle_add_local_once(start_next_queued);
+        return;
+    }
+
+    let titles: Vec<String> = queued_titles()
+        .iter()
+        .map(|title| format!("• {}", title))
+        .collect();
+    let dialog = adw::AlertDialog::builder()
+        .heading("Run Queued Actions?")
+        .body(format!(
+            "The last action did not complete. These actions are still waiting to run:\n\n{}",
+            titles.join("\n")
+        ))
+        .build();
+    dialog.add_responses(&[("discard", "Discard"), ("run", "Run")]);
+    dialog.set_response_appearance("run", adw::ResponseAppearance::Suggested);
+    dialog.set_default_response(Some("discard"));
+    dialog.set_close_response("discard");
+    dialog.connect_response(None, |_, response| {
+        if response == "run" {
+            start_next_queued();
+        } else {
+            clear_queue();
+        }
+    });
+
+    dialog.present(Some(&parent));
+}
+
+/// Start the oldest queued run, if any.
+fn start_next_queued() {
+    let Some(run) = QUEUE.with_borrow_mut(VecDeque::pop_front) else {
+        return;
+    };
+    info!("Starting queued action: {}", run.title);
+    run_with_options(&run.parent, run.commands, &run.title, run.options);
 }
 
 /// Run commands with a progress dialog.
@@ -167,34 +476,391 @@ pub fn is_running() -> bool {
 /// run(&window, commands, "System Setup");
 /// ```
 pub fn run(parent: &Window, commands: CommandSequence, title: &str) {
+    run_with_options(parent, commands, title, RunOptions::default());
+}
+
+/// Let the user copy the current log file to a location of their choice.
+fn save_log(widgets: &Rc<TaskRunnerWidgets>) {
+    let Some(log_path) = widgets.log_path() else {
+        return;
+    };
+
+    let dialog = gtk4::FileDialog::new();
+    if let Some(name) = log_path.file_name() {
+        dialog.set_initial_name(Some(&name.to_string_lossy()));
+    }
+
+    let widgets = widgets.clone();
+    glib::spawn_future_local(async move {
+        let Ok(file) = dialog.save_future(Some(&widgets.window)).await else {
+            // User cancelled
+            return;
+        };
+        let Some(dest) = file.path() else {
+            return;
+        };
+
+        match std::fs::copy(&log_path, &dest) {
+            Ok(_) => info!("Saved task log to {}", dest.display()),
+            Err(e) => {
+                error!("Failed to save log to {}: {}", dest.display(), e);
+                widgets.append_colored(&format!("\nFailed to save log: {}\n", e), "error");
+            }
+        }
+    });
+}
+
+/// Write the output shown so far to a temp file and open it in a text editor.
+///
+/// Uses the default app for plain text, or `$EDITOR` in a terminal dialog if
+/// there is none. The file is added to `files` so it can be removed once the
+/// task window closes.
+fn open_in_editor(widgets: &Rc<TaskRunnerWidgets>, files: &RefCell<Vec<PathBuf>>) {
+    let buffer = &widgets.output_text_buffer;
+    let text = buffer.text(&buffer.start_iter(), &buffer.end_iter(), false);
+
+    let path = std::env::temp_dir().join(format!(
+        "xero-toolkit-output-{:016x}.txt",
+        rand::random::<u64>()
+    ));
+    let written = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(&path)
+        .and_then(|mut file| {
+            file.write_all(text.as_bytes()).inspect_err(|_| {
+                let _ = std::fs::remove_file(&path);
+            })
+        });
+    if let Err(e) = written {
+        error!("Failed to write output to {}: {}", path.display(), e);
+        widgets.append_colored(&format!("\nFailed to write output file: {}\n", e), "error");
+        return;
+    }
+    files.borrow_mut().push(path.clone());
+
+    if let Some(app) = gio::AppInfo::default_for_type("text/plain", false) {
+        info!("Opening output in {}", app.name());
+        let context = WidgetExt::display(&widgets.window).app_launch_context();
+        if let Err(e) = app.launch(&[gio::File::for_path(&path)], Some(&context)) {
+            error!("Failed to launch {}: {}", app.name(), e);
+            widgets.append_colored(
+                &format!("\nFailed to open {}: {}\n", app.name(), e),
+                "error",
+            );
+        }
+        return;
+    }
+
+    let editor = std::env::var("EDITOR").unwrap_or_default();
+    let mut words = editor.split_whitespace();
+    let Some(program) = words.next() else {
+        warn!("No text editor available to open the output");
+        widgets.append_colored(
+            "\nNo text editor found. Set a default app for text files or the EDITOR \
+            variable, or use Save Log instead.\n",
+            "error",
+        );
+        return;
+    };
+
+    let path = path.to_string_lossy();
+    let args: Vec<&str> = words.chain([path.as_ref()]).collect();
+    crate::ui::dialogs::terminal::show_terminal_dialog(
+        &widgets.window,
+        "Command Output",
+        program,
+        &args,
+    );
+}
+
+/// Close the finished task window and reboot through the privilege backend.
+fn reboot(widgets: &Rc<TaskRunnerWidgets>) {
+    info!("Reboot requested from task window");
+    let parent = widgets.window.transient_for();
+    widgets.window.close();
+
+    let commands = CommandSequence::new()
+        .then(
+            Command::builder()
+                .privileged()
+                .program("systemctl")
+                .args(&["reboot"])
+                .description("Rebooting...")
+                .build(),
+        )
+        .build();
+
+    match parent {
+        Some(parent) => run(&parent, commands, "Reboot"),
+        None => warn!("Task window has no parent, cannot reboot"),
+    }
+}
+
+/// Run commands with a progress dialog using custom [`RunOptions`].
+///
+/// If another action is running, the commands are queued and start once it
+/// has finished.
+pub fn run_with_options(
+    parent: &Window,
+    commands: CommandSequence,
+    title: &str,
+    options: RunOptions,
+) {
     if commands.is_empty() {
         error!("No commands provided");
         return;
     }
 
-    if is_running() {
-        warn!("Action already running - ignoring request");
+    let Some(guard) = ActionGuard::acquire() else {
+        info!("Action already running - queueing '{}'", title);
+        QUEUE.with_borrow_mut(|queue| {
+            queue.push_back(QueuedRun {
+                parent: parent.clone(),
+                commands,
+                title: title.to_string(),
+                options,
+            })
+        });
+        return;
+    };
+
+    if options.confirm {
+        confirm_run(parent, commands, title, options, guard);
+        return;
+    }
+
+    begin_run(parent, commands, title, options, guard);
+}
+
+/// Start a run that holds the action guard.
+fn begin_run(
+    parent: &Window,
+    commands: CommandSequence,
+    title: &str,
+    options: RunOptions,
+    guard: ActionGuard,
+) {
+    // A lock left behind by an interrupted run would make every pacman step fail
+    if !options.dry_run
+        && commands.uses_pacman()
+        && crate::core::check_pacman_lock() == crate::core::LockState::Stale
+    {
+        confirm_stale_lock_removal(parent, commands, title, options, guard);
         return;
     }
 
-    ACTION_RUNNING.store(true, Ordering::SeqCst);
+    start_task_window(parent, commands, title, options, guard);
+}
 
-    let builder = gtk4::Builder::from_resource(crate::config::resources::dialogs::TASK_LIST);
+/// Show every step of `commands` and ask before running them.
+///
+/// Each step lists the command it resolves to and how it runs. Cancelling
+/// lets queued runs continue, since nothing was started.
+fn confirm_run(
+    parent: &Window,
+    commands: CommandSequence,
+    title: &str,
+    options: RunOptions,
+    guard: ActionGuard,
+) {
+    let list = gtk4::ListBox::builder()
+        .selection_mode(gtk4::SelectionMode::None)
+        .css_classes(["boxed-list"])
+        .build();
+    for command in &commands.commands {
+        let resolved = match executor::resolve_command(command) {
+            Ok((program, args)) => executor::format_command_line(&program, &args),
+            Err(reason) => format!("Can't run: {}", reason),
+        };
+        let row = adw::ActionRow::builder()
+            .title(glib::markup_escape_text(&command.description))
+            .subtitle(glib::markup_escape_text(&resolved))
+            .subtitle_selectable(true)
+            .build();
+
+        let kind_name = step_kind(command);
+        let kind = Label::new(Some(kind_name));
+        kind.add_css_class("caption-heading");
+        if matches!(kind_name, "Root" | "AUR") {
+            kind.add_css_class("warning");
+        }
+        row.add_suffix(&kind);
+        list.append(&row);
+    }
+    let scrolled = gtk4::ScrolledWindow::builder()
+        .child(&list)
+        .hscrollbar_policy(gtk4::PolicyType::Never)
+        .max_content_height(360)
+        .propagate_natural_height(true)
+        .build();
+
+    let dialog = adw::AlertDialog::builder()
+        .heading(format!("Run {}?", title))
+        .body(format!(
+            "{} step(s) will run. Steps marked Root run with administrator privileges.",
+            commands.commands.len()
+        ))
+        .extra_child(&scrolled)
+        .build();
+    dialog.add_responses(&[("cancel", "Cancel"), ("run", "Confirm")]);
+    dialog.set_response_appearance("run", adw::ResponseAppearance::Suggested);
+    dialog.set_default_response(Some("cancel"));
+    dialog.set_close_response("cancel");
+
+    let parent_clone = parent.clone();
+    let title = title.to_string();
+    let pending = RefCell::new(Some((commands, options, guard)));
+    dialog.connect_response(None, move |_, response| {
+        let Some((commands, options, guard)) = pending.borrow_mut().take() else {
+            return;
+        };
+
+        if response != "run" {
+            info!("Run '{}' cancelled before starting", title);
+            drop(guard);
+            action_finished(true);
+            return;
+        }
+        begin_run(&parent_clone, commands, &title, options, guard);
+    });
+
+    dialog.present(Some(parent));
+}
+
+/// How a step runs, as shown when reviewing a run.
+fn step_kind(command: &Command) -> &'static str {
+    match command.command_type {
+        command::CommandType::Privileged if command.run_as.is_some() => "User",
+        command::CommandType::Privileged => "Root",
+        command::CommandType::Aur => "AUR",
+        command::CommandType::Flatpak => "Flatpak",
+        command::CommandType::Normal => "User",
+    }
+}
+
+/// Offer to remove a stale pacman database lock before running `commands`.
+///
+/// Removing the lock is prepended to the sequence as a privileged step.
+/// Declining aborts the run without opening the task window. Queued runs
+/// would likely hit the same lock, so the user is asked before they start.
+fn confirm_stale_lock_removal(
+    parent: &Window,
+    commands: CommandSequence,
+    title: &str,
+    options: RunOptions,
+    guard: ActionGuard,
+) {
+    let dialog = adw::AlertDialog::builder()
+        .heading("Stale Package Database Lock")
+        .body(format!(
+            "{} exists, but no pacman process is running. It was probably left behind by an interrupted operation.\n\nRemove the lock and continue?",
+            crate::core::pacman_lock::DB_LOCK_PATH
+        ))
+        .build();
+    dialog.add_responses(&[("cancel", "Cancel"), ("remove", "Remove Lock")]);
+    dialog.set_response_appearance("remove", adw::ResponseAppearance::Destructive);
+    dialog.set_default_response(Some("cancel"));
+    dialog.set_close_response("cancel");
+
+    let parent_clone = parent.clone();
+    let title = title.to_string();
+    let pending = RefCell::new(Some((commands, options, guard)));
+    dialog.connect_response(None, move |_, response| {
+        let Some((mut commands, options, guard)) = pending.borrow_mut().take() else {
+            return;
+        };
+
+        if response != "remove" {
+            info!("Stale pacman lock kept, aborting run");
+            drop(guard);
+            action_finished(false);
+            return;
+        }
+
+        commands.commands.insert(
+            0,
+            Command::builder()
+                .privileged()
+                .program("rm")
+                .args(&["-f", crate::core::pacman_lock::DB_LOCK_PATH])
+                .description("Removing stale pacman database lock...")
+                .build(),
+        );
+        start_task_window(&parent_clone, commands, &title, options, guard);
+    });
+
+    dialog.present(Some(parent));
+}
+
+/// Open the task window for `commands`, telling the user if it can't be built.
+///
+/// A missing or broken task window resource is reported in a dialog instead
+/// of aborting the whole application.
+fn start_task_window(
+    parent: &Window,
+    commands: CommandSequence,
+    title: &str,
+    options: RunOptions,
+    guard: ActionGuard,
+) {
+    let Err(e) = open_task_window(parent, commands, title, options, guard) else {
+        return;
+    };
+    error!("Failed to open task window for '{}': {:#}", title, e);
 
-    let window: Window = extract_widget(&builder, "task_window");
-    let title_label: Label = extract_widget(&builder, "task_title");
-    let task_list_container: gtk4::Box = extract_widget(&builder, "task_list_container");
-    let scrolled_window: gtk4::ScrolledWindow = extract_widget(&builder, "task_scrolled_window");
-    let cancel_button: Button = extract_widget(&builder, "cancel_button");
-    let close_button: Button = extract_widget(&builder, "close_button");
-    let sidebar_toggle: ToggleButton = extract_widget(&builder, "sidebar_toggle_button");
-    let sidebar_revealer: gtk4::Revealer = extract_widget(&builder, "sidebar_revealer");
-    let output_text_view: gtk4::TextView = extract_widget(&builder, "output_text_view");
+    let dialog = adw::AlertDialog::builder()
+        .heading("Could Not Open Task Window")
+        .body(format!(
+            "The task window could not be built, so \"{}\" was not run. \
+            The application may be installed incorrectly.\n\n{:#}",
+            title, e
+        ))
+        .build();
+    dialog.add_response("close", "Close");
+    dialog.present(Some(parent));
+
+    action_finished(false);
+}
+
+/// Build the task window and start executing `commands`.
+fn open_task_window(
+    parent: &Window,
+    commands: CommandSequence,
+    title: &str,
+    options: RunOptions,
+    guard: ActionGuard,
+) -> anyhow::Result<()> {
+    let builder = load_builder(crate::config::resources::dialogs::TASK_LIST)?;
+
+    let window: Window = try_extract_widget(&builder, "task_window")?;
+    let title_label: Label = try_extract_widget(&builder, "task_title")?;
+    let progress_bar: gtk4::ProgressBar = try_extract_widget(&builder, "task_progress_bar")?;
+    let time_label: Label = try_extract_widget(&builder, "task_time_label")?;
+    let task_list_container: gtk4::Box = try_extract_widget(&builder, "task_list_container")?;
+    let scrolled_window: gtk4::ScrolledWindow =
+        try_extract_widget(&builder, "task_scrolled_window")?;
+    let cancel_button: Button = try_extract_widget(&builder, "cancel_button")?;
+    let pause_button: Button = try_extract_widget(&builder, "pause_button")?;
+    let close_button: Button = try_extract_widget(&builder, "close_button")?;
+    let save_log_button: Button = try_extract_widget(&builder, "save_log_button")?;
+    let retry_button: Button = try_extract_widget(&builder, "retry_button")?;
+    let sidebar_toggle: ToggleButton = try_extract_widget(&builder, "sidebar_toggle_button")?;
+    let ansi_color_toggle: ToggleButton = try_extract_widget(&builder, "ansi_color_toggle")?;
+    let open_editor_button: Button = try_extract_widget(&builder, "open_editor_button")?;
+    let sidebar_revealer: gtk4::Revealer = try_extract_widget(&builder, "sidebar_revealer")?;
+    let output_text_view: gtk4::TextView = try_extract_widget(&builder, "output_text_view")?;
     let output_text_buffer = output_text_view.buffer();
+    let stdin_entry: gtk4::Entry = try_extract_widget(&builder, "stdin_entry")?;
+    let reboot_banner: adw::Banner = try_extract_widget(&builder, "reboot_banner")?;
 
     window.set_transient_for(Some(parent));
     window.set_title(Some(title));
 
+    // The previous run's output is replaced by this one's
+    LAST_RUN.with_borrow_mut(Option::take);
+
     let commands_vec = commands.commands;
 
     // Create task items for each command
@@ -213,36 +879,118 @@ pub fn run(parent: &Window, commands: CommandSequence, title: &str) {
 
     // Initialize output buffer
     output_text_buffer.set_text("Command outputs will appear here as tasks execute...\n\n");
+    let dry_run = options.dry_run;
 
     let widgets = Rc::new(TaskRunnerWidgets::new(
         window.clone(),
         title_label,
+        progress_bar,
+        time_label,
         task_list_container,
         scrolled_window,
         cancel_button.clone(),
+        pause_button.clone(),
         close_button.clone(),
+        save_log_button.clone(),
+        retry_button.clone(),
         task_items,
+        commands_vec.iter().map(|cmd| cmd.weight).collect(),
         sidebar_toggle,
+        ansi_color_toggle,
         sidebar_revealer,
         output_text_view,
         output_text_buffer,
+        stdin_entry.clone(),
+        reboot_banner.clone(),
     ));
 
     // Setup sidebar toggle binding and initialize collapsed
     widgets.setup_sidebar_toggle();
     widgets.init_sidebar_collapsed();
+    widgets.setup_output_folding();
+
+    if options.log_to_file {
+        match executor::LogWriter::create() {
+            Ok(log) => {
+                log.write(&format!("### {} ###\n", title));
+                widgets.set_log_writer(log);
+            }
+            Err(e) => warn!("Failed to create task log file: {}", e),
+        }
+    }
+
+    if dry_run {
+        info!("Dry run enabled - commands will not be executed");
+        widgets.append_colored(DRY_RUN_BANNER, "error");
+        widgets.sidebar_toggle.set_active(true);
+        widgets.keep_succeeded_output();
+    }
 
     let cancelled = Rc::new(RefCell::new(false));
-    let current_process = Rc::new(RefCell::new(None::<gtk4::gio::Subprocess>));
-    let commands = Rc::new(commands_vec);
+    let state = executor::ExecutionState::new(
+        widgets.clone(),
+        commands_vec,
+        options,
+        cancelled.clone(),
+        guard,
+        Rc::new(spawner::SystemSpawner),
+    );
 
     // Cancel button handler
-    let widgets_clone = widgets.clone();
-    let cancelled_clone = cancelled.clone();
+    let state_clone = state.clone();
     cancel_button.connect_clicked(move |_| {
-        *cancelled_clone.borrow_mut() = true;
-        widgets_clone.disable_cancel();
-        widgets_clone.set_title(CANCEL_WAITING_MESSAGE);
+        cancel_run(&state_clone);
+    });
+
+    // Escape cancels like the Cancel button
+    let state_clone = state.clone();
+    let first_escape: Cell<Option<Instant>> = Cell::new(None);
+    let escape_action = gtk4::CallbackAction::new(move |_, _| {
+        let cancel_button = &state_clone.widgets.cancel_button;
+        if !cancel_button.is_visible() || !cancel_button.is_sensitive() {
+            return glib::Propagation::Proceed;
+        }
+
+        let confirmed = first_escape
+            .take()
+            .is_some_and(|pressed| pressed.elapsed() < ESCAPE_CONFIRM_TIMEOUT);
+        if !confirmed && state_clone.is_package_transaction_running() {
+            first_escape.set(Some(Instant::now()));
+            state_clone.widgets.append_colored(
+                "\nA package transaction is running, stopping it can damage the package \
+                database. Press Escape again to cancel anyway.\n",
+                "warning",
+            );
+            return glib::Propagation::Stop;
+        }
+
+        cancel_run(&state_clone);
+        glib::Propagation::Stop
+    });
+    let shortcuts = gtk4::ShortcutController::new();
+    shortcuts.add_shortcut(gtk4::Shortcut::new(
+        gtk4::ShortcutTrigger::parse_string("Escape"),
+        Some(escape_action),
+    ));
+    window.add_controller(shortcuts);
+
+    // Reboot banner handler
+    let widgets_clone = widgets.clone();
+    reboot_banner.connect_button_clicked(move |_| {
+        reboot(&widgets_clone);
+    });
+
+    // Input entry handler
+    let state_clone = state.clone();
+    stdin_entry.connect_activate(move |entry| {
+        state_clone.send_input(&entry.text());
+        entry.set_text("");
+    });
+
+    // Pause button handler
+    let state_clone = state.clone();
+    pause_button.connect_clicked(move |_| {
+        state_clone.toggle_pause();
     });
 
     // Close button handler
@@ -251,23 +999,93 @@ pub fn run(parent: &Window, commands: CommandSequence, title: &str) {
         widgets_clone.window.close();
     });
 
+    // Retry button handler
+    let state_clone = state.clone();
+    retry_button.connect_clicked(move |_| {
+        retry_failed(&state_clone);
+    });
+
+    // Save log button handler
+    let widgets_clone = widgets.clone();
+    save_log_button.connect_clicked(move |_| {
+        save_log(&widgets_clone);
+    });
+
+    // Open in editor button handler
+    let editor_files: Rc<RefCell<Vec<PathBuf>>> = Rc::default();
+    let widgets_clone = widgets.clone();
+    let editor_files_clone = editor_files.clone();
+    open_editor_button.connect_clicked(move |_| {
+        open_in_editor(&widgets_clone, &editor_files_clone);
+    });
+
     // Window close handler
     let cancelled_clone = cancelled.clone();
+    let state_clone = state.clone();
     window.connect_close_request(move |_| {
-        ACTION_RUNNING.store(false, Ordering::SeqCst);
+        for path in editor_files.borrow_mut().drain(..) {
+            if let Err(e) = std::fs::remove_file(&path) {
+                warn!("Failed to remove {}: {}", path.display(), e);
+            }
+        }
+        state_clone.release_action();
         *cancelled_clone.borrow_mut() = true;
+        // Don't leave a stopped process behind
+        state_clone.unpause();
+        state_clone.widgets.stop_timer();
         glib::Propagation::Proceed
     });
 
     window.present();
+    start_from(state, 0);
+    Ok(())
+}
 
-    // Check if we need the daemon (any privileged or AUR commands)
-    let needs_daemon = commands.iter().any(|cmd| {
-        matches!(
-            cmd.command_type,
-            command::CommandType::Privileged | command::CommandType::Aur
-        )
-    });
+/// Cancel the run, from the Cancel button or the Escape key.
+fn cancel_run(state: &executor::ExecutionState) {
+    state.cancel();
+    state.widgets.disable_cancel();
+    state.widgets.set_title(CANCEL_WAITING_MESSAGE);
+}
+
+/// Run the sequence of a failed run again, starting at the failed step.
+///
+/// Steps before it already succeeded and are not run again.
+fn retry_failed(state: &Rc<executor::ExecutionState>) {
+    let Some(guard) = ActionGuard::acquire() else {
+        state.widgets.append_colored(
+            "\nAnother action is running, retry once it has finished\n",
+            "error",
+        );
+        return;
+    };
+    let Some(index) = state.take_failed_step() else {
+        return;
+    };
+
+    info!("Retrying from step {}", index + 1);
+    state.reacquire_action(guard);
+    state.widgets.resume_running();
+    state.widgets.append_colored(
+        &format!("\nRetrying from step {}...\n", index + 1),
+        "stdout",
+    );
+    start_from(state.clone(), index);
+}
+
+/// Start the authentication daemon if needed, then execute from `index`.
+fn start_from(state: Rc<executor::ExecutionState>, index: usize) {
+    let widgets = &state.widgets;
+
+    // Check if we need the daemon (any privileged or AUR commands on the pkexec backend)
+    let needs_daemon = !state.options.dry_run
+        && crate::core::privilege::get().uses_daemon()
+        && state.commands.iter().any(|cmd| {
+            matches!(
+                cmd.command_type,
+                command::CommandType::Privileged | command::CommandType::Aur
+            )
+        });
 
     // Start daemon if needed
     if needs_daemon {
@@ -277,11 +1095,29 @@ pub fn run(parent: &Window, commands: CommandSequence, title: &str) {
             widgets.append_colored(&error_msg, "error");
             widgets.set_title(&format!("Failed to start authentication daemon: {}", e));
             widgets.show_completion(false, "Failed to start authentication daemon");
+            state.release_action();
+            action_finished(false);
             return;
         }
         info!("Daemon ready for privileged commands");
     }
 
     // Start executing commands
-    executor::execute_commands(widgets, commands, 0, cancelled, current_process);
+    executor::execute_commands(state, index);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_action_guard_clears_flag_on_drop() {
+        let guard = ActionGuard::acquire().expect("no action running");
+        assert!(ActionGuard::acquire().is_none());
+
+        drop(guard);
+        let guard = ActionGuard::acquire().expect("flag cleared on drop");
+        drop(guard);
+        assert!(ActionGuard::acquire().is_some());
+    }
 }
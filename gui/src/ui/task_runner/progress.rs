@@ -0,0 +1,74 @@
+//! Progress parsing for command output.
+//!
+//! Recognizes pacman's progress lines so the progress bar can move within
+//! a long-running step instead of only advancing between steps.
+
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// Matches the `( 12/148)` package counter at the start of a line.
+fn counter_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"^\(\s*(\d+)/(\d+)\)").unwrap())
+}
+
+/// Matches the trailing `45%` of a progress bar line.
+fn percent_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(\d{1,3})%\s*$").unwrap())
+}
+
+/// Parse a line of output into a progress fraction between 0.0 and 1.0.
+///
+/// Handles pacman's `( n/total)` counters, optionally combined with the
+/// per-package percentage, and plain download lines ending in a percentage.
+/// ANSI escape codes are ignored. Returns None for lines without progress.
+pub fn parse_progress(line: &str) -> Option<f64> {
+    let line = strip_ansi_escapes::strip_str(line);
+    let line = line.trim();
+
+    let percent = percent_regex()
+        .captures(line)
+        .and_then(|caps| caps[1].parse::<f64>().ok())
+        .filter(|p| *p <= 100.0)
+        .map(|p| p / 100.0);
+
+    let counter = counter_regex().captures(line).and_then(|caps| {
+        let current = caps[1].parse::<f64>().ok()?;
+        let total = caps[2].parse::<f64>().ok()?;
+        (total > 0.0 && current <= total).then_some((current, total))
+    });
+
+    match (counter, percent) {
+        (Some((current, total)), Some(percent)) => Some((current - 1.0 + percent) / total),
+        (Some((current, total)), None) => Some(current / total),
+        (None, Some(percent)) => Some(percent),
+        (None, None) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pacman_output() {
+        // Package counter with per-package percentage
+        let line = "( 3/4) upgrading linux                [################------]  75%";
+        assert_eq!(parse_progress(line), Some((2.0 + 0.75) / 4.0));
+
+        // Counter only
+        assert_eq!(parse_progress("(1/2) checking keys in keyring"), Some(0.5));
+
+        // Download line, colored
+        let line = "\x1b[1m core-x86_64\x1b[0m  120.5 KiB  1203 KiB/s 00:00 [######] 100%";
+        assert_eq!(parse_progress(line), Some(1.0));
+
+        // Lines without progress
+        assert_eq!(
+            parse_progress(":: Synchronizing package databases..."),
+            None
+        );
+        assert_eq!(parse_progress("resolving dependencies..."), None);
+    }
+}
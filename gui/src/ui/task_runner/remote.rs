@@ -0,0 +1,141 @@
+//! Detection of steps that run scripts downloaded from the internet.
+//!
+//! Installers like `curl URL | bash` run whatever the server sends, so the
+//! user is asked before such a step with the URLs about to be trusted.
+
+use super::command::{Command, CommandType};
+
+/// Shells whose command text is checked for remote scripts.
+const SHELLS: &[&str] = &["bash", "sh", "zsh"];
+
+/// Tools that download the scripts.
+const DOWNLOADERS: &[&str] = &["curl", "wget"];
+
+/// URLs of remote scripts that `command` downloads and runs in a shell.
+///
+/// Looks at the `-c` string of shell commands and at the body of script
+/// steps. Empty if the step doesn't run a shell or doesn't feed a download
+/// to one.
+pub fn remote_script_urls(command: &Command) -> Vec<String> {
+    if !SHELLS.contains(&command.program.as_str()) {
+        return Vec::new();
+    }
+
+    match &command.script {
+        Some(body) => urls_run_by_shell(body),
+        None => urls_run_by_shell(&command.args.join(" ")),
+    }
+}
+
+/// URLs downloaded in shell code whose downloads end up in a shell.
+///
+/// Matches `curl URL | bash`, `sh <(curl URL)` and scripts that save a
+/// download and then run `bash` or `sh` on their own line.
+fn urls_run_by_shell(code: &str) -> Vec<String> {
+    let words: Vec<&str> = code.split_whitespace().collect();
+    let downloads = words
+        .iter()
+        .any(|word| DOWNLOADERS.contains(&word.trim_start_matches("<(")));
+    let piped = words.windows(2).any(|pair| match pair {
+        ["|", shell] => SHELLS.contains(shell),
+        [_, word] => word
+            .strip_prefix('|')
+            .is_some_and(|shell| SHELLS.contains(&shell)),
+        _ => false,
+    });
+    let substituted = words.iter().any(|word| {
+        word.strip_prefix("<(")
+            .is_some_and(|tool| DOWNLOADERS.contains(&tool))
+    });
+    let runs_download = code.lines().any(|line| {
+        let line = line.trim_start();
+        SHELLS.iter().any(|shell| {
+            line.strip_prefix(shell)
+                .is_some_and(|rest| rest.starts_with(' '))
+        })
+    });
+
+    if !downloads || !(piped || substituted || runs_download) {
+        return Vec::new();
+    }
+
+    let mut urls: Vec<String> = Vec::new();
+    for word in words {
+        let url = word.trim_matches(|c| matches!(c, '"' | '\'' | '(' | ')' | ';'));
+        if (url.starts_with("https://") || url.starts_with("http://"))
+            && !urls.iter().any(|known| known == url)
+        {
+            urls.push(url.to_string());
+        }
+    }
+    urls
+}
+
+/// Confirmation text for a step running the remote scripts at `urls`.
+pub fn remote_script_warning(command: &Command, urls: &[String]) -> String {
+    let privileges = if command.command_type == CommandType::Privileged {
+        "with administrator privileges"
+    } else {
+        "as your user"
+    };
+
+    format!(
+        "This step downloads a script and runs it {} without showing it first:\n\n{}\n\n\
+        The script can change anything on your system and may differ from what \
+        it was when this step was written. Only continue if you trust its source.",
+        privileges,
+        urls.join("\n")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_urls_run_by_shell() {
+        assert_eq!(
+            urls_run_by_shell("curl -fsSL https://example.com/install.sh | bash"),
+            vec!["https://example.com/install.sh"]
+        );
+        assert_eq!(
+            urls_run_by_shell(
+                "sh <(curl --proto '=https' --tlsv1.2 -L https://nixos.org/nix/install) --daemon"
+            ),
+            vec!["https://nixos.org/nix/install"]
+        );
+        assert_eq!(
+            urls_run_by_shell("curl -fsSL https://example.com/install.sh -o \"$f\"\nbash \"$f\"\n"),
+            vec!["https://example.com/install.sh"]
+        );
+
+        // Downloads that are never run
+        assert!(urls_run_by_shell("curl -fsSL https://example.com/a.tar -o a.tar").is_empty());
+        assert!(urls_run_by_shell("echo https://example.com | bash").is_empty());
+    }
+
+    #[test]
+    fn test_remote_script_urls() {
+        let piped = Command::builder()
+            .normal()
+            .program("sh")
+            .args(&["-c", "wget -qO- https://example.com/i.sh |sh"])
+            .description("Installing")
+            .build();
+        assert_eq!(remote_script_urls(&piped), vec!["https://example.com/i.sh"]);
+
+        let script = Command::script("curl -fsSL https://example.com/i.sh | bash\n", "installer");
+        assert_eq!(
+            remote_script_urls(&script),
+            vec!["https://example.com/i.sh"]
+        );
+
+        let other = Command::builder()
+            .normal()
+            .program("echo")
+            .args(&["curl https://example.com | bash"])
+            .description("Echo")
+            .build();
+        assert!(remote_script_urls(&other).is_empty());
+    }
+}
@@ -0,0 +1,71 @@
+//! Temporary files for script steps.
+//!
+//! The body of a [`Command::script`](super::command::Command::script) step
+//! is written to a file under the temp directory right before the step
+//! runs. The file name is random and the file is created exclusively, so
+//! another user can't predict it or plant a symlink in its place.
+
+use log::warn;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+
+/// Script written to disk, removed again when dropped.
+#[derive(Debug)]
+pub struct ScriptFile {
+    path: PathBuf,
+}
+
+impl ScriptFile {
+    /// Write `body` to a new file that only the current user can access.
+    pub fn create(body: &str) -> std::io::Result<Self> {
+        let path =
+            std::env::temp_dir().join(format!("xero-toolkit-{:016x}.sh", rand::random::<u64>()));
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o700)
+            .open(&path)?;
+
+        // Removes the file again if writing fails
+        let script = Self { path };
+        file.write_all(body.as_bytes())?;
+        file.flush()?;
+        Ok(script)
+    }
+
+    /// Location of the script.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for ScriptFile {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            warn!("Failed to remove script {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn test_script_file_is_private_and_removed() {
+        let script = ScriptFile::create("echo hello\n").unwrap();
+        let other = ScriptFile::create("echo hello\n").unwrap();
+        assert_ne!(script.path(), other.path());
+
+        let path = script.path().to_path_buf();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "echo hello\n");
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+
+        drop(script);
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,278 @@
+//! Step sequencing shared by the GTK and headless runners.
+//!
+//! [`Sequence`] decides which step runs next, whether a failed attempt is
+//! retried and how the run ends. The runners only start the processes and
+//! show what happened, so both follow the same rules and the rules can be
+//! tested without GTK or real processes.
+
+use super::command::{Command, CommandResult, TaskStatus};
+use std::time::Duration;
+
+/// What a runner does next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Next {
+    /// Mark the already satisfied step at the index as skipped
+    Skip(usize),
+    /// Start `attempt` of the step at `index` and report how it ended to
+    /// [`Sequence::finished`]
+    Run { index: usize, attempt: u32 },
+    /// Every step ran or was skipped
+    Done,
+    /// The run was cancelled, before the step at the index if any was left
+    Cancelled(Option<usize>),
+}
+
+/// How a run goes on after an attempt of a step has ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum StepOutcome {
+    /// The run was cancelled and stops here
+    Cancelled,
+    /// The step failed and is tried again after the delay
+    Retry(Duration),
+    /// The run goes on with the next step, marking this one with the status
+    Continue(TaskStatus),
+    /// The step failed and the run stops here
+    Failed,
+}
+
+/// Position of a run in its command sequence.
+#[derive(Debug)]
+pub(super) struct Sequence {
+    /// Step the run is at
+    index: usize,
+    /// Failed attempts of the step so far
+    attempt: u32,
+}
+
+impl Sequence {
+    /// Start a sequence at the step at `index`.
+    pub fn new(index: usize) -> Self {
+        Self { index, attempt: 0 }
+    }
+
+    /// Decide what to do next, given whether the run was cancelled.
+    ///
+    /// Steps for which `is_satisfied` holds are skipped, moving on to the
+    /// following step.
+    pub fn next(
+        &mut self,
+        commands: &[Command],
+        cancelled: bool,
+        is_satisfied: impl Fn(&Command) -> bool,
+    ) -> Next {
+        if cancelled {
+            return Next::Cancelled((self.index < commands.len()).then_some(self.index));
+        }
+        let Some(cmd) = commands.get(self.index) else {
+            return Next::Done;
+        };
+
+        if is_satisfied(cmd) {
+            let index = self.index;
+            self.advance();
+            return Next::Skip(index);
+        }
+
+        Next::Run {
+            index: self.index,
+            attempt: self.attempt,
+        }
+    }
+
+    /// Record how the running attempt ended and decide how the run goes on.
+    pub fn finished(
+        &mut self,
+        commands: &[Command],
+        result: &CommandResult,
+        cancelled: bool,
+    ) -> StepOutcome {
+        let outcome = step_outcome(&commands[self.index], result, self.attempt, cancelled);
+        match outcome {
+            StepOutcome::Retry(_) => self.attempt += 1,
+            StepOutcome::Continue(_) => self.advance(),
+            StepOutcome::Cancelled | StepOutcome::Failed => {}
+        }
+        outcome
+    }
+
+    /// Go on with the next step, e.g. after a dry run of the current one.
+    pub fn advance(&mut self) {
+        self.index += 1;
+        self.attempt = 0;
+    }
+}
+
+/// Decide how the run goes on after `attempt` of `cmd` ended with `result`.
+fn step_outcome(
+    cmd: &Command,
+    result: &CommandResult,
+    attempt: u32,
+    cancelled: bool,
+) -> StepOutcome {
+    if cancelled {
+        StepOutcome::Cancelled
+    } else if matches!(result, CommandResult::Success) {
+        StepOutcome::Continue(TaskStatus::Success)
+    } else if attempt < cmd.retries {
+        StepOutcome::Retry(retry_delay(cmd, attempt))
+    } else if cmd.allow_failure {
+        StepOutcome::Continue(TaskStatus::Warning)
+    } else {
+        StepOutcome::Failed
+    }
+}
+
+/// Delay before retrying `cmd` after `attempt` failed, doubling every time.
+fn retry_delay(cmd: &Command, attempt: u32) -> Duration {
+    cmd.retry_delay.saturating_mul(2u32.saturating_pow(attempt))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FAILURE: CommandResult = CommandResult::Failure {
+        exit_code: Some(1),
+        signal: None,
+    };
+
+    fn step(description: &str) -> Command {
+        Command::builder()
+            .normal()
+            .program("true")
+            .description(description)
+            .build()
+    }
+
+    fn never_satisfied(_: &Command) -> bool {
+        false
+    }
+
+    #[test]
+    fn test_sequence_runs_steps_in_order() {
+        let commands = [step("first"), step("second")];
+        let mut sequence = Sequence::new(0);
+
+        for index in 0..commands.len() {
+            assert_eq!(
+                sequence.next(&commands, false, never_satisfied),
+                Next::Run { index, attempt: 0 }
+            );
+            assert_eq!(
+                sequence.finished(&commands, &CommandResult::Success, false),
+                StepOutcome::Continue(TaskStatus::Success)
+            );
+        }
+        assert_eq!(sequence.next(&commands, false, never_satisfied), Next::Done);
+
+        // Retrying a failed run starts at the failed step
+        let mut sequence = Sequence::new(1);
+        assert_eq!(
+            sequence.next(&commands, false, never_satisfied),
+            Next::Run {
+                index: 1,
+                attempt: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_sequence_skips_satisfied_steps() {
+        let commands = [step("installed"), step("missing")];
+        let is_satisfied = |cmd: &Command| cmd.description == "installed";
+        let mut sequence = Sequence::new(0);
+
+        assert_eq!(sequence.next(&commands, false, is_satisfied), Next::Skip(0));
+        assert_eq!(
+            sequence.next(&commands, false, is_satisfied),
+            Next::Run {
+                index: 1,
+                attempt: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_sequence_retries_with_backoff() {
+        let commands = [Command::builder()
+            .normal()
+            .program("false")
+            .description("Retried")
+            .retries(2)
+            .retry_delay(Duration::from_secs(2))
+            .build()];
+        let mut sequence = Sequence::new(0);
+
+        for (attempt, delay) in [(0, 2), (1, 4)] {
+            assert_eq!(
+                sequence.next(&commands, false, never_satisfied),
+                Next::Run { index: 0, attempt }
+            );
+            assert_eq!(
+                sequence.finished(&commands, &FAILURE, false),
+                StepOutcome::Retry(Duration::from_secs(delay))
+            );
+        }
+        assert_eq!(
+            sequence.next(&commands, false, never_satisfied),
+            Next::Run {
+                index: 0,
+                attempt: 2
+            }
+        );
+        assert_eq!(
+            sequence.finished(&commands, &FAILURE, false),
+            StepOutcome::Failed
+        );
+    }
+
+    #[test]
+    fn test_sequence_continues_after_allowed_failure() {
+        let commands = [
+            Command::builder()
+                .normal()
+                .program("false")
+                .description("Optional")
+                .allow_failure()
+                .build(),
+            step("next"),
+        ];
+        let mut sequence = Sequence::new(0);
+
+        sequence.next(&commands, false, never_satisfied);
+        assert_eq!(
+            sequence.finished(&commands, &FAILURE, false),
+            StepOutcome::Continue(TaskStatus::Warning)
+        );
+        assert_eq!(
+            sequence.next(&commands, false, never_satisfied),
+            Next::Run {
+                index: 1,
+                attempt: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_sequence_cancelled() {
+        let commands = [step("first"), step("second")];
+        let mut sequence = Sequence::new(0);
+
+        // Cancelling wins over retries and even a successful step
+        sequence.next(&commands, false, never_satisfied);
+        assert_eq!(
+            sequence.finished(&commands, &CommandResult::Success, true),
+            StepOutcome::Cancelled
+        );
+        assert_eq!(
+            sequence.next(&commands, true, never_satisfied),
+            Next::Cancelled(Some(0))
+        );
+
+        let mut sequence = Sequence::new(commands.len());
+        assert_eq!(
+            sequence.next(&commands, true, never_satisfied),
+            Next::Cancelled(None)
+        );
+    }
+}
@@ -0,0 +1,470 @@
+//! Process spawning for the runners.
+//!
+//! Both runners start step processes through [`CommandSpawner`] and collect
+//! their output with [`run_process`], so tests can swap real processes for
+//! scripted ones and check the runners' handling of success, failure and
+//! cancellation without running pacman.
+//!
+//! ## Output ordering
+//!
+//! A process may exit before its last output has been read. The exit result
+//! is therefore only made available once both output streams have reached
+//! end of file, and [`ProcessWatch::poll`] hands out the result only
+//! together with, or after, every line the process wrote. A step is never
+//! finalized while output of it is still in flight.
+
+use super::command::CommandResult;
+use log::{error, warn};
+use std::io::{self, Read, Write};
+use std::os::unix::process::CommandExt;
+use std::path::Path;
+use std::process::{Child, ExitStatus, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use xero_auth::utils::read_buffer_with_line_processing;
+
+/// Process to start for a step, with its command already resolved.
+pub struct SpawnRequest<'a> {
+    pub program: &'a str,
+    pub args: &'a [String],
+    /// Extra environment variables
+    pub env: &'a [(String, String)],
+    /// PATH override, such as the one with the sudo shim
+    pub path: Option<String>,
+    pub cwd: Option<&'a Path>,
+    /// Pipe stdin so input can be typed for the process, instead of inheriting it
+    pub stdin: bool,
+    /// Lead a new process group, so signals reach the process' children
+    pub process_group: bool,
+}
+
+/// Starts the processes of steps.
+pub trait CommandSpawner {
+    /// Start a process with piped stdout and stderr.
+    fn spawn(&self, request: &SpawnRequest) -> io::Result<Box<dyn SpawnedProcess>>;
+}
+
+/// A started process, owned by the thread that waits for it.
+pub trait SpawnedProcess: Send {
+    /// Process ID, used to send signals.
+    fn id(&self) -> u32;
+    /// Input pipe, if stdin was requested.
+    fn take_stdin(&mut self) -> Option<Box<dyn Write>>;
+    fn take_stdout(&mut self) -> Option<Box<dyn Read + Send>>;
+    fn take_stderr(&mut self) -> Option<Box<dyn Read + Send>>;
+    /// Block until the process has exited.
+    fn wait(&mut self) -> io::Result<ExitStatus>;
+}
+
+/// Spawns real processes with [`std::process::Command`].
+pub struct SystemSpawner;
+
+impl CommandSpawner for SystemSpawner {
+    fn spawn(&self, request: &SpawnRequest) -> io::Result<Box<dyn SpawnedProcess>> {
+        let mut process = std::process::Command::new(request.program);
+        process.args(request.args);
+        if request.process_group {
+            process.process_group(0);
+        }
+        if let Some(path) = &request.path {
+            process.env("PATH", path);
+        }
+        if let Some(cwd) = request.cwd {
+            process.current_dir(cwd);
+        }
+        process.envs(request.env.iter().map(|(key, value)| (key, value)));
+
+        process.stdout(Stdio::piped());
+        process.stderr(Stdio::piped());
+        if request.stdin {
+            process.stdin(Stdio::piped());
+        }
+
+        Ok(Box::new(process.spawn()?))
+    }
+}
+
+impl SpawnedProcess for Child {
+    fn id(&self) -> u32 {
+        Child::id(self)
+    }
+
+    fn take_stdin(&mut self) -> Option<Box<dyn Write>> {
+        self.stdin
+            .take()
+            .map(|stdin| Box::new(stdin) as Box<dyn Write>)
+    }
+
+    fn take_stdout(&mut self) -> Option<Box<dyn Read + Send>> {
+        self.stdout
+            .take()
+            .map(|stdout| Box::new(stdout) as Box<dyn Read + Send>)
+    }
+
+    fn take_stderr(&mut self) -> Option<Box<dyn Read + Send>> {
+        self.stderr
+            .take()
+            .map(|stderr| Box::new(stderr) as Box<dyn Read + Send>)
+    }
+
+    fn wait(&mut self) -> io::Result<ExitStatus> {
+        Child::wait(self)
+    }
+}
+
+/// Pass the output of `process` on line by line until both streams are
+/// closed, then wait for it to exit.
+///
+/// The result is only returned once both streams have reached end of file,
+/// so all output has been passed on by then, even if the process exited
+/// before its last lines were read. Reading a stream stops early if its
+/// callback returns false.
+pub fn run_process(
+    mut process: Box<dyn SpawnedProcess>,
+    on_stdout: impl FnMut(String) -> bool + Send + 'static,
+    on_stderr: impl FnMut(String) -> bool + Send + 'static,
+) -> CommandResult {
+    let stdout_handle = process
+        .take_stdout()
+        .map(|stdout| read_stream(stdout, "stdout", on_stdout));
+    let stderr_handle = process
+        .take_stderr()
+        .map(|stderr| read_stream(stderr, "stderr", on_stderr));
+
+    for handle in [stdout_handle, stderr_handle].into_iter().flatten() {
+        if let Err(e) = handle.join() {
+            warn!("Error joining output reader thread: {:?}", e);
+        }
+    }
+
+    match process.wait() {
+        Ok(status) => CommandResult::from_status(status),
+        Err(e) => {
+            error!("Error waiting for process: {}", e);
+            CommandResult::Failure {
+                exit_code: None,
+                signal: None,
+            }
+        }
+    }
+}
+
+/// Process read on a background thread, polled from the main loop.
+pub struct ProcessWatch {
+    result: Arc<Mutex<Option<CommandResult>>>,
+    stdout: Receiver<String>,
+    stderr: Receiver<String>,
+}
+
+/// Output received by a [`ProcessWatch`] since its last poll.
+#[derive(Debug)]
+pub struct PolledOutput {
+    /// Stdout lines, each with its line ending
+    pub stdout: Vec<String>,
+    pub stderr: String,
+    /// Set once the process has exited and all of its output is included
+    pub result: Option<CommandResult>,
+}
+
+impl ProcessWatch {
+    /// Read the output of `process` and wait for it on a background thread.
+    pub fn start(process: Box<dyn SpawnedProcess>) -> Self {
+        let result = Arc::new(Mutex::new(None));
+        let (stdout_tx, stdout) = mpsc::channel();
+        let (stderr_tx, stderr) = mpsc::channel();
+
+        let result_clone = result.clone();
+        thread::spawn(move || {
+            let exit_result = run_process(
+                process,
+                move |text| match stdout_tx.send(text) {
+                    Ok(()) => true,
+                    Err(e) => {
+                        warn!("Failed to send stdout chunk to channel: {}", e);
+                        false
+                    }
+                },
+                move |text| match stderr_tx.send(text) {
+                    Ok(()) => true,
+                    Err(e) => {
+                        warn!("Failed to send stderr chunk to channel: {}", e);
+                        false
+                    }
+                },
+            );
+            *result_clone.lock().unwrap() = Some(exit_result);
+        });
+
+        Self {
+            result,
+            stdout,
+            stderr,
+        }
+    }
+
+    /// Take the output received so far, and the result once there is one.
+    ///
+    /// The result is taken before the output is drained. It is only set
+    /// after the reader threads have sent everything, so once it is seen,
+    /// the drained output is complete.
+    pub fn poll(&self) -> PolledOutput {
+        let result = self.result.lock().unwrap().take();
+        PolledOutput {
+            stdout: self.stdout.try_iter().collect(),
+            stderr: self.stderr.try_iter().collect(),
+            result,
+        }
+    }
+}
+
+/// Read one output stream on its own thread.
+fn read_stream(
+    stream: Box<dyn Read + Send>,
+    name: &'static str,
+    on_line: impl FnMut(String) -> bool + Send + 'static,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        read_buffer_with_line_processing(stream, on_line, |e| {
+            warn!("Error reading {}: {}", name, e);
+        });
+    })
+}
+
+/// Scripted processes for testing the runners.
+#[cfg(test)]
+pub mod fake {
+    use super::*;
+    use std::cell::RefCell;
+    use std::collections::VecDeque;
+    use std::os::unix::process::ExitStatusExt;
+    use std::time::Duration;
+
+    /// Behaviour of one fake process.
+    pub struct FakeScript {
+        stdout: Vec<String>,
+        stderr: String,
+        status: ExitStatus,
+        late_stdout: Duration,
+        on_spawn: Option<Box<dyn FnOnce()>>,
+    }
+
+    impl FakeScript {
+        /// Process that exits with `code`.
+        pub fn exit(code: i32) -> Self {
+            Self {
+                stdout: Vec::new(),
+                stderr: String::new(),
+                status: ExitStatus::from_raw(code << 8),
+                late_stdout: Duration::ZERO,
+                on_spawn: None,
+            }
+        }
+
+        /// Print these lines to stdout.
+        pub fn stdout(mut self, lines: &[&str]) -> Self {
+            self.stdout = lines.iter().map(|line| format!("{}\n", line)).collect();
+            self
+        }
+
+        pub fn stderr(mut self, text: &str) -> Self {
+            self.stderr = text.to_string();
+            self
+        }
+
+        /// Hold back the last stdout line for `delay` after the process has
+        /// exited.
+        pub fn late_stdout(mut self, delay: Duration) -> Self {
+            self.late_stdout = delay;
+            self
+        }
+
+        /// Call `callback` when the process is spawned.
+        pub fn on_spawn(mut self, callback: impl FnOnce() + 'static) -> Self {
+            self.on_spawn = Some(Box::new(callback));
+            self
+        }
+    }
+
+    /// Spawns fake processes from a script per spawn, in order.
+    #[derive(Default)]
+    pub struct FakeSpawner {
+        scripts: RefCell<VecDeque<FakeScript>>,
+        /// Programs spawned so far, in order
+        pub spawned: RefCell<Vec<String>>,
+    }
+
+    impl FakeSpawner {
+        pub fn new(scripts: impl IntoIterator<Item = FakeScript>) -> Self {
+            Self {
+                scripts: RefCell::new(scripts.into_iter().collect()),
+                spawned: RefCell::default(),
+            }
+        }
+    }
+
+    impl CommandSpawner for FakeSpawner {
+        fn spawn(&self, request: &SpawnRequest) -> io::Result<Box<dyn SpawnedProcess>> {
+            let script = self.scripts.borrow_mut().pop_front().ok_or_else(|| {
+                io::Error::new(io::ErrorKind::NotFound, "no fake process scripted")
+            })?;
+            self.spawned.borrow_mut().push(request.program.to_string());
+            if let Some(callback) = script.on_spawn {
+                callback();
+            }
+
+            Ok(Box::new(FakeProcess {
+                stdout: Some(DelayedReader {
+                    chunks: script.stdout.into_iter().map(String::into_bytes).collect(),
+                    delay_before_last: script.late_stdout,
+                }),
+                stderr: Some(script.stderr.into_bytes()),
+                status: script.status,
+            }))
+        }
+    }
+
+    /// Process that has exited by the time it is waited for.
+    ///
+    /// It has no real process ID, so it must never be signalled.
+    struct FakeProcess {
+        stdout: Option<DelayedReader>,
+        stderr: Option<Vec<u8>>,
+        status: ExitStatus,
+    }
+
+    impl SpawnedProcess for FakeProcess {
+        fn id(&self) -> u32 {
+            0
+        }
+
+        fn take_stdin(&mut self) -> Option<Box<dyn Write>> {
+            None
+        }
+
+        fn take_stdout(&mut self) -> Option<Box<dyn Read + Send>> {
+            self.stdout
+                .take()
+                .map(|stdout| Box::new(stdout) as Box<dyn Read + Send>)
+        }
+
+        fn take_stderr(&mut self) -> Option<Box<dyn Read + Send>> {
+            self.stderr
+                .take()
+                .map(|stderr| Box::new(io::Cursor::new(stderr)) as Box<dyn Read + Send>)
+        }
+
+        fn wait(&mut self) -> io::Result<ExitStatus> {
+            Ok(self.status)
+        }
+    }
+
+    /// Reader returning one chunk per read, waiting before the last one.
+    struct DelayedReader {
+        chunks: VecDeque<Vec<u8>>,
+        delay_before_last: Duration,
+    }
+
+    impl Read for DelayedReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let Some(chunk) = self.chunks.pop_front() else {
+                return Ok(0);
+            };
+            if self.chunks.is_empty() {
+                thread::sleep(self.delay_before_last);
+            }
+            let len = chunk.len().min(buf.len());
+            buf[..len].copy_from_slice(&chunk[..len]);
+            if len < chunk.len() {
+                self.chunks.push_front(chunk[len..].to_vec());
+            }
+            Ok(len)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fake::{FakeScript, FakeSpawner};
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    #[test]
+    fn test_run_process_reads_output_written_after_exit() {
+        let spawner = FakeSpawner::new([FakeScript::exit(0)
+            .stdout(&["first", "last"])
+            .stderr("warning\n")
+            .late_stdout(Duration::from_millis(50))]);
+        let process = spawner
+            .spawn(&SpawnRequest {
+                program: "fake",
+                args: &[],
+                env: &[],
+                path: None,
+                cwd: None,
+                stdin: false,
+                process_group: false,
+            })
+            .unwrap();
+
+        let stdout = Arc::new(Mutex::new(String::new()));
+        let stderr = Arc::new(Mutex::new(String::new()));
+        let (stdout_clone, stderr_clone) = (stdout.clone(), stderr.clone());
+        let result = run_process(
+            process,
+            move |line| {
+                stdout_clone.lock().unwrap().push_str(&line);
+                true
+            },
+            move |line| {
+                stderr_clone.lock().unwrap().push_str(&line);
+                true
+            },
+        );
+
+        assert!(matches!(result, CommandResult::Success));
+        assert_eq!(*stdout.lock().unwrap(), "first\nlast\n");
+        assert_eq!(*stderr.lock().unwrap(), "warning\n");
+    }
+
+    #[test]
+    fn test_process_watch_gives_result_after_late_output() {
+        let spawner = FakeSpawner::new([FakeScript::exit(3)
+            .stdout(&["first", "last"])
+            .late_stdout(Duration::from_millis(100))]);
+        let process = spawner
+            .spawn(&SpawnRequest {
+                program: "fake",
+                args: &[],
+                env: &[],
+                path: None,
+                cwd: None,
+                stdin: false,
+                process_group: false,
+            })
+            .unwrap();
+
+        // The fake has exited at once, but its last line is still held back
+        let watch = ProcessWatch::start(process);
+        let mut stdout = String::new();
+        let result = loop {
+            let polled = watch.poll();
+            stdout.extend(polled.stdout);
+            if let Some(result) = polled.result {
+                break result;
+            }
+            assert!(!stdout.contains("last"));
+            thread::sleep(Duration::from_millis(10));
+        };
+
+        assert_eq!(stdout, "first\nlast\n");
+        assert!(matches!(
+            result,
+            CommandResult::Failure {
+                exit_code: Some(3),
+                ..
+            }
+        ));
+    }
+}
@@ -0,0 +1,92 @@
+//! Tagging of stderr lines by severity.
+//!
+//! AUR helpers and build tools print plenty of informational text to
+//! stderr. Instead of showing all of it as an error, each line is matched
+//! against error and warning patterns; everything else is shown muted.
+
+/// Tag of stderr lines that look like errors.
+pub const ERROR_TAG: &str = "error";
+
+/// Tag of stderr lines that look like warnings.
+pub const WARNING_TAG: &str = "warning";
+
+/// Tag of other stderr lines.
+pub const MUTED_TAG: &str = "stderr-muted";
+
+/// Substrings marking a stderr line as an error or a warning.
+///
+/// Patterns are matched case-insensitively. The defaults suit pacman and
+/// AUR helpers; steps running other tools can set their own with
+/// [`CommandBuilder::stderr_patterns`](super::command::CommandBuilder::stderr_patterns).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StderrPatterns {
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+impl Default for StderrPatterns {
+    fn default() -> Self {
+        Self::new(
+            &["error:", "failed", "fatal:", "\u{2717}"],
+            &["warning:", "warn:"],
+        )
+    }
+}
+
+impl StderrPatterns {
+    /// Create patterns from error and warning substrings.
+    pub fn new(errors: &[&str], warnings: &[&str]) -> Self {
+        let lowercase = |patterns: &[&str]| patterns.iter().map(|p| p.to_lowercase()).collect();
+        Self {
+            errors: lowercase(errors),
+            warnings: lowercase(warnings),
+        }
+    }
+
+    /// Text tag for a line of stderr output.
+    pub fn tag_for(&self, line: &str) -> &'static str {
+        let line = line.to_lowercase();
+        if self.errors.iter().any(|p| line.contains(p.as_str())) {
+            ERROR_TAG
+        } else if self.warnings.iter().any(|p| line.contains(p.as_str())) {
+            WARNING_TAG
+        } else {
+            MUTED_TAG
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tag_for_default_patterns() {
+        let patterns = StderrPatterns::default();
+        assert_eq!(
+            patterns.tag_for("error: failed to commit transaction\n"),
+            ERROR_TAG
+        );
+        assert_eq!(
+            patterns.tag_for("==> ERROR: A failure occurred\n"),
+            ERROR_TAG
+        );
+        assert_eq!(patterns.tag_for("\u{2717} build\n"), ERROR_TAG);
+        assert_eq!(
+            patterns.tag_for("warning: linux-6.10 is up to date -- skipping\n"),
+            WARNING_TAG
+        );
+        assert_eq!(
+            patterns.tag_for(":: Resolving dependencies...\n"),
+            MUTED_TAG
+        );
+    }
+
+    #[test]
+    fn test_tag_for_custom_patterns() {
+        let patterns = StderrPatterns::new(&["E:"], &["W:"]);
+        assert_eq!(patterns.tag_for("E: Unable to locate package\n"), ERROR_TAG);
+        assert_eq!(patterns.tag_for("w: something odd\n"), WARNING_TAG);
+        assert_eq!(patterns.tag_for("error: not an error here\n"), MUTED_TAG);
+    }
+}
@@ -3,28 +3,93 @@
 //! This module provides the UI components for displaying command execution progress,
 //! including task items, status icons, and scroll management.
 
+use super::ansi;
 use super::command::TaskStatus;
+use super::executor::LogWriter;
+use super::stderr::{self, StderrPatterns};
 use adw::prelude::*;
+use gtk4::glib;
 use gtk4::{
-    Box as GtkBox, Button, Image, Label, Revealer, ScrolledWindow, TextBuffer, TextView,
-    ToggleButton, Window,
+    Box as GtkBox, Button, Entry, Image, Label, ProgressBar, Revealer, ScrolledWindow, TextBuffer,
+    TextMark, TextView, ToggleButton, Window,
 };
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// Steps that must have finished before a remaining time is estimated.
+const MIN_STEPS_FOR_ESTIMATE: usize = 2;
+
+/// Marker in front of a step header whose output is shown.
+const EXPANDED_MARKER: &str = "\u{25be} ";
+
+/// Marker in front of a step header whose output is folded away.
+const COLLAPSED_MARKER: &str = "\u{25b8} ";
+
+/// Lines kept in the output view unless configured otherwise.
+const DEFAULT_OUTPUT_LINE_LIMIT: usize = 10_000;
+
+/// First line of the output view once old lines were dropped.
+const TRUNCATED_MARKER: &str = "[... earlier output truncated ...]\n";
+
+/// Output of one step attempt in the output view.
+struct OutputSection {
+    /// Index of the step the output belongs to
+    step: usize,
+    /// Position of the fold marker in front of the header
+    marker: TextMark,
+    /// Start of the output below the header
+    body: TextMark,
+    collapsed: Cell<bool>,
+}
 
 /// Container for all task runner dialog widgets.
 pub struct TaskRunnerWidgets {
     pub window: Window,
     pub title_label: Label,
+    pub progress_bar: ProgressBar,
+    pub time_label: Label,
     #[allow(dead_code)]
     // Stored for potential future use, currently only used during initialization
     pub task_list_container: GtkBox,
     pub scrolled_window: ScrolledWindow,
     pub cancel_button: Button,
+    pub pause_button: Button,
     pub close_button: Button,
+    pub save_log_button: Button,
+    pub retry_button: Button,
     pub task_items: Vec<TaskItem>,
+    /// Progress weight of each step, see [`Command::weight`](super::Command::weight)
+    step_weights: Vec<f64>,
     pub sidebar_toggle: ToggleButton,
+    pub ansi_color_toggle: ToggleButton,
     pub sidebar_revealer: Revealer,
     pub output_text_view: TextView,
     pub output_text_buffer: TextBuffer,
+    pub stdin_entry: Entry,
+    pub reboot_banner: adw::Banner,
+    log: RefCell<Option<LogWriter>>,
+    current_step: Cell<usize>,
+    /// When the first step was spawned
+    started_at: Cell<Option<Instant>>,
+    /// When the running step was started
+    step_started_at: Cell<Option<Instant>>,
+    /// When the running command was paused, while it is
+    paused_at: Cell<Option<Instant>>,
+    /// Durations of the finished steps
+    step_durations: RefCell<Vec<Duration>>,
+    /// Source updating the elapsed time every second
+    timer: RefCell<Option<glib::SourceId>>,
+    /// Step sections of the output, in order
+    sections: RefCell<Vec<OutputSection>>,
+    /// Fold a step's output once it succeeds
+    fold_succeeded: Cell<bool>,
+    /// A completed step needs a reboot to take effect
+    reboot_recommended: Cell<bool>,
+    /// Lines kept in the output view before the oldest are dropped
+    line_limit: usize,
+    /// Whether old output was dropped and the truncation marker shown
+    truncated: Cell<bool>,
 }
 
 impl TaskRunnerWidgets {
@@ -33,28 +98,61 @@ impl TaskRunnerWidgets {
     pub fn new(
         window: Window,
         title_label: Label,
+        progress_bar: ProgressBar,
+        time_label: Label,
         task_list_container: GtkBox,
         scrolled_window: ScrolledWindow,
         cancel_button: Button,
+        pause_button: Button,
         close_button: Button,
+        save_log_button: Button,
+        retry_button: Button,
         task_items: Vec<TaskItem>,
+        step_weights: Vec<f64>,
         sidebar_toggle: ToggleButton,
+        ansi_color_toggle: ToggleButton,
         sidebar_revealer: Revealer,
         output_text_view: TextView,
         output_text_buffer: TextBuffer,
+        stdin_entry: Entry,
+        reboot_banner: adw::Banner,
     ) -> Self {
         let widgets = Self {
             window,
             title_label,
+            progress_bar,
+            time_label,
             task_list_container,
             scrolled_window,
             cancel_button,
+            pause_button,
             close_button,
+            save_log_button,
+            retry_button,
             task_items,
+            step_weights,
             sidebar_toggle,
+            ansi_color_toggle,
             sidebar_revealer,
             output_text_view,
             output_text_buffer,
+            stdin_entry,
+            reboot_banner,
+            log: RefCell::new(None),
+            current_step: Cell::new(0),
+            started_at: Cell::new(None),
+            step_started_at: Cell::new(None),
+            paused_at: Cell::new(None),
+            step_durations: RefCell::new(Vec::new()),
+            timer: RefCell::new(None),
+            sections: RefCell::new(Vec::new()),
+            fold_succeeded: Cell::new(true),
+            reboot_recommended: Cell::new(false),
+            line_limit: crate::config::user::get()
+                .output_line_limit
+                .unwrap_or(DEFAULT_OUTPUT_LINE_LIMIT)
+                .max(1),
+            truncated: Cell::new(false),
         };
 
         // Set up color tags for output
@@ -95,6 +193,38 @@ impl TaskRunnerWidgets {
         error_tag.set_property("foreground", "rgb(231, 76, 60)");
         error_tag.set_property("weight", 700);
         tag_table.add(&error_tag);
+
+        // Warning tag (yellow), for stderr lines that look like warnings
+        let warning_tag = TextTag::new(Some(stderr::WARNING_TAG));
+        warning_tag.set_property("foreground", "rgb(241, 196, 15)");
+        tag_table.add(&warning_tag);
+
+        // Muted tag (dim gray), for informational stderr lines
+        let muted_tag = TextTag::new(Some(stderr::MUTED_TAG));
+        muted_tag.set_property("foreground", "rgb(150, 150, 150)");
+        tag_table.add(&muted_tag);
+
+        // Output of folded steps
+        let folded_tag = TextTag::new(Some("folded"));
+        folded_tag.set_property("invisible", true);
+        tag_table.add(&folded_tag);
+
+        // ANSI foreground colors from command output
+        let ansi_colors = [
+            "rgb(128, 128, 128)",
+            "rgb(231, 76, 60)",
+            "rgb(46, 204, 113)",
+            "rgb(241, 196, 15)",
+            "rgb(100, 149, 237)",
+            "rgb(155, 89, 182)",
+            "rgb(26, 188, 156)",
+            "rgb(236, 240, 241)",
+        ];
+        for (name, color) in ansi::COLOR_TAGS.iter().zip(ansi_colors) {
+            let tag = TextTag::new(Some(name));
+            tag.set_property("foreground", color);
+            tag_table.add(&tag);
+        }
     }
 
     /// Bind the sidebar toggle button to the revealer.
@@ -171,6 +301,12 @@ impl TaskItem {
 
     /// Update the status of this task item.
     pub fn set_status(&self, status: TaskStatus) {
+        if status == TaskStatus::Skipped {
+            self.status_icon.add_css_class("dim-label");
+        } else {
+            self.status_icon.remove_css_class("dim-label");
+        }
+
         match status {
             TaskStatus::Pending => {
                 self.spinner_icon.set_visible(false);
@@ -190,11 +326,21 @@ impl TaskItem {
                 self.status_icon.set_icon_name(Some("circle-xmark"));
                 self.status_icon.set_visible(true);
             }
+            TaskStatus::Warning => {
+                self.spinner_icon.set_visible(false);
+                self.status_icon.set_icon_name(Some("circle-exclamation"));
+                self.status_icon.set_visible(true);
+            }
             TaskStatus::Cancelled => {
                 self.spinner_icon.set_visible(false);
                 self.status_icon.set_icon_name(Some("circle-stop"));
                 self.status_icon.set_visible(true);
             }
+            TaskStatus::Skipped => {
+                self.spinner_icon.set_visible(false);
+                self.status_icon.set_icon_name(Some("circle-noth-symbolic"));
+                self.status_icon.set_visible(true);
+            }
         }
     }
 }
@@ -236,6 +382,115 @@ impl TaskRunnerWidgets {
             task_item.set_status(status);
             self.scroll_to_task(index);
         }
+
+        match status {
+            TaskStatus::Running => {
+                self.current_step.set(index);
+                self.update_progress(weighted_fraction(&self.step_weights, index, 0.0));
+                // Retries keep the start of the first attempt
+                if self.step_started_at.get().is_none() {
+                    self.step_started_at.set(Some(Instant::now()));
+                }
+            }
+            TaskStatus::Success | TaskStatus::Warning => {
+                self.update_progress(weighted_fraction(&self.step_weights, index + 1, 0.0));
+                if let Some(started) = self.step_started_at.take() {
+                    self.step_durations.borrow_mut().push(started.elapsed());
+                }
+                // Warnings stay expanded like failures
+                if status == TaskStatus::Success && self.fold_succeeded.get() {
+                    self.set_step_collapsed(index, true);
+                }
+            }
+            TaskStatus::Skipped => {
+                // The skip notice is written to the output after this
+                self.current_step.set(index);
+                self.update_progress(weighted_fraction(&self.step_weights, index + 1, 0.0));
+            }
+            _ => {
+                self.step_started_at.set(None);
+            }
+        }
+    }
+
+    /// Start showing the elapsed time, updated once per second.
+    ///
+    /// Does nothing if the timer is already running.
+    pub fn start_timer(self: &Rc<Self>) {
+        if self.started_at.get().is_some() {
+            return;
+        }
+        self.started_at.set(Some(Instant::now()));
+        self.time_label.set_visible(true);
+        self.update_time_label();
+
+        let widgets = Rc::downgrade(self);
+        let source = glib::timeout_add_seconds_local(1, move || {
+            let Some(widgets) = widgets.upgrade() else {
+                return glib::ControlFlow::Break;
+            };
+            widgets.update_time_label();
+            glib::ControlFlow::Continue
+        });
+        *self.timer.borrow_mut() = Some(source);
+    }
+
+    /// Stop updating the elapsed time, leaving the total on screen.
+    pub fn stop_timer(&self) {
+        if let Some(source) = self.timer.borrow_mut().take() {
+            source.remove();
+        }
+        if let Some(started) = self.started_at.get() {
+            self.time_label
+                .set_text(&format!("Took {}", format_duration(started.elapsed())));
+        }
+    }
+
+    /// Show the elapsed time and, once enough steps finished, an estimate.
+    fn update_time_label(&self) {
+        // The time stands still while the command is paused
+        if self.paused_at.get().is_some() {
+            return;
+        }
+        let Some(started) = self.started_at.get() else {
+            return;
+        };
+
+        let mut text = format!("Elapsed {}", format_duration(started.elapsed()));
+        let finished = self.step_durations.borrow().len();
+        let remaining_steps = self.task_items.len().saturating_sub(finished);
+        let current_elapsed = self
+            .step_started_at
+            .get()
+            .map(|step_started| step_started.elapsed())
+            .unwrap_or_default();
+        if let Some(remaining) = estimate_remaining(
+            &self.step_durations.borrow(),
+            remaining_steps,
+            current_elapsed,
+        ) {
+            text.push_str(&format!(
+                " \u{00b7} ~{} remaining",
+                format_duration(remaining)
+            ));
+        }
+        self.time_label.set_text(&text);
+    }
+
+    /// Set the progress bar to the given fraction of the whole run.
+    fn update_progress(&self, fraction: f64) {
+        self.progress_bar.set_fraction(fraction.clamp(0.0, 1.0));
+    }
+
+    /// Move the progress bar within the current step.
+    ///
+    /// `fraction` is the progress of the running step, between 0.0 and 1.0.
+    pub fn update_substep_progress(&self, fraction: f64) {
+        self.update_progress(weighted_fraction(
+            &self.step_weights,
+            self.current_step.get(),
+            fraction,
+        ));
     }
 
     /// Set the dialog title.
@@ -246,13 +501,69 @@ impl TaskRunnerWidgets {
     /// Disable the cancel button.
     pub fn disable_cancel(&self) {
         self.cancel_button.set_sensitive(false);
+        self.pause_button.set_sensitive(false);
+    }
+
+    /// Allow pausing while a command process is running.
+    pub fn set_pause_available(&self, available: bool) {
+        self.pause_button
+            .set_sensitive(available && self.cancel_button.is_sensitive());
+    }
+
+    /// Show the input entry while an interactive command runs.
+    ///
+    /// The output is revealed so the command's prompts are visible.
+    pub fn set_input_available(&self, available: bool) {
+        self.stdin_entry.set_visible(available);
+        self.stdin_entry.set_text("");
+        if available {
+            self.sidebar_toggle.set_active(true);
+            self.stdin_entry.grab_focus();
+        }
+    }
+
+    /// Show whether the running command is paused.
+    ///
+    /// Time spent paused is left out of the elapsed time and the estimate.
+    pub fn set_paused(&self, paused: bool) {
+        if paused {
+            if self.paused_at.get().is_none() {
+                self.paused_at.set(Some(Instant::now()));
+            }
+            self.pause_button.set_label("Resume");
+            self.pause_button
+                .set_tooltip_text(Some("Resume the paused command"));
+            self.progress_bar.set_text(Some("Paused"));
+        } else {
+            self.pause_button.set_label("Pause");
+            self.pause_button
+                .set_tooltip_text(Some("Pause the running command"));
+            if let Some(paused_at) = self.paused_at.take() {
+                self.skip_paused_time(paused_at.elapsed());
+            }
+        }
+        self.progress_bar.set_show_text(paused);
+    }
+
+    /// Move the start times forward by the time spent paused.
+    fn skip_paused_time(&self, paused: Duration) {
+        if let Some(started) = self.started_at.get() {
+            self.started_at.set(Some(started + paused));
+        }
+        if let Some(step_started) = self.step_started_at.get() {
+            self.step_started_at.set(Some(step_started + paused));
+        }
+        self.update_time_label();
     }
 
     /// Enable the close button and hide cancel button.
     pub fn enable_close(&self) {
         self.cancel_button.set_visible(false);
+        self.pause_button.set_visible(false);
         self.close_button.set_visible(true);
         self.close_button.set_sensitive(true);
+        self.save_log_button
+            .set_visible(self.log.borrow().is_some());
     }
 
     /// Show completion state with a final message.
@@ -267,35 +578,317 @@ impl TaskRunnerWidgets {
             self.close_button.remove_css_class("suggested-action");
             self.title_label.remove_css_class("success");
             self.title_label.add_css_class("error");
+            self.reveal_failed_output();
+        }
+
+        // Changes of completed steps apply on reboot even if a later one failed
+        if self.reboot_recommended.get() {
+            self.reboot_banner.set_revealed(true);
         }
 
         self.enable_close();
     }
 
+    /// Warn that the steps succeeded but the result failed verification.
+    pub fn show_verification_failure(&self, reason: &str) {
+        self.set_title(&format!("Completed, but verification failed: {}", reason));
+        self.title_label.remove_css_class("success");
+        self.title_label.add_css_class("warning");
+    }
+
+    /// Offer to run the sequence again from the failed step.
+    pub fn offer_retry(&self) {
+        self.retry_button.set_visible(true);
+        self.retry_button.add_css_class("suggested-action");
+    }
+
+    /// Return to the running state for a retry after a failure.
+    ///
+    /// Task statuses are kept, so steps that succeeded stay marked as done.
+    pub fn resume_running(&self) {
+        self.retry_button.set_visible(false);
+        self.close_button.set_visible(false);
+        self.save_log_button.set_visible(false);
+        self.cancel_button.set_visible(true);
+        self.cancel_button.set_sensitive(true);
+        self.pause_button.set_visible(true);
+        self.title_label.remove_css_class("error");
+        self.title_label.remove_css_class("warning");
+        self.reboot_banner.set_revealed(false);
+        // Time the retry on its own
+        self.started_at.set(None);
+        self.step_started_at.set(None);
+        self.paused_at.set(None);
+    }
+
+    /// Show the reboot banner once the sequence has finished.
+    pub fn recommend_reboot(&self) {
+        self.reboot_recommended.set(true);
+    }
+
+    /// Open the output view at the output of the step that failed.
+    ///
+    /// Expands all output if the failed step has no output section.
+    fn reveal_failed_output(&self) {
+        self.sidebar_toggle.set_active(true);
+
+        let failed_step = self.current_step.get();
+        let sections = self.sections.borrow();
+        let Some(section) = sections.iter().rev().find(|s| s.step == failed_step) else {
+            for section in sections.iter() {
+                self.set_section_collapsed(section, false);
+            }
+            return;
+        };
+
+        self.set_section_collapsed(section, false);
+        self.output_text_view
+            .scroll_to_mark(&section.marker, 0.0, true, 0.0, 0.0);
+    }
+
+    /// Start mirroring all output to the given log file.
+    pub fn set_log_writer(&self, log: LogWriter) {
+        *self.log.borrow_mut() = Some(log);
+    }
+
+    /// Path of the current log file, if output is being logged.
+    pub fn log_path(&self) -> Option<std::path::PathBuf> {
+        self.log
+            .borrow()
+            .as_ref()
+            .map(|log| log.path().to_path_buf())
+    }
+
+    /// Text of the output view, including folded step output.
+    pub fn output_text(&self) -> String {
+        let buffer = &self.output_text_buffer;
+        buffer
+            .text(&buffer.start_iter(), &buffer.end_iter(), true)
+            .to_string()
+    }
+
+    /// Write text to the log file only, without showing it in the output view.
+    pub fn write_log(&self, text: &str) {
+        if let Some(log) = self.log.borrow().as_ref() {
+            log.write(text);
+        }
+    }
+
     /// Append text with a specific color tag.
     pub fn append_colored(&self, text: &str, tag_name: &str) {
+        self.write_log(text);
+        self.insert_tagged(text, tag_name);
+        self.trim_output();
+        self.scroll_to_bottom();
+    }
+
+    /// Drop the oldest lines of the output view once it exceeds the line limit.
+    ///
+    /// Lines are dropped in batches of a tenth of the limit rather than one by
+    /// one, and a marker at the top shows that output is missing. The log
+    /// file keeps the full output.
+    fn trim_output(&self) {
+        let buffer = &self.output_text_buffer;
+        let excess = (buffer.line_count().max(0) as usize).saturating_sub(self.line_limit);
+        if excess <= self.line_limit / 10 {
+            return;
+        }
+
+        // Keep the marker as the first line
+        let first_line = i32::from(self.truncated.get());
+        let Some(mut start) = buffer.iter_at_line(first_line) else {
+            return;
+        };
+        let mut end = buffer
+            .iter_at_line(first_line + excess as i32)
+            .unwrap_or_else(|| buffer.end_iter());
+
+        // Steps whose header is dropped can no longer be folded
+        let end_offset = end.offset();
+        self.sections.borrow_mut().retain(|section| {
+            let kept = buffer.iter_at_mark(&section.marker).offset() >= end_offset;
+            if !kept {
+                buffer.delete_mark(&section.marker);
+                buffer.delete_mark(&section.body);
+            }
+            kept
+        });
+
+        buffer.delete(&mut start, &mut end);
+        if !self.truncated.replace(true) {
+            let mut start = buffer.start_iter();
+            self.insert_tagged_at(&mut start, TRUNCATED_MARKER, "timestamp");
+        }
+    }
+
+    /// Append text to the output view only, with a specific color tag.
+    fn insert_tagged(&self, text: &str, tag_name: &str) {
+        let mut end = self.output_text_buffer.end_iter();
+        self.insert_tagged_at(&mut end, text, tag_name);
+    }
+
+    /// Insert text at `iter` with a specific color tag, moving `iter` past it.
+    fn insert_tagged_at(&self, iter: &mut gtk4::TextIter, text: &str, tag_name: &str) {
         // Get start position before insertion
-        let start_offset = self.output_text_buffer.end_iter().offset();
+        let start_offset = iter.offset();
 
         // Insert text
-        let mut end = self.output_text_buffer.end_iter();
-        self.output_text_buffer.insert(&mut end, text);
+        self.output_text_buffer.insert(iter, text);
 
         // Get fresh iterators after insertion
         let start = self.output_text_buffer.iter_at_offset(start_offset);
-        let end_fresh = self.output_text_buffer.end_iter();
 
         // Apply tag
         if let Some(tag) = self.output_text_buffer.tag_table().lookup(tag_name) {
-            self.output_text_buffer.apply_tag(&tag, &start, &end_fresh);
+            self.output_text_buffer.apply_tag(&tag, &start, iter);
         }
+    }
+
+    /// Append command output that may contain ANSI escape sequences.
+    ///
+    /// Colors are rendered as text tags when the color toggle is active, otherwise
+    /// escape sequences are stripped and `tag_name` is used for the whole text.
+    pub fn append_output(&self, text: &str, tag_name: &str) {
+        for line in text.split_inclusive('\n') {
+            self.insert_output(line, tag_name);
+        }
+        self.trim_output();
+        self.scroll_to_bottom();
+    }
+
+    /// Append stderr output, tagging each line by how serious it looks.
+    pub fn append_stderr(&self, text: &str, patterns: &StderrPatterns) {
+        for line in text.split_inclusive('\n') {
+            self.insert_output(line, patterns.tag_for(line));
+        }
+        self.trim_output();
         self.scroll_to_bottom();
     }
 
-    /// Append a command header.
+    /// Log a line of output and insert it into the output view.
+    fn insert_output(&self, line: &str, tag_name: &str) {
+        if !self.ansi_color_toggle.is_active() {
+            let line = strip_ansi_escapes::strip_str(line);
+            self.write_log(&line);
+            self.insert_tagged(&line, tag_name);
+            return;
+        }
+
+        let segments = ansi::parse(line);
+        self.write_log(&segments.iter().map(|s| s.text.as_str()).collect::<String>());
+        for segment in &segments {
+            self.insert_tagged(&segment.text, segment.color.unwrap_or(tag_name));
+        }
+    }
+
+    /// Append a command header, starting a new foldable output section.
+    ///
+    /// The fold marker is shown in the output view only and not logged.
     pub fn append_command_header(&self, description: &str) {
-        let header = format!("\n=== {} ===\n", description);
-        self.append_colored(&header, "header");
+        let header = format!("=== {} ===\n", description);
+        self.write_log(&format!("\n{}", header));
+
+        self.insert_tagged("\n", "header");
+        let buffer = &self.output_text_buffer;
+        let marker = buffer.create_mark(None, &buffer.end_iter(), true);
+        self.insert_tagged(EXPANDED_MARKER, "header");
+        self.insert_tagged(&header, "header");
+        let body = buffer.create_mark(None, &buffer.end_iter(), true);
+
+        self.sections.borrow_mut().push(OutputSection {
+            step: self.current_step.get(),
+            marker,
+            body,
+            collapsed: Cell::new(false),
+        });
+        self.scroll_to_bottom();
+    }
+
+    /// Keep the output of succeeded steps expanded, as in dry runs.
+    pub fn keep_succeeded_output(&self) {
+        self.fold_succeeded.set(false);
+    }
+
+    /// Fold or unfold the output of every attempt of a step.
+    fn set_step_collapsed(&self, step: usize, collapsed: bool) {
+        for section in self.sections.borrow().iter().filter(|s| s.step == step) {
+            self.set_section_collapsed(section, collapsed);
+        }
+    }
+
+    /// Fold or unfold a section's output and update its marker.
+    fn set_section_collapsed(&self, section: &OutputSection, collapsed: bool) {
+        if section.collapsed.replace(collapsed) == collapsed {
+            return;
+        }
+
+        let buffer = &self.output_text_buffer;
+        let start = buffer.iter_at_mark(&section.body);
+        let end = self.section_end(section);
+        if collapsed {
+            buffer.apply_tag_by_name("folded", &start, &end);
+        } else {
+            buffer.remove_tag_by_name("folded", &start, &end);
+        }
+
+        // Swap the marker character
+        let mut marker_start = buffer.iter_at_mark(&section.marker);
+        let mut marker_end = marker_start;
+        marker_end.forward_chars(EXPANDED_MARKER.chars().count() as i32);
+        buffer.delete(&mut marker_start, &mut marker_end);
+        let marker = if collapsed {
+            COLLAPSED_MARKER
+        } else {
+            EXPANDED_MARKER
+        };
+        self.insert_tagged_at(&mut marker_start, marker, "header");
+    }
+
+    /// End of a section's output: the next header, or the end of the buffer.
+    fn section_end(&self, section: &OutputSection) -> gtk4::TextIter {
+        let buffer = &self.output_text_buffer;
+        let sections = self.sections.borrow();
+        let next = sections
+            .iter()
+            .skip_while(|s| s.marker != section.marker)
+            .nth(1);
+
+        match next {
+            Some(next) => {
+                // Stop before the blank line in front of the next header
+                let mut end = buffer.iter_at_mark(&next.marker);
+                end.backward_char();
+                end
+            }
+            None => buffer.end_iter(),
+        }
+    }
+
+    /// Toggle a section's output by clicking its header.
+    pub fn setup_output_folding(self: &Rc<Self>) {
+        let click = gtk4::GestureClick::new();
+        let widgets = Rc::downgrade(self);
+        click.connect_released(move |_, _, x, y| {
+            let Some(widgets) = widgets.upgrade() else {
+                return;
+            };
+            let view = &widgets.output_text_view;
+            let (x, y) =
+                view.window_to_buffer_coords(gtk4::TextWindowType::Widget, x as i32, y as i32);
+            let Some(iter) = view.iter_at_location(x, y) else {
+                return;
+            };
+
+            let buffer = &widgets.output_text_buffer;
+            let sections = widgets.sections.borrow();
+            if let Some(section) = sections
+                .iter()
+                .find(|s| buffer.iter_at_mark(&s.marker).line() == iter.line())
+            {
+                widgets.set_section_collapsed(section, !section.collapsed.get());
+            }
+        });
+        self.output_text_view.add_controller(click);
     }
 
     /// Scroll output view to bottom.
@@ -312,3 +905,89 @@ impl TaskRunnerWidgets {
         self.sidebar_revealer.set_reveal_child(false);
     }
 }
+
+/// Estimate the time left from the average duration of the finished steps.
+///
+/// `current_elapsed` is how long the running step has been going already.
+/// Returns None until [`MIN_STEPS_FOR_ESTIMATE`] steps have finished.
+fn estimate_remaining(
+    finished: &[Duration],
+    remaining_steps: usize,
+    current_elapsed: Duration,
+) -> Option<Duration> {
+    if finished.len() < MIN_STEPS_FOR_ESTIMATE || remaining_steps == 0 {
+        return None;
+    }
+
+    let average = finished.iter().sum::<Duration>() / finished.len() as u32;
+    let remaining = average.saturating_mul(remaining_steps as u32);
+    Some(remaining.saturating_sub(current_elapsed))
+}
+
+/// Format a duration as `42s`, `3m 05s` or `1h 02m`.
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs < 60 {
+        format!("{}s", secs)
+    } else if secs < 3600 {
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}h {:02}m", secs / 3600, (secs % 3600) / 60)
+    }
+}
+
+/// Fraction of the whole run that is done, weighting each step.
+///
+/// The first `completed` steps are done and the step after them is
+/// `step_fraction` done.
+fn weighted_fraction(weights: &[f64], completed: usize, step_fraction: f64) -> f64 {
+    let total: f64 = weights.iter().sum();
+    if total <= 0.0 {
+        return 0.0;
+    }
+
+    let done: f64 = weights.iter().take(completed).sum::<f64>()
+        + weights
+            .get(completed)
+            .map_or(0.0, |weight| weight * step_fraction.clamp(0.0, 1.0));
+    (done / total).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weighted_fraction() {
+        // Equal weights behave like counting steps
+        assert_eq!(weighted_fraction(&[1.0; 4], 1, 0.0), 0.25);
+        assert_eq!(weighted_fraction(&[1.0; 4], 1, 0.5), 0.375);
+        assert_eq!(weighted_fraction(&[1.0; 4], 4, 0.0), 1.0);
+
+        // A quick step barely moves the bar before a long one
+        assert_eq!(weighted_fraction(&[1.0, 8.0, 1.0], 1, 0.0), 0.1);
+        assert_eq!(weighted_fraction(&[1.0, 8.0, 1.0], 1, 0.5), 0.5);
+        assert_eq!(weighted_fraction(&[1.0, 8.0, 1.0], 2, 0.0), 0.9);
+
+        assert_eq!(weighted_fraction(&[], 0, 0.5), 0.0);
+        assert_eq!(weighted_fraction(&[0.0, 0.0], 1, 0.0), 0.0);
+    }
+
+    #[test]
+    fn test_estimate_remaining() {
+        let finished = [Duration::from_secs(10), Duration::from_secs(30)];
+        assert_eq!(
+            estimate_remaining(&finished, 3, Duration::from_secs(5)),
+            Some(Duration::from_secs(55))
+        );
+        assert_eq!(estimate_remaining(&finished[..1], 3, Duration::ZERO), None);
+        assert_eq!(estimate_remaining(&finished, 0, Duration::ZERO), None);
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_secs(42)), "42s");
+        assert_eq!(format_duration(Duration::from_secs(185)), "3m 05s");
+        assert_eq!(format_duration(Duration::from_secs(3720)), "1h 02m");
+    }
+}
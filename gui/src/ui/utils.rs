@@ -1,8 +1,10 @@
-//! UI utility functions for widget extraction.
+//! UI utility functions for widget extraction and button tooltips.
 
+use crate::ui::task_runner::CommandSequence;
+use anyhow::{anyhow, Context};
 use gtk4::glib;
 use gtk4::prelude::*;
-use gtk4::Builder;
+use gtk4::{Builder, Button};
 
 /// Helper to extract widgets from builder with consistent error handling.
 pub fn extract_widget<T: IsA<glib::Object>>(builder: &Builder, name: &str) -> T {
@@ -10,3 +12,46 @@ pub fn extract_widget<T: IsA<glib::Object>>(builder: &Builder, name: &str) -> T
         .object(name)
         .unwrap_or_else(|| panic!("Failed to get widget with id '{}'", name))
 }
+
+/// Load a builder from a bundled UI resource.
+///
+/// Unlike `Builder::from_resource`, a missing or invalid resource is
+/// returned as an error instead of aborting the application.
+pub fn load_builder(resource: &str) -> anyhow::Result<Builder> {
+    let builder = Builder::new();
+    builder
+        .add_from_resource(resource)
+        .with_context(|| format!("Failed to load UI resource {}", resource))?;
+    Ok(builder)
+}
+
+/// Get an object from a builder, naming it in the error if it is missing
+/// or has an unexpected type.
+pub fn try_extract_widget<T: IsA<glib::Object>>(
+    builder: &Builder,
+    name: &str,
+) -> anyhow::Result<T> {
+    let object = builder
+        .object::<glib::Object>(name)
+        .ok_or_else(|| anyhow!("UI object '{}' is missing", name))?;
+    object.downcast::<T>().map_err(|object| {
+        anyhow!(
+            "UI object '{}' is a {}, expected {}",
+            name,
+            object.type_().name(),
+            T::static_type().name()
+        )
+    })
+}
+
+/// Show the steps returned by `build` as the tooltip of `button`.
+///
+/// Pass the same function the click handler runs. The commands are rebuilt
+/// whenever the tooltip is shown, so it matches what a click would do.
+pub fn set_command_tooltip(button: &Button, build: impl Fn() -> CommandSequence + 'static) {
+    button.set_has_tooltip(true);
+    button.connect_query_tooltip(move |_, _, _, _, tooltip| {
+        tooltip.set_text(Some(&build().summary()));
+        true
+    });
+}
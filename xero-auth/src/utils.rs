@@ -2,6 +2,13 @@
 
 use std::io::{ErrorKind, Read};
 
+/// Read `reader` to the end, passing each line to `send_fn`.
+///
+/// Lines end at `\n`, `\r` or `\r\n` and are sent with a trailing `\n`, so
+/// progress bars redrawn with `\r` arrive as separate lines. Bytes that are
+/// not valid UTF-8 are replaced rather than ending the stream. Reading stops
+/// when `send_fn` returns false (the return value is then false) or on a
+/// read error, which is passed to `on_error`.
 pub fn read_buffer_with_line_processing<R, F, E>(
     mut reader: R,
     mut send_fn: F,
@@ -76,3 +83,44 @@ where
     acc.clear();
     send_fn(text)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_lines(input: &[u8]) -> Vec<String> {
+        let mut lines = Vec::new();
+        let completed = read_buffer_with_line_processing(
+            input,
+            |line| {
+                lines.push(line);
+                true
+            },
+            |e| panic!("unexpected read error: {}", e),
+        );
+        assert!(completed);
+        lines
+    }
+
+    #[test]
+    fn test_invalid_utf8_does_not_end_stream() {
+        let lines = read_lines(b"building\n\xff\xfe bad bytes\nstill going\r\ndone");
+        assert_eq!(
+            lines,
+            vec![
+                "building\n",
+                "\u{fffd}\u{fffd} bad bytes\n",
+                "still going\n",
+                "done",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_carriage_returns_split_lines() {
+        assert_eq!(
+            read_lines(b" 10%\r 50%\r100%\n"),
+            vec![" 10%\n", " 50%\n", "100%\n"]
+        );
+    }
+}